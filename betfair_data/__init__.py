@@ -1,4 +1,5 @@
 from .betfair_data import *
+from .betfair_data import _unpickle
 
 
 __doc__ = betfair_data.__doc__
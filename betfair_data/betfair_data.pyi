@@ -1,13 +1,73 @@
-from typing import Iterator, List, Sequence, Optional
+from typing import Any, Dict, Iterator, List, Sequence, Optional
 from datetime import datetime
 
 class File(Iterator[Market]):
-    def __init__(self, path: str, bytes: bytes, cumulative_runner_tv: bool = True, mutable: bool = False) -> None: ...
+    def __init__(self, path: str, bytes: bytes, cumulative_runner_tv: bool = True, mutable: bool = False, changes: bool = False, ladder_depth: Optional[int] = None, heartbeats: bool = False) -> None: ...
     file_name: str
 
+    def definition_history(self) -> List[DefinitionChange]: ...
+    """Every version of each market's definition in the file, with the fields that changed from the version before"""
+    def lifecycle_events(self) -> List[LifecycleEvent]: ...
+    """Runner removals and reinstatements, in play, suspensions, bsp reconciliation and settlement, in the order they happened. Reads whatever is left of the file to find them, so iterating the file afterwards yields nothing more"""
+
 class Files(Iterator[File]):
     """"""
-    def __init__(self, paths: Sequence[str], cumulative_runner_tv: bool = True, mutable: bool = False) -> None: ...
+    def __init__(self, paths: Sequence[str], cumulative_runner_tv: bool = True, mutable: bool = False, changes: bool = False, ladder_depth: Optional[int] = None, heartbeats: bool = False, split_markets: bool = False) -> None: ...
+
+class Issue():
+    """A data quality problem found by validate, market_id, selection_id, handicap and publish_time are set when the problem belongs to a market, runner or update"""
+    file: str
+    line: Optional[int]
+    kind: str
+    market_id: Optional[str]
+    selection_id: Optional[int]
+    handicap: Optional[float]
+    publish_time: Optional[int]
+    message: str
+
+class DefinitionChange():
+    """A version of a market's definition. changed names the fields that differ from the previous version, runner fields as runners.<selection_id>.<field>, and is empty for a market's first definition"""
+    market_id: str
+    version: int
+    publish_time: Optional[datetime]
+    changed: List[str]
+    definition: Dict[str, Any]
+
+class LifecycleEvent():
+    """
+    A point in the life of a market or runner, kind is one of runner_removed, runner_reinstated, in_play, suspended, resumed, bsp_reconciled or settled.
+    selection_id and handicap are set for runner events, removals also have the reduction factor, removal date and the runner as it was before removal (a RunnerMut on a mutable File), and winners is set when a market settles
+    """
+    kind: str
+    market_id: str
+    publish_time: datetime
+    selection_id: Optional[int]
+    handicap: Optional[float]
+    adjustment_factor: Optional[float]
+    removal_date: Optional[datetime]
+    runner: Optional[Runner]
+    winners: List[int]
+
+def validate(paths: Sequence[str]) -> List[Issue]:
+    """Replay stream files and report data quality issues, kind is one of io_error, parse_error, truncated, non_monotonic_pt, missing_definition, version_regression, unknown_runner, invalid_size, crossed_book, traded_volume_decreased or not_closed"""
+
+class StreamWriter():
+    """
+    Writes markets out as Betfair historic stream lines, compressed as bz2 or gz by default
+    when the file name ends in .bz2 or .gz.
+            >>> with bfd.StreamWriter("out.bz2") as w:
+            ...     for market in bfd.File("in.bz2", bs):
+            ...         w.write_market(market)
+    """
+    def __init__(self, file: str, compression: Optional[str] = None) -> None: ...
+    file_name: str
+    def write_market(self, market: Any, publish_time: Optional[int] = None, clk: Optional[str] = None) -> None:
+        """Write a full image (img: true) of a Market, MarketMut or bflw MarketBook, publish_time and clk default to the market's own"""
+    def write_changes(self, publish_time: int, mc: Any, clk: Optional[str] = None) -> None:
+        """Write raw mc deltas, given as a json string or json serialisable objects, either one mc entry or a list of them"""
+    def close(self) -> None: ...
+    def __enter__(self) -> StreamWriter: ...
+    def __exit__(self, *args: Any) -> bool: ...
 
 class Market():
     """
@@ -76,9 +136,35 @@ class Market():
     turn_in_play_enabled: bool
     venue: Optional[str]
     version: int
+    back_overround: float
+    """Sum of the implied probabilities of the best back price of each active runner"""
+    lay_overround: float
+    """Sum of the implied probabilities of the best lay price of each active runner"""
+    implied_probability: float
+    """Sum of the implied probabilities of the last traded price of each active runner"""
+    changes: Optional[MarketChange]
+    """What changed in the update that produced this market, only set for immutable markets when changes=True"""
+    change_type: Optional[str]
+    """The `ct` of the stream message that carried the update, one of ["SUB_IMAGE", "RESUB_DELTA", "HEARTBEAT"] or None for a regular delta.
+    HEARTBEAT updates are only yielded when heartbeats=True, and re-yield every open market unchanged apart from publish_time and clk"""
+    conflated: bool
+    """If the update was conflated (`con`) by the stream"""
+    stream_status: Optional[int]
+    """The `status` of the stream message, None when healthy or 503 if the stream was unreliable"""
+    last_trade_time: Optional[datetime]
+    """The publish time of the last update where any runner traded"""
 
     def copy(self) -> Market: ...
-    """Performs a deep copy if mutable, or reference copy if immutable"""
+    """Performs a deep copy if mutable, or reference copy if immutable, copy.copy and copy.deepcopy do the same.
+    Immutable markets can also be pickled"""
+    def runner_matrix(self) -> Any: ...
+    """A (runners, 5) float64 numpy array with a row per runner of
+    [selection_id, last_price_traded, total_matched, best_back, best_lay], missing values are nan.
+    Requires numpy to be installed."""
+    def to_json(self) -> str: ...
+    """A full image of the market as a Betfair stream `mc` entry (img: true) with its marketDefinition and runner ladders"""
+    def to_dict(self) -> Dict[str, Any]: ...
+    """to_json parsed into python objects"""
 
 
 class Runner():
@@ -100,11 +186,41 @@ class Runner():
     removal_date: Optional[int]
     ex: RunnerBookEX
     sp: RunnerBookSP
+    implied_probability: Optional[float]
+    """1 / last_price_traded, or None if the runner hasn't traded"""
+    traded_volume_delta: List[PriceSize]
+    """The size traded at each price since the previous update, empty if the runner didn't trade"""
+    total_matched_delta: float
+    """The change in total_matched since the previous update"""
+    is_crossed: bool
+    """If the best back price is at or above the best lay price"""
+    last_update_time: Optional[datetime]
+    """The publish time of the last update that changed the runner's atb, atl or trd ladders"""
 
 class RunnerBookEX():
     available_to_back: List[PriceSize]
+    """Truncated to the ladder_depth best levels when set"""
     available_to_lay: List[PriceSize]
+    """Truncated to the ladder_depth best levels when set"""
     traded_volume: List[PriceSize]
+    available_to_back_array: Any
+    """A read-only (n, 2) float64 numpy array of [price, size] rows. Requires numpy to be installed"""
+    available_to_lay_array: Any
+    """A read-only (n, 2) float64 numpy array of [price, size] rows. Requires numpy to be installed"""
+    traded_volume_array: Any
+    """A read-only (n, 2) float64 numpy array of [price, size] rows. Requires numpy to be installed"""
+    best_back: Optional[PriceSize]
+    best_lay: Optional[PriceSize]
+    spread: Optional[int]
+    """Number of ticks between the best back and best lay prices"""
+    wap: Optional[float]
+    """Volume weighted average traded price"""
+
+    def volume_at_price(self, price: float) -> float: ...
+    def back_depth(self, ticks: int = 3) -> float: ...
+    """Total size available to back within ticks of the best back price"""
+    def lay_depth(self, ticks: int = 3) -> float: ...
+    """Total size available to lay within ticks of the best lay price"""
 
 class RunnerBookSP(): 
     far_price: Optional[float]
@@ -113,6 +229,36 @@ class RunnerBookSP():
     back_stake_taken: List[PriceSize]
     lay_liability_taken: List[PriceSize]
 
+class MarketChange():
+    """
+    The changes carried by a single market update.
+    """
+    definition: bool
+    """Did the market definition change"""
+    definition_fields: List[str]
+    """The names of the market fields that changed"""
+    status: Optional[str]
+    """The new market status, or None if it didn't change"""
+    total_matched: Optional[float]
+    runners: List[RunnerChange]
+    """The runners the update sent changes for or changed the status of"""
+
+class RunnerChange():
+    """
+    The changes to a single runner. Values are None if the update didn't set them, and ladders contain
+    the levels the update sent, in the order it sent them, a removed level has a size of 0.
+    """
+    selection_id: int
+    handicap: Optional[float]
+    status: Optional[str]
+    last_price_traded: Optional[float]
+    total_matched: Optional[float]
+    available_to_back: List[PriceSize]
+    available_to_lay: List[PriceSize]
+    traded_volume: List[PriceSize]
+    back_stake_taken: List[PriceSize]
+    lay_liability_taken: List[PriceSize]
+
 class PriceSize():
     price: float
     size: float
@@ -1,16 +1,48 @@
 from datetime import datetime
-from typing import Iterator, Optional, Sequence, List, str
-from betfair_data import RunnerBookEX as RunnerBookEX, RunnerBookSP as RunnerBookSP, PriceSize as PriceSize
+from typing import Any, Dict, Iterator, Optional, Sequence, List, Union, str
+from betfair_data import RunnerBookEX as RunnerBookEX, RunnerBookSP as RunnerBookSP, PriceSize as PriceSize, DefinitionChange as DefinitionChange
 
 class File(Iterator[Sequence[MarketBook]]):
     file_name: str
     stream_unique_id: Optional[int]
+    clk: Optional[str]
+    initial_clk: Optional[str]
     
-    def __init__(self, path: str, bytes: bytes, cumulative_runner_tv: bool = True, streaming_unique_id: Optional[int] = None) -> None: ...
+    def __init__(self, path: str, bytes: bytes, cumulative_runner_tv: bool = True, calculate_market_tv: Optional[bool] = None, streaming_unique_id: Optional[int] = None, calculate_totals: bool = False, update_clk: bool = True, max_latency: Optional[float] = None) -> None: ...
+    def definition_history(self) -> List[DefinitionChange]: ...
+    """Every version of each market's definition in the file, with the fields that changed from the version before"""
 
 class Files(Iterator[File]):
     """"""
-    def __init__(self, paths: Sequence[str], cumulative_runner_tv: bool = True, streaming_unique_id: Optional[int] = None) -> None: ...
+    def __init__(self, paths: Sequence[str], cumulative_runner_tv: bool = True, calculate_market_tv: Optional[bool] = None, streaming_unique_id: Optional[int] = None, split_markets: bool = False, calculate_totals: bool = False, update_clk: bool = True, max_latency: Optional[float] = None) -> None: ...
+
+def create_historical_generator_stream(file_path: str, listener_options: Optional[Dict[str, Any]] = None, operation: str = "marketSubscription", unique_id: int = 0) -> HistoricalGeneratorStream: ...
+"""Reads a historic file like betfairlightweight's create_historical_generator_stream. listener_options are the StreamListener's arguments, with the same defaults"""
+
+class HistoricalGeneratorStream:
+    file_path: str
+
+    def get_generator(self) -> HistoricalGeneratorStream: ...
+    def __call__(self) -> Iterator[List[MarketBook]]: ...
+
+class Cache:
+    """The market cache of a StreamListener, fed stream lines or files and queried with snap/get"""
+    clk: Optional[str]
+    initial_clk: Optional[str]
+    stream_unique_id: Optional[int]
+
+    def __init__(self, cumulative_runner_tv: bool = True, calculate_market_tv: Optional[bool] = None, streaming_unique_id: Optional[int] = None, calculate_totals: bool = False, update_clk: bool = True, max_latency: Optional[float] = None) -> None: ...
+    def on_data(self, data: Union[str, bytes]) -> List[MarketBook]: ...
+    """Applies one or more stream lines, returning the updated books in order"""
+    def on_files(self, paths: Sequence[str]) -> List[MarketBook]: ...
+    """Applies whole stream files, archives and compressed files included"""
+    def snap(self, market_ids: Optional[Sequence[str]] = None) -> List[MarketBook]: ...
+    def get(self, market_id: str) -> Optional[MarketBook]: ...
+    def clear_closed(self) -> int: ...
+    """Drops closed markets, returning how many were removed"""
+    def clear(self) -> None: ...
+    def __len__(self) -> int: ...
+    def __contains__(self, market_id: str) -> bool: ...
 
 class MarketBook:
     streaming_unique_id: Optional[int]
@@ -21,7 +53,8 @@ class MarketBook:
     inplay: bool
     is_market_data_delayed: bool
     key_line_description: Optional[MarketDefinitionKeyLine]
-    last_match_time: datetime
+    last_match_time: Optional[datetime]
+    """The publish time of the last update that matched, only set with calculate_totals=True"""
     market_definition: MarketDefinition
     market_id: str
     number_of_active_runners: int
@@ -33,10 +66,18 @@ class MarketBook:
     runners_voidable: bool
     runners: List[RunnerBook]
     status: str
-    total_available: float
+    total_available: Optional[float]
+    """The sum of every atb and atl size across the runners, only set with calculate_totals=True"""
     total_matched: float
     version: int
 
+    def to_json(self) -> str: ...
+    """A full image of the market as a Betfair stream `mc` entry (img: true) with its marketDefinition and runner ladders"""
+    def to_dict(self) -> Dict[str, Any]: ...
+    """to_json parsed into python objects"""
+    def market_catalogue(self) -> MarketCatalogue: ...
+    """A betfairlightweight style MarketCatalogue filled in from the market definition"""
+
 class RunnerBook:
     adjustment_factor: float
     ex: RunnerBookEX
@@ -107,3 +148,53 @@ class MarketDefinitionKeyLine:
 class MarketDefinitionKeyLineSelection:
     handicap: float
     selection_id: int
+
+class MarketCatalogue:
+    market_id: str
+    market_name: Optional[str]
+    market_start_time: datetime
+    total_matched: float
+    competition: None
+    description: MarketCatalogueDescription
+    event: Event
+    event_type: EventType
+    runners: List[RunnerCatalogue]
+
+class MarketCatalogueDescription:
+    betting_type: str
+    bsp_market: bool
+    clarifications: None
+    discount_allowed: bool
+    each_way_divisor: Optional[float]
+    market_base_rate: float
+    market_time: datetime
+    line_range_info: None
+    market_type: str
+    persistence_enabled: bool
+    price_ladder_description: Optional[PriceLadderDescription]
+    race_type: Optional[str]
+    regulator: Optional[str]
+    rules: None
+    rules_has_date: None
+    suspend_time: Optional[datetime]
+    turn_in_play_enabled: bool
+    wallet: None
+
+class Event:
+    id: str
+    name: Optional[str]
+    country_code: Optional[str]
+    time_zone: str
+    venue: Optional[str]
+    open_date: datetime
+
+class EventType:
+    id: str
+    name: None
+
+class RunnerCatalogue:
+    selection_id: int
+    runner_name: Optional[str]
+    sort_priority: int
+    handicap: float
+    metadata: Dict[str, Any]
@@ -0,0 +1,49 @@
+from typing import Sequence
+import tarfile
+import bz2
+import time
+import logging
+import betfair_data as bfd
+from betfair_data import bflw
+
+logging.basicConfig(level=logging.WARN, format='%(levelname)s %(name)s %(message)s')
+
+# times only the stream parsing, each file is decompressed before its clock starts, as bz2
+# takes most of the time in the other benchmarks
+
+paths = [
+    "data/2021_10_OctRacingAUPro.tar",
+    "data/2021_11_NovRacingAUPro.tar",
+    "data/2021_12_DecRacingAUPro.tar",
+]
+
+def load_tar(file_paths: Sequence[str]):
+    for file_path in file_paths:
+        with tarfile.TarFile(file_path) as archive:
+            for file in archive:
+                if file.isfile():
+                    yield file.name, bz2.decompress(archive.extractfile(file).read())
+
+def immutable(name: str, bs: bytes) -> int:
+    return sum(1 for _ in bfd.File(name, bs, cumulative_runner_tv=True, mutable=False))
+
+def mutable(name: str, bs: bytes) -> int:
+    return sum(1 for _ in bfd.File(name, bs, cumulative_runner_tv=True, mutable=True))
+
+def bflw_books(name: str, bs: bytes) -> int:
+    return sum(len(books) for books in bflw.File(name, bs))
+
+readers = [immutable, mutable, bflw_books]
+elapsed = {r.__name__: 0.0 for r in readers}
+update_count = 0
+
+for name, bs in load_tar(paths):
+    for reader in readers:
+        start = time.perf_counter()
+        updates = reader(name, bs)
+        elapsed[reader.__name__] += time.perf_counter() - start
+    update_count += updates
+
+print(f"Updates {update_count}")
+for reader, secs in elapsed.items():
+    print(f"{reader}: {secs:.2f}s")
@@ -0,0 +1,79 @@
+import bz2
+import io
+import json
+import random
+import sys
+import tarfile
+
+# Writes a tar of bz2 stream files shaped like the betfair PRO historic data, for running the
+# benchmarks without the real archives. python py_src/benchmarks/synthetic_data.py out.tar [markets] [updates]
+
+TICKS = [round(p, 2) for p in
+         [1.01 + i * 0.01 for i in range(99)] + [2.0 + i * 0.02 for i in range(50)] +
+         [3.0 + i * 0.05 for i in range(20)] + [4.0 + i * 0.1 for i in range(20)] +
+         [6.0 + i * 0.2 for i in range(20)] + [10.0 + i * 0.5 for i in range(20)]]
+
+
+def definition(market_id, runners, status, in_play, version):
+    return {
+        "bspMarket": True, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+        "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+        "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+        "bspReconciled": in_play, "complete": True, "inPlay": in_play, "crossMatching": True, "runnersVoidable": False,
+        "numberOfActiveRunners": len(runners), "betDelay": 1 if in_play else 0, "status": status, "regulators": ["MR_INT"],
+        "venue": "Test Park", "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+        "openDate": "2021-12-01T03:00:00.000Z", "version": version, "name": "R1 1000m", "eventName": "Test Park (AUS) 1st Dec",
+        "priceLadderDefinition": {"type": "CLASSIC"},
+        "runners": [{"status": "ACTIVE", "sortPriority": i + 1, "id": sid, "name": f"{i + 1}. Runner {sid}"} for i, sid in enumerate(runners)],
+    }
+
+
+def market_file(rng, n, updates):
+    market_id = f"1.19{n:07d}"
+    runners = [30000000 + n * 20 + i for i in range(rng.randint(6, 14))]
+    mid = {sid: rng.randrange(20, len(TICKS) - 20) for sid in runners}
+    traded = {sid: {} for sid in runners}
+    pt = 1638320000000 + n * 10_000_000
+
+    lines = [{"op": "mcm", "clk": "1", "pt": pt, "mc": [{"id": market_id, "marketDefinition": definition(market_id, runners, "OPEN", False, 1)}]}]
+    for u in range(updates):
+        pt += rng.randint(50, 2000)
+        rc = []
+        for sid in rng.sample(runners, rng.randint(1, 3)):
+            mid[sid] = max(3, min(len(TICKS) - 4, mid[sid] + rng.choice((-1, 0, 0, 1))))
+            change = {
+                "atb": [[TICKS[mid[sid] - i], round(rng.uniform(0, 300), 2) if rng.random() > 0.1 else 0] for i in range(rng.randint(1, 3))],
+                "atl": [[TICKS[mid[sid] + 1 + i], round(rng.uniform(0, 300), 2) if rng.random() > 0.1 else 0] for i in range(rng.randint(0, 2))],
+            }
+            if rng.random() < 0.3:
+                price = TICKS[mid[sid]]
+                traded[sid][price] = round(traded[sid].get(price, 0) + rng.uniform(1, 50), 2)
+                change["trd"] = [[price, traded[sid][price]]]
+                change["ltp"] = price
+                change["tv"] = round(sum(traded[sid].values()), 2)
+            change["id"] = sid
+            rc.append(change)
+        lines.append({"op": "mcm", "clk": str(u + 2), "pt": pt, "mc": [{"id": market_id, "rc": rc}]})
+        if u == updates * 9 // 10:
+            lines.append({"op": "mcm", "clk": str(u + 2), "pt": pt, "mc": [{"id": market_id, "marketDefinition": definition(market_id, runners, "OPEN", True, 2)}]})
+    closed = definition(market_id, runners, "CLOSED", True, 3)
+    for i, r in enumerate(closed["runners"]):
+        r["status"] = "WINNER" if i == 0 else "LOSER"
+    lines.append({"op": "mcm", "clk": str(updates + 3), "pt": pt + 1000, "mc": [{"id": market_id, "marketDefinition": closed}]})
+
+    return market_id, "\n".join(json.dumps(l, separators=(",", ":")) for l in lines).encode()
+
+
+if __name__ == "__main__":
+    out = sys.argv[1]
+    markets = int(sys.argv[2]) if len(sys.argv) > 2 else 200
+    updates = int(sys.argv[3]) if len(sys.argv) > 3 else 5000
+    rng = random.Random(0)
+
+    with tarfile.open(out, "w") as archive:
+        for n in range(markets):
+            market_id, data = market_file(rng, n, updates)
+            bs = bz2.compress(data)
+            info = tarfile.TarInfo(f"PRO/2021/Dec/1/31091000/{market_id}.bz2")
+            info.size = len(bs)
+            archive.addfile(info, io.BytesIO(bs))
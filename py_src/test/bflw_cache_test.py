@@ -0,0 +1,72 @@
+import json
+from betfair_data import bflw as bfd_bflw
+
+# Cache keeps the latest book of each market as lines are fed to it
+
+def definition(status: str):
+    return {
+        "bspMarket": False, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+        "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+        "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+        "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+        "numberOfActiveRunners": 1, "betDelay": 0, "status": status, "regulators": ["MR_INT"],
+        "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+        "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+        "runners": [{"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"}],
+    }
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [
+        {"id": "1.190000001", "img": True, "marketDefinition": definition("OPEN"), "rc": [{"id": 101, "atb": [[2.0, 10]]}]},
+        {"id": "1.190000002", "img": True, "marketDefinition": definition("OPEN"), "rc": [{"id": 101, "atb": [[3.0, 10]]}]},
+    ]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [
+        {"id": "1.190000001", "rc": [{"id": 101, "atb": [[2.0, 5]]}]},
+    ]},
+    {"op": "mcm", "clk": "3", "pt": 1638320002000, "mc": [
+        {"id": "1.190000002", "marketDefinition": definition("CLOSED")},
+    ]},
+]
+
+lines = [json.dumps(m) for m in stream]
+
+
+def test_cache():
+    cache = bfd_bflw.Cache(cumulative_runner_tv=False)
+    assert len(cache) == 0 and cache.snap() == []
+
+    updated = cache.on_data(lines[0])
+    assert [mb.market_id for mb in updated] == ["1.190000001", "1.190000002"]
+    assert cache.clk == "1"
+
+    updated = cache.on_data(lines[1].encode())
+    assert [mb.market_id for mb in updated] == ["1.190000001"]
+    assert cache.get("1.190000001").runners[0].ex.available_to_back[0].size == 5
+    assert cache.get("1.190000003") is None
+
+    assert [mb.market_id for mb in cache.snap()] == ["1.190000001", "1.190000002"]
+    assert [mb.market_id for mb in cache.snap(market_ids=["1.190000002", "1.190000003"])] == ["1.190000002"]
+
+    cache.on_data(lines[2])
+    assert cache.get("1.190000002").status == "CLOSED"
+    assert cache.clear_closed() == 1
+    assert [mb.market_id for mb in cache.snap()] == ["1.190000001"]
+    assert "1.190000002" not in cache and "1.190000001" in cache
+
+
+def test_matches_file():
+    data = "\n".join(lines)
+    books = [mb for mbs in bfd_bflw.File("cache.json", data.encode(), cumulative_runner_tv=False) for mb in mbs]
+
+    cache = bfd_bflw.Cache(cumulative_runner_tv=False)
+    updated = cache.on_data(data)
+
+    # a File yields every market on each line, the cache only hands back the ones the line updated
+    assert [mb.market_id for mb in updated] == ["1.190000001", "1.190000002", "1.190000001", "1.190000002"]
+    assert [mb.to_json() for mb in cache.snap()] == [mb.to_json() for mb in books[-2:]]
+
+
+if __name__ == "__main__":
+    test_cache()
+    test_matches_file()
+    print("bflw cache tests passed")
@@ -0,0 +1,65 @@
+import json
+from datetime import datetime
+from betfair_data import bflw as bfd_bflw
+
+# market_catalogue() builds the betfairlightweight catalogue objects from the definition
+
+definition = {
+    "bspMarket": True, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 7,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 2, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"], "venue": "Randwick",
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney", "raceType": "Flat",
+    "openDate": "2021-12-01T02:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Rand 1st Dec",
+    "runners": [
+        {"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "1. One"},
+        {"status": "ACTIVE", "sortPriority": 2, "id": 102, "name": "2. Two", "hc": 1.5},
+    ],
+}
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "tv": 12, "marketDefinition": definition}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def test_catalogue():
+    book = [mb for mbs in bfd_bflw.File("catalogue.json", data, cumulative_runner_tv=False) for mb in mbs][-1]
+    cat = book.market_catalogue()
+
+    assert cat.market_id == "1.190000000"
+    assert cat.market_name == "R1 1000m"
+    assert cat.market_start_time == datetime(2021, 12, 1, 3)
+    assert cat.total_matched == 12
+    assert cat.competition is None
+
+    assert cat.event.id == "31091000"
+    assert cat.event.name == "Rand 1st Dec"
+    assert cat.event.country_code == "AU"
+    assert cat.event.time_zone == "Australia/Sydney"
+    assert cat.event.venue == "Randwick"
+    assert cat.event.open_date == datetime(2021, 12, 1, 2)
+    assert cat.event_type.id == "7"
+    assert cat.event_type.name is None
+
+    assert cat.description.market_type == "WIN"
+    assert cat.description.betting_type == "ODDS"
+    assert cat.description.bsp_market
+    assert cat.description.market_base_rate == 7
+    assert cat.description.regulator == "MR_INT"
+    assert cat.description.race_type == "Flat"
+    assert cat.description.rules is None
+    assert cat.description.clarifications is None and cat.description.line_range_info is None
+
+    assert [r.selection_id for r in cat.runners] == [101, 102]
+    assert [r.runner_name for r in cat.runners] == ["1. One", "2. Two"]
+    assert [r.sort_priority for r in cat.runners] == [1, 2]
+    assert [r.handicap for r in cat.runners] == [0, 1.5]
+    assert cat.runners[0].metadata == {}
+
+
+if __name__ == "__main__":
+    test_catalogue()
+    print("bflw catalogue tests passed")
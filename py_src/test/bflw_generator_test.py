@@ -0,0 +1,75 @@
+import bz2
+import json
+import os
+import tempfile
+from betfair_data import bflw as bfd_bflw
+
+# create_historical_generator_stream reads a file the same way as bflw.File, with the
+# StreamListener's defaults
+
+definition = {
+    "bspMarket": False, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 1, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+    "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+    "runners": [{"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"}],
+}
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "tv": 20, "marketDefinition": definition, "rc": [
+        {"id": 101, "tv": 20, "atb": [[2.0, 10]], "trd": [[2.0, 5]]},
+    ]}]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "tv": 30, "rc": [
+        {"id": 101, "tv": 30, "trd": [[2.0, 15]]},
+    ]}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def test_generator():
+    with tempfile.TemporaryDirectory() as dir:
+        path = os.path.join(dir, "1.190000000.bz2")
+        with open(path, "wb") as f:
+            f.write(bz2.compress(data))
+
+        stream = bfd_bflw.create_historical_generator_stream(file_path=path, listener_options={"max_latency": None})
+        gen = stream.get_generator()
+        updates = list(gen())
+
+        assert [len(mbs) for mbs in updates] == [1, 1]
+        assert [mbs[0].total_matched for mbs in updates] == [20, 30]
+        assert [mbs[0].streaming_unique_id for mbs in updates] == [0, 0]
+
+        file = bfd_bflw.File(path, data, cumulative_runner_tv=False, max_latency=None)
+        assert [[mb.to_json() for mb in mbs] for mbs in updates] == [[mb.to_json() for mb in mbs] for mbs in file]
+
+        updates = list(stream.get_generator()())
+        assert [mbs[0].total_matched for mbs in updates] == [20, 30]
+
+        updates = list(bfd_bflw.create_historical_generator_stream(path, {"cumulative_runner_tv": True, "calculate_market_tv": True, "max_latency": None}).get_generator()())
+        assert [mbs[0].total_matched for mbs in updates] == [5, 15]
+
+
+def test_bad_options():
+    for options in [{"lightweight": True}, {"not_an_option": 1}]:
+        try:
+            bfd_bflw.create_historical_generator_stream("missing.bz2", options)
+            assert False, options
+        except (TypeError, ValueError):
+            pass
+
+    try:
+        bfd_bflw.create_historical_generator_stream("missing.bz2").get_generator()()
+        assert False
+    except FileNotFoundError:
+        pass
+
+
+if __name__ == "__main__":
+    test_generator()
+    test_bad_options()
+    print("bflw generator tests passed")
@@ -0,0 +1,69 @@
+import json
+from betfair_data import bflw as bfd_bflw
+
+# the StreamListener options, calculate_market_tv follows cumulative_runner_tv unless given
+
+definition = {
+    "bspMarket": False, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 2, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+    "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+    "runners": [
+        {"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"},
+        {"status": "ACTIVE", "sortPriority": 2, "id": 102, "name": "Two"},
+    ],
+}
+
+stream = [
+    {"op": "mcm", "initialClk": "10", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "tv": 100, "marketDefinition": definition, "rc": [
+        {"id": 101, "tv": 50, "trd": [[2.0, 5]]},
+        {"id": 102, "tv": 50, "trd": [[3.0, 7]]},
+    ]}]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "tv": 110, "rc": [
+        {"id": 101, "tv": 60, "trd": [[2.0, 15]]},
+    ]}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def last_book(**kwargs):
+    return [mb for mbs in bfd_bflw.File("listener.json", data, **kwargs) for mb in mbs][-1]
+
+
+def test_market_tv():
+    book = last_book()
+    assert book.total_matched == 22
+    assert [r.total_matched for r in book.runners] == [15, 7]
+
+    book = last_book(cumulative_runner_tv=False)
+    assert book.total_matched == 110
+    assert [r.total_matched for r in book.runners] == [60, 50]
+
+    book = last_book(cumulative_runner_tv=True, calculate_market_tv=False)
+    assert book.total_matched == 110
+    assert [r.total_matched for r in book.runners] == [15, 7]
+
+
+def test_update_clk():
+    file = bfd_bflw.File("listener.json", data)
+    assert file.clk is None and file.initial_clk is None
+
+    next(file)
+    assert file.clk == "1" and file.initial_clk == "10"
+    next(file)
+    assert file.clk == "2" and file.initial_clk == "10"
+
+    file = bfd_bflw.File("listener.json", data, update_clk=False)
+    for _ in file:
+        pass
+    assert file.clk is None and file.initial_clk is None
+
+
+if __name__ == "__main__":
+    test_market_tv()
+    test_update_clk()
+    print("bflw listener tests passed")
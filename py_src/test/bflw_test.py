@@ -23,7 +23,7 @@ paths = glob("data/stream/*")
 #     "data/2021_11_NovRacingAUPro.tar",
 # ]
 
-bfd_source = bfd_bflw.Files(paths, cumulative_runner_tv=False)
+bfd_source = bfd_bflw.Files(paths, cumulative_runner_tv=False, calculate_market_tv=False, update_clk=False)
 
 def bflw_source(file_paths: Sequence[str]):
     trading = betfairlightweight.APIClient("username", "password", "appkey")
@@ -0,0 +1,76 @@
+import json
+from datetime import datetime
+from betfair_data import bflw as bfd_bflw
+
+# total_available and last_match_time are only filled in with calculate_totals
+
+definition = {
+    "bspMarket": False, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 2, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+    "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+    "runners": [
+        {"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"},
+        {"status": "ACTIVE", "sortPriority": 2, "id": 102, "name": "Two"},
+    ],
+}
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition, "rc": [
+        {"id": 101, "atb": [[2.0, 10], [1.99, 5]], "atl": [[2.02, 5]], "trd": [[2.0, 5]]},
+        {"id": 102, "atb": [[3.0, 4]]},
+    ]}]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 102, "atl": [[3.1, 2.5]]},
+    ]}]},
+    {"op": "mcm", "clk": "3", "pt": 1638320002000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 101, "atb": [[2.0, 0]], "trd": [[2.0, 15]]},
+    ]}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def time(ts: int):
+    return datetime.utcfromtimestamp(ts / 1000)
+
+
+def test_totals():
+    books = [mb for mbs in bfd_bflw.File("totals.json", data, calculate_totals=True) for mb in mbs]
+    assert len(books) == 3
+
+    assert books[0].total_available == 24
+    assert books[0].last_match_time == time(1638320000000)
+
+    assert books[1].total_available == 26.5
+    assert books[1].last_match_time == time(1638320000000)
+
+    assert books[2].total_available == 16.5
+    assert books[2].last_match_time == time(1638320002000)
+
+
+def test_without_market_tv():
+    # no tv is sent or calculated, the match times come from the runners' traded ladders alone
+    books = [mb for mbs in bfd_bflw.File("totals.json", data, calculate_totals=True, cumulative_runner_tv=False, calculate_market_tv=False) for mb in mbs]
+
+    assert all(mb.total_matched == 0 for mb in books)
+    assert books[0].last_match_time == time(1638320000000)
+    assert books[1].last_match_time == time(1638320000000)
+    assert books[2].last_match_time == time(1638320002000)
+
+
+def test_off_by_default():
+    books = [mb for mbs in bfd_bflw.File("totals.json", data) for mb in mbs]
+
+    assert all(mb.total_available is None for mb in books)
+    assert all(mb.last_match_time is None for mb in books)
+
+
+if __name__ == "__main__":
+    test_totals()
+    test_without_market_tv()
+    test_off_by_default()
+    print("bflw totals tests passed")
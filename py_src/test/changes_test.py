@@ -0,0 +1,68 @@
+import json
+import betfair_data as bfd
+
+# change sets hold what each update sent, not what differs between two markets
+
+def definition(version: int, status: str = "OPEN", two: str = "ACTIVE"):
+    return {
+        "bspMarket": False, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+        "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+        "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+        "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+        "numberOfActiveRunners": 2, "betDelay": 0, "status": status, "regulators": ["MR_INT"],
+        "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+        "openDate": "2021-12-01T03:00:00.000Z", "version": version, "name": "R1 1000m", "eventName": "Test",
+        "runners": [
+            {"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"},
+            {"status": two, "sortPriority": 2, "id": 102, "name": "Two"},
+        ],
+    }
+
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition(1), "rc": [
+        {"id": 101, "atb": [[2.0, 10]], "trd": [[2.0, 5]], "ltp": 2.0},
+    ]}]},
+    # the same size sent again for a level is still a change the stream made
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 101, "atb": [[2.0, 10], [1.99, 0]]},
+    ]}]},
+    {"op": "mcm", "clk": "3", "pt": 1638320002000, "mc": [{"id": "1.190000000", "marketDefinition": definition(2, "SUSPENDED", "REMOVED")}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def levels(ps):
+    return [(p.price, p.size) for p in ps]
+
+
+def test_changes():
+    changes = [m.changes for m in bfd.File("changes.json", data, changes=True)]
+
+    first = changes[0]
+    assert first.definition_fields == ["market_definition"]
+    assert first.status == "OPEN"
+    assert {rc.selection_id: rc.status for rc in first.runners} == {101: "ACTIVE", 102: "ACTIVE"}
+    one = next(rc for rc in first.runners if rc.selection_id == 101)
+    assert levels(one.available_to_back) == [(2.0, 10)]
+    assert levels(one.traded_volume) == [(2.0, 5)]
+    assert one.last_price_traded == 2.0
+    assert one.total_matched == 5
+
+    resent = changes[1]
+    assert not resent.definition and resent.status is None
+    assert [rc.selection_id for rc in resent.runners] == [101]
+    assert levels(resent.runners[0].available_to_back) == [(2.0, 10), (1.99, 0)]
+    assert resent.runners[0].status is None and resent.runners[0].total_matched is None
+
+    removed = changes[2]
+    assert set(removed.definition_fields) == {"status", "version"}
+    assert removed.status == "SUSPENDED"
+    assert [(rc.selection_id, rc.status) for rc in removed.runners] == [(102, "REMOVED")]
+    assert removed.runners[0].available_to_back == []
+
+
+if __name__ == "__main__":
+    test_changes()
+    print("changes tests passed")
@@ -0,0 +1,78 @@
+import json
+import betfair_data as bfd
+
+# crossed ladders and the update times of runners and trades
+
+definition = {
+    "bspMarket": False, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 2, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+    "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+    "runners": [
+        {"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"},
+        {"status": "ACTIVE", "sortPriority": 2, "id": 102, "name": "Two"},
+    ],
+}
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition, "rc": [
+        {"id": 101, "atb": [[2.0, 10]], "atl": [[2.02, 5]], "trd": [[2.0, 5]], "tv": 5},
+        {"id": 102, "atb": [[3.0, 4]]},
+    ]}]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 101, "atl": [[2.02, 0], [2.0, 3]]},
+    ]}]},
+    {"op": "mcm", "clk": "3", "pt": 1638320002000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 102, "ltp": 3.0},
+    ]}]},
+    {"op": "mcm", "clk": "4", "pt": 1638320003000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 101, "atl": [[2.0, 0], [2.04, 1]], "trd": [[2.0, 8]], "tv": 8},
+    ]}]},
+    # pt written after mc
+    {"op": "mcm", "clk": "5", "mc": [{"id": "1.190000000", "rc": [
+        {"id": 102, "atl": [[3.2, 2]], "trd": [[3.0, 2]], "tv": 2},
+    ]}], "pt": 1638320004000},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def runner(market, selection_id: int):
+    return next(r for r in market.runners if r.selection_id == selection_id)
+
+
+def test_market(mutable: bool):
+    markets = [m.copy() for m in bfd.File("crossed.json", data, cumulative_runner_tv=False, mutable=mutable)]
+    assert len(markets) == 5
+    pts = [m.publish_time for m in markets]
+
+    assert not runner(markets[0], 101).is_crossed
+    assert runner(markets[0], 101).last_update_time == pts[0]
+    assert markets[0].last_trade_time == pts[0]
+
+    assert runner(markets[1], 101).is_crossed
+    assert runner(markets[1], 101).last_update_time == pts[1]
+    assert markets[1].last_trade_time == pts[0]
+
+    # an ltp on its own doesn't touch the ladders
+    assert runner(markets[2], 101).is_crossed
+    assert runner(markets[2], 101).last_update_time == pts[1]
+    assert runner(markets[2], 102).last_update_time == pts[0]
+    assert markets[2].last_trade_time == pts[0]
+
+    assert not runner(markets[3], 101).is_crossed
+    assert runner(markets[3], 101).last_update_time == pts[3]
+    assert markets[3].last_trade_time == pts[3]
+
+    assert pts[4] is not None
+    assert runner(markets[4], 102).last_update_time == pts[4]
+    assert markets[4].last_trade_time == pts[4]
+
+
+if __name__ == "__main__":
+    test_market(mutable=True)
+    test_market(mutable=False)
+    print("crossed tests passed")
@@ -0,0 +1,121 @@
+import json
+from datetime import datetime
+import betfair_data as bfd
+from betfair_data import bflw as bfd_bflw
+
+# definition_history lists each definition version with the fields that changed
+
+def definition(version: int, in_play: bool, bet_delay: int, removed: bool):
+    return {
+        "bspMarket": False, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+        "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+        "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+        "bspReconciled": False, "complete": True, "inPlay": in_play, "crossMatching": True, "runnersVoidable": False,
+        "numberOfActiveRunners": 1 if removed else 2, "betDelay": bet_delay, "status": "OPEN", "regulators": ["MR_INT"],
+        "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+        "openDate": "2021-12-01T03:00:00.000Z", "version": version, "name": "R1 1000m", "eventName": "Test",
+        "runners": [
+            {"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"},
+            {"status": "REMOVED", "sortPriority": 2, "id": 102, "name": "Two", "removalDate": "2021-12-01T02:00:00.000Z"} if removed else
+            {"status": "ACTIVE", "sortPriority": 2, "id": 102, "name": "Two"},
+        ],
+    }
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition(1, False, 0, False)}]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "rc": [{"id": 101, "atb": [[2.0, 10]]}]}]},
+    {"op": "mcm", "clk": "3", "pt": 1638320002000, "mc": [{"id": "1.190000000", "marketDefinition": definition(2, False, 0, True)}]},
+    {"op": "mcm", "clk": "4", "pt": 1638320003000, "mc": [{"id": "1.190000000", "marketDefinition": definition(2, False, 0, True)}]},
+    {"op": "mcm", "clk": "5", "pt": 1638320004000, "mc": [{"id": "1.190000000", "marketDefinition": definition(3, True, 5, True)}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def check(history):
+    assert [c.version for c in history] == [1, 2, 3]
+    assert [c.publish_time for c in history] == [datetime.utcfromtimestamp(ts) for ts in [1638320000, 1638320002, 1638320004]]
+    assert history[0].changed == []
+    assert history[1].changed == ["number_of_active_runners", "version", "runners.102.removal_date", "runners.102.status"]
+    assert history[2].changed == ["bet_delay", "in_play", "version"]
+    assert history[2].definition["inPlay"] is True
+
+
+def test_history():
+    for mutable in [False, True]:
+        file = bfd.File("history.json", data, cumulative_runner_tv=False, mutable=mutable)
+        check(file.definition_history())
+
+        # the whole file is covered however far it's been read
+        next(file)
+        check(file.definition_history())
+
+    check(bfd_bflw.File("history.json", data, cumulative_runner_tv=False).definition_history())
+
+
+def test_names_are_attributes():
+    file = bfd.File("history.json", data, cumulative_runner_tv=False)
+    market = list(file)[-1]
+    runner = market.runners[0]
+
+    for c in file.definition_history():
+        for name in c.changed:
+            if name.startswith("runners."):
+                assert hasattr(runner, name.split(".")[2]), name
+            else:
+                assert hasattr(market, name), name
+
+
+def test_markets_tracked_separately():
+    a = definition(1, False, 0, False)
+    b = definition(1, False, 0, False)
+    b["runners"] = [{"status": "ACTIVE", "sortPriority": 1, "id": 101, "hc": 1.5}]
+    b2 = dict(b, version=2, runners=b["runners"] + [{"status": "ACTIVE", "sortPriority": 2, "id": 101, "hc": -1.5}])
+
+    lines = [
+        {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000001", "img": True, "marketDefinition": a}, {"id": "1.190000002", "img": True, "marketDefinition": b}]},
+        {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000002", "marketDefinition": b2}]},
+    ]
+    raw = "\n".join(json.dumps(m) for m in lines).encode()
+
+    history = bfd.File("markets.json", raw, cumulative_runner_tv=False).definition_history()
+    assert [(c.market_id, c.version, c.changed) for c in history] == [
+        ("1.190000001", 1, []),
+        ("1.190000002", 1, []),
+        ("1.190000002", 2, ["version", "runners.101:-1.5"]),
+    ]
+
+
+def test_escaped_ids():
+    lines = [
+        {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition(1, False, 0, False)}]},
+        {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "marketDefinition": definition(2, False, 0, True)}]},
+    ]
+    # the second line's id is written escaped, it's still the same market
+    raw = (json.dumps(lines[0]) + "\n" + json.dumps(lines[1]).replace("1.190000000", "1.19000000\\u0030")).encode()
+
+    history = bfd.File("escaped.json", raw, cumulative_runner_tv=False).definition_history()
+    assert [(c.market_id, c.version) for c in history] == [("1.190000000", 1), ("1.190000000", 2)]
+    assert history[1].changed == ["number_of_active_runners", "version", "runners.102.removal_date", "runners.102.status"]
+
+
+def test_parse_error_raises():
+    for raw in [data + b"\n{\"op\": \"mcm\", \"mc\": [}]}", data.replace(b'"version": 2', b'"version": "2"')]:
+        for file in [bfd.File("broken.json", raw, cumulative_runner_tv=False), bfd_bflw.File("broken.json", raw, cumulative_runner_tv=False)]:
+            try:
+                file.definition_history()
+                assert False, "expected a parse error"
+            except RuntimeError as err:
+                assert "broken.json" in str(err)
+
+    # a file cut off part way through its last message ends there, the same as the readers
+    check(bfd.File("cut.json", data + b"\n{\"op\": \"mcm\", \"mc\": [", cumulative_runner_tv=False).definition_history())
+
+
+if __name__ == "__main__":
+    test_history()
+    test_names_are_attributes()
+    test_markets_tracked_separately()
+    test_escaped_ids()
+    test_parse_error_raises()
+    print("definition history tests passed")
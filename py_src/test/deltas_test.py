@@ -0,0 +1,64 @@
+import json
+import betfair_data as bfd
+
+# traded and matched deltas belong to the update that carried them, runners the next update
+# doesn't touch go back to empty deltas
+
+definition = {
+    "bspMarket": False, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 3, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+    "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+    "runners": [
+        {"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"},
+        {"status": "ACTIVE", "sortPriority": 2, "id": 102, "name": "Two"},
+        {"status": "ACTIVE", "sortPriority": 3, "id": 103, "name": "Three"},
+    ],
+}
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition, "rc": [
+        {"id": 101, "trd": [[2.0, 5]], "tv": 5},
+        {"id": 102, "trd": [[3.0, 4]], "tv": 4},
+    ]}]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 101, "trd": [[2.0, 8]], "tv": 8},
+    ]}]},
+    {"op": "mcm", "clk": "3", "pt": 1638320002000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 103, "atb": [[5.0, 2]]},
+    ]}]},
+    {"op": "mcm", "clk": "4", "pt": 1638320003000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 102, "trd": [[3.0, 6]], "tv": 6},
+    ]}]},
+    {"op": "mcm", "clk": "5", "pt": 1638320004000, "mc": [{"id": "1.190000000", "marketDefinition": {**definition, "version": 2}}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def deltas(market):
+    return {
+        r.selection_id: ([(ps.price, ps.size) for ps in r.traded_volume_delta], r.total_matched_delta)
+        for r in market.runners
+    }
+
+
+def test_deltas(mutable: bool):
+    markets = [m.copy() for m in bfd.File("deltas.json", data, cumulative_runner_tv=False, mutable=mutable)]
+    assert len(markets) == 5
+
+    none = ([], 0.0)
+    assert deltas(markets[0]) == {101: ([(2.0, 5)], 5.0), 102: ([(3.0, 4)], 4.0), 103: none}
+    assert deltas(markets[1]) == {101: ([(2.0, 3)], 3.0), 102: none, 103: none}
+    assert deltas(markets[2]) == {101: none, 102: none, 103: none}
+    assert deltas(markets[3]) == {101: none, 102: ([(3.0, 2)], 2.0), 103: none}
+    assert deltas(markets[4]) == {101: none, 102: none, 103: none}
+
+
+if __name__ == "__main__":
+    test_deltas(mutable=False)
+    test_deltas(mutable=True)
+    print("deltas tests passed")
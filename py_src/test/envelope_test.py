@@ -0,0 +1,74 @@
+import json
+import betfair_data as bfd
+
+# a self-recorded stream with the connection/status lines and heartbeats left in
+
+definition = {
+    "bspMarket": False, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 1, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+    "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+    "runners": [{"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"}],
+}
+
+stream = [
+    {"op": "connection", "connectionId": "002-000000000000-000000"},
+    {"op": "status", "id": 1, "statusCode": "SUCCESS", "connectionClosed": False},
+    {"op": "mcm", "id": 1, "initialClk": "1", "clk": "1", "pt": 1638320000000, "ct": "SUB_IMAGE", "heartbeatMs": 5000,
+     "conflateMs": 0, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition, "rc": [
+        {"id": 101, "atb": [[2.0, 10]], "ltp": 2.0, "tv": 20},
+    ]}]},
+    {"op": "mcm", "id": 1, "clk": "2", "pt": 1638320005000, "ct": "HEARTBEAT"},
+    {"op": "mcm", "id": 1, "clk": "3", "pt": 1638320006000, "status": 503, "mc": [{"id": "1.190000000", "con": True, "rc": [
+        {"id": 101, "atb": [[2.0, 12]]},
+    ]}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def test_market(mutable: bool):
+    markets = [m.copy() for m in bfd.File("envelope.json", data, cumulative_runner_tv=False, mutable=mutable)]
+    assert len(markets) == 2
+
+    assert markets[0].change_type == "SUB_IMAGE"
+    assert not markets[0].conflated
+    assert markets[0].stream_status is None
+
+    assert markets[1].change_type is None
+    assert markets[1].conflated
+    assert markets[1].stream_status == 503
+
+
+def test_heartbeats(mutable: bool):
+    markets = [m.copy() for m in bfd.File("envelope.json", data, cumulative_runner_tv=False, mutable=mutable, heartbeats=True)]
+    assert len(markets) == 3
+
+    hb = markets[1]
+    assert hb.change_type == "HEARTBEAT"
+    assert hb.clk == "2"
+    assert not hb.conflated
+    assert [(p.price, p.size) for p in hb.runners[0].ex.available_to_back] == [(2.0, 10)]
+
+    # a heartbeat isn't a repeat of the update before it
+    assert markets[0].runners[0].total_matched_delta == 20
+    assert hb.runners[0].total_matched_delta == 0
+    assert hb.runners[0].traded_volume_delta == []
+
+
+def test_heartbeat_changes():
+    markets = list(bfd.File("envelope.json", data, cumulative_runner_tv=False, heartbeats=True, changes=True))
+
+    hb = markets[1].changes
+    assert hb.runners == [] and not hb.definition and hb.total_matched is None
+
+
+if __name__ == "__main__":
+    for mutable in (True, False):
+        test_market(mutable=mutable)
+        test_heartbeats(mutable=mutable)
+    test_heartbeat_changes()
+    print("envelope tests passed")
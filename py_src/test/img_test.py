@@ -0,0 +1,95 @@
+import json
+import betfair_data as bfd
+from betfair_data import bflw as bfd_bflw
+
+# a recorded stream where the connection drops and the resubscribe image (img: true)
+# no longer contains the prices that were on the ladder before the disconnect
+
+definition = {
+    "bspMarket": False, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 2, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+    "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+    "runners": [
+        {"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"},
+        {"status": "ACTIVE", "sortPriority": 2, "id": 102, "name": "Two"},
+    ],
+}
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition, "rc": [
+        {"id": 101, "atb": [[2.0, 10], [1.9, 5]], "atl": [[2.1, 4]], "trd": [[2.0, 20]], "ltp": 2.0, "tv": 20},
+        {"id": 102, "atb": [[3.0, 8]], "atl": [[3.2, 6]]},
+    ]}]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 101, "atb": [[1.8, 7]], "trd": [[2.0, 25]], "tv": 25},
+    ]}]},
+    # resubscribe
+    {"op": "mcm", "clk": "3", "pt": 1638320060000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition, "rc": [
+        {"id": 101, "atb": [[2.2, 3]], "atl": [[2.3, 9]], "trd": [[2.2, 30]], "ltp": 2.2, "tv": 30},
+    ]}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def ladder(ps):
+    return [(p.price, p.size) for p in ps]
+
+
+def check_image(runners):
+    r1, r2 = runners
+    assert ladder(r1.ex.available_to_back) == [(2.2, 3)], ladder(r1.ex.available_to_back)
+    assert ladder(r1.ex.available_to_lay) == [(2.3, 9)], ladder(r1.ex.available_to_lay)
+    assert ladder(r1.ex.traded_volume) == [(2.2, 30)], ladder(r1.ex.traded_volume)
+    assert r1.last_price_traded == 2.2
+    assert r1.total_matched == 30
+
+    assert ladder(r2.ex.available_to_back) == [], ladder(r2.ex.available_to_back)
+    assert ladder(r2.ex.available_to_lay) == [], ladder(r2.ex.available_to_lay)
+    assert r2.last_price_traded is None
+    assert r2.total_matched == 0
+
+
+def test_market(mutable: bool):
+    markets = [m.copy() for m in bfd.File("img.json", data, cumulative_runner_tv=False, mutable=mutable)]
+    assert len(markets) == 3
+
+    assert ladder(markets[1].runners[0].ex.available_to_back) == [(2.0, 10), (1.9, 5), (1.8, 7)]
+    check_image(markets[2].runners)
+
+
+def test_bflw():
+    books = [mb for mbs in bfd_bflw.File("img.json", data, cumulative_runner_tv=False) for mb in mbs]
+    assert len(books) == 3
+
+    assert ladder(books[1].runners[0].ex.available_to_back) == [(2.0, 10), (1.9, 5), (1.8, 7)]
+    check_image(books[2].runners)
+
+
+# an image without a definition still replaces the runners, only those it sends prices for are kept
+def test_image_without_definition():
+    resubscribe = {"op": "mcm", "clk": "3", "pt": 1638320060000, "mc": [{"id": "1.190000000", "img": True, "rc": [
+        {"id": 101, "atb": [[2.2, 3]], "atl": [[2.3, 9]], "trd": [[2.2, 30]], "ltp": 2.2, "tv": 30},
+    ]}]}
+    data = "\n".join(json.dumps(m) for m in stream[:2] + [resubscribe]).encode()
+
+    for mutable in [False, True]:
+        markets = [m.copy() for m in bfd.File("img.json", data, cumulative_runner_tv=False, mutable=mutable)]
+        assert [r.selection_id for r in markets[2].runners] == [101]
+        assert ladder(markets[2].runners[0].ex.available_to_back) == [(2.2, 3)]
+        assert markets[2].total_matched == 0
+
+    books = [mb for mbs in bfd_bflw.File("img.json", data, cumulative_runner_tv=False) for mb in mbs]
+    assert [r.selection_id for r in books[2].runners] == [101]
+
+
+if __name__ == "__main__":
+    test_market(mutable=True)
+    test_market(mutable=False)
+    test_bflw()
+    test_image_without_definition()
+    print("img tests passed")
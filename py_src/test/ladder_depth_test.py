@@ -0,0 +1,75 @@
+import json
+import betfair_data as bfd
+
+# ladder_depth limits the atb/atl levels python sees, the full ladders are still kept underneath
+
+definition = {
+    "bspMarket": False, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 1, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+    "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+    "runners": [{"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"}],
+}
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition, "rc": [
+        {"id": 101, "atb": [[2.0, 10], [1.99, 5], [1.98, 1]], "atl": [[2.02, 4], [2.04, 3], [2.06, 2]]},
+    ]}]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 101, "atl": [[2.02, 0]]},
+    ]}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def ladder(l):
+    return [(ps.price, ps.size) for ps in l]
+
+
+def test_ladder_depth(mutable: bool):
+    markets = [m.copy() for m in bfd.File("depth.json", data, ladder_depth=2, mutable=mutable)]
+    first, second = (m.runners[0].ex for m in markets)
+
+    assert ladder(first.available_to_back) == [(2.0, 10), (1.99, 5)]
+    assert ladder(first.available_to_lay) == [(2.02, 4), (2.04, 3)]
+    assert ladder(second.available_to_back) == [(2.0, 10), (1.99, 5)]
+    assert ladder(second.available_to_lay) == [(2.04, 3), (2.06, 2)]
+
+    # the full ladders are still there for the analytics and a market image
+    assert second.back_depth(ticks=3) == 16
+    assert markets[1].to_dict()["rc"][0]["atb"] == [[2.0, 10], [1.99, 5], [1.98, 1]]
+
+
+def test_views_carried():
+    # a side of the book an update didn't change inside the depth keeps the list python already has
+    deeper = stream + [
+        {"op": "mcm", "clk": "3", "pt": 1638320002000, "mc": [{"id": "1.190000000", "rc": [
+            {"id": 101, "atb": [[1.97, 8], [1.98, 0]], "atl": [[2.06, 0]]},
+        ]}]},
+        {"op": "mcm", "clk": "4", "pt": 1638320003000, "mc": [{"id": "1.190000000", "rc": [
+            {"id": 101, "atb": [[2.0, 10]]},
+        ]}]},
+    ]
+    deeper = "\n".join(json.dumps(m) for m in deeper).encode()
+    views = [
+        (m.runners[0].ex.available_to_back, m.runners[0].ex.available_to_lay)
+        for m in bfd.File("depth.json", deeper, ladder_depth=2)
+    ]
+
+    assert views[1][0] is views[0][0]
+    assert views[1][1] is not views[0][1]
+    assert views[2][0] is views[1][0]
+    assert views[2][1] is not views[1][1]
+    # re-sending a level with the same size changes nothing
+    assert views[3][0] is views[2][0]
+
+
+if __name__ == "__main__":
+    test_ladder_depth(mutable=False)
+    test_ladder_depth(mutable=True)
+    test_views_carried()
+    print("ladder depth tests passed")
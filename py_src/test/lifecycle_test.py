@@ -0,0 +1,124 @@
+import json
+from datetime import datetime
+import betfair_data as bfd
+
+# lifecycle_events picks out removals, reinstatements and market status changes
+
+def definition(version: int, status: str, in_play: bool = False, bsp_reconciled: bool = False, removed: str = "ACTIVE", winner: bool = False):
+    two = {"status": removed, "sortPriority": 2, "id": 102, "name": "Two"}
+    if removed == "REMOVED":
+        two.update({"adjustmentFactor": 23.5, "removalDate": "2021-12-01T02:00:00.000Z"})
+
+    return {
+        "bspMarket": True, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+        "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+        "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+        "bspReconciled": bsp_reconciled, "complete": True, "inPlay": in_play, "crossMatching": True, "runnersVoidable": False,
+        "numberOfActiveRunners": 2, "betDelay": 0, "status": status, "regulators": ["MR_INT"],
+        "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+        "openDate": "2021-12-01T03:00:00.000Z", "version": version, "name": "R1 1000m", "eventName": "Test",
+        "runners": [
+            {"status": "WINNER" if winner else "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"},
+            two,
+        ],
+    }
+
+def line(pt: int, mc):
+    return json.dumps({"op": "mcm", "clk": str(pt), "pt": pt, "mc": [mc]})
+
+id = "1.190000000"
+data = "\n".join([
+    line(1638320000000, {"id": id, "img": True, "marketDefinition": definition(1, "OPEN"), "rc": [{"id": 102, "atb": [[4.0, 10]], "atl": [[4.2, 8]]}]}),
+    line(1638320001000, {"id": id, "marketDefinition": definition(2, "SUSPENDED", removed="REMOVED")}),
+    line(1638320002000, {"id": id, "marketDefinition": definition(3, "OPEN", removed="REMOVED")}),
+    line(1638320003000, {"id": id, "marketDefinition": definition(4, "OPEN")}),
+    line(1638320004000, {"id": id, "marketDefinition": definition(5, "OPEN", in_play=True, bsp_reconciled=True)}),
+    line(1638320005000, {"id": id, "marketDefinition": definition(6, "CLOSED", in_play=True, bsp_reconciled=True, winner=True)}),
+]).encode()
+
+
+def test_events():
+    for mutable in [False, True]:
+        events = bfd.File("lifecycle.json", data, cumulative_runner_tv=False, mutable=mutable).lifecycle_events()
+
+        assert [(e.kind, e.selection_id) for e in events] == [
+            ("runner_removed", 102),
+            ("suspended", None),
+            ("resumed", None),
+            ("runner_reinstated", 102),
+            ("in_play", None),
+            ("bsp_reconciled", None),
+            ("settled", None),
+        ]
+
+        removed = events[0]
+        assert removed.market_id == id
+        assert removed.publish_time == datetime.utcfromtimestamp(1638320001)
+        assert removed.adjustment_factor == 23.5
+        assert removed.removal_date == datetime(2021, 12, 1, 2)
+        assert removed.runner.status == "ACTIVE"
+        assert removed.runner.ex.available_to_back[0].price == 4.0
+        assert removed.runner.ex.available_to_lay[0].size == 8
+
+        assert events[-1].winners == [101]
+        assert all(e.winners == [] for e in events[:-1])
+
+
+def test_first_definition():
+    # the first definition a market has starts off its statuses, they aren't changes
+    data = "\n".join([
+        line(1638320000000, {"id": id, "img": True, "marketDefinition": definition(1, "SUSPENDED", removed="REMOVED")}),
+        line(1638320001000, {"id": id, "marketDefinition": definition(2, "SUSPENDED", removed="REMOVED")}),
+        line(1638320002000, {"id": id, "marketDefinition": definition(3, "OPEN", removed="REMOVED")}),
+    ]).encode()
+
+    events = bfd.File("lifecycle.json", data, cumulative_runner_tv=False).lifecycle_events()
+    assert [(e.kind, e.selection_id) for e in events] == [("resumed", None)]
+
+
+def test_runners_from_rc():
+    # runners an rc creates before any definition mentions them have no status to move from
+    three = {"status": "REMOVED", "sortPriority": 3, "id": 103, "name": "Three", "adjustmentFactor": 4.0}
+    first = definition(1, "OPEN")
+    second = definition(2, "OPEN")
+    second["runners"] = second["runners"] + [three]
+    third = definition(3, "OPEN")
+    third["runners"] = third["runners"] + [dict(three, status="ACTIVE", adjustmentFactor=None)]
+
+    data = "\n".join([
+        line(1638320000000, {"id": id, "img": True, "rc": [{"id": 101, "atb": [[2.0, 5]]}], "marketDefinition": first}),
+        line(1638320001000, {"id": id, "rc": [{"id": 103, "atb": [[9.0, 5]]}]}),
+        line(1638320002000, {"id": id, "marketDefinition": second}),
+        line(1638320003000, {"id": id, "marketDefinition": third}),
+    ]).encode()
+
+    for mutable in [False, True]:
+        events = bfd.File("lifecycle.json", data, cumulative_runner_tv=False, mutable=mutable).lifecycle_events()
+        assert [(e.kind, e.selection_id) for e in events] == [("runner_reinstated", 103)], mutable
+
+
+def test_same_reader():
+    for mutable in [False, True]:
+        file = bfd.File("lifecycle.json", data, cumulative_runner_tv=False, mutable=mutable)
+        next(file)
+        next(file)
+
+        # the rest of the file is read by the file's own reader, so there's nothing left to iterate
+        events = file.lifecycle_events()
+        assert [e.kind for e in events][:2] == ["runner_removed", "suspended"]
+        assert len(events) == 7
+        assert list(file) == []
+
+        assert [e.kind for e in file.lifecycle_events()] == [e.kind for e in events]
+
+        removed = events[0].runner
+        assert removed.ex.available_to_back[0].price == 4.0
+        assert type(removed).__name__ == ("RunnerMut" if mutable else "Runner")
+
+
+if __name__ == "__main__":
+    test_events()
+    test_first_definition()
+    test_runners_from_rc()
+    test_same_reader()
+    print("lifecycle tests passed")
@@ -0,0 +1,56 @@
+import json
+import betfair_data as bfd
+from betfair_data import bflw as bfd_bflw
+
+# a file recorded mid stream starts with deltas for a market that hasn't had a definition yet,
+# they should be skipped rather than ending (or crashing) the file
+
+definition = {
+    "bspMarket": True, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 1, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+    "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+    "runners": [{"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"}],
+}
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "rc": [{"id": 101, "atb": [[2.0, 10]]}]}]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "tv": 5}]},
+    {"op": "mcm", "clk": "3", "pt": 1638320002000, "mc": [{"id": "1.190000000", "marketDefinition": definition, "rc": [{"id": 101, "atl": [[2.1, 4]]}]}]},
+    {"op": "mcm", "clk": "4", "pt": 1638320003000, "mc": [{"id": "1.190000000", "rc": [{"id": 101, "atb": [[1.9, 3]]}]}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def ladder(ps):
+    return [(p.price, p.size) for p in ps]
+
+
+def test_market(mutable: bool):
+    markets = [m.copy() for m in bfd.File("missing.json", data, cumulative_runner_tv=False, mutable=mutable)]
+    assert len(markets) == 2, len(markets)
+
+    # the atb from before the definition was dropped along with the rest of that update
+    assert ladder(markets[0].runners[0].ex.available_to_back) == []
+    assert ladder(markets[0].runners[0].ex.available_to_lay) == [(2.1, 4)]
+    assert ladder(markets[1].runners[0].ex.available_to_back) == [(1.9, 3)]
+
+
+def test_bflw():
+    books = [mb for mbs in bfd_bflw.File("missing.json", data, cumulative_runner_tv=False) for mb in mbs]
+    assert [mb.publish_time_epoch for mb in books] == [1638320002000, 1638320003000]
+
+    assert ladder(books[0].runners[0].ex.available_to_back) == []
+    assert ladder(books[0].runners[0].ex.available_to_lay) == [(2.1, 4)]
+    assert ladder(books[1].runners[0].ex.available_to_back) == [(1.9, 3)]
+
+
+if __name__ == "__main__":
+    test_market(mutable=False)
+    test_market(mutable=True)
+    test_bflw()
+    print("missing definition tests passed")
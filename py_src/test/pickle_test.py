@@ -0,0 +1,111 @@
+import copy
+import json
+import pickle
+import betfair_data as bfd
+from betfair_data import bflw as bfd_bflw
+
+# pickled markets should load back the same as the original, and copies of a MarketMut shouldn't
+# share state with it
+
+definition = {
+    "bspMarket": True, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 1, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney", "venue": "Test Park",
+    "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+    "priceLadderDefinition": {"type": "CLASSIC"},
+    "runners": [
+        {"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"},
+        {"status": "REMOVED", "sortPriority": 2, "id": 102, "name": "Two", "adjustmentFactor": 12.5,
+         "removalDate": "2021-12-01T02:00:00.000Z"},
+    ],
+}
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition, "rc": [
+        {"id": 101, "atb": [[2.0, 10], [1.9, 5]], "atl": [[2.1, 4]], "trd": [[2.0, 20]], "ltp": 2.0, "tv": 20, "spn": "NaN"},
+    ]}]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 101, "atb": [[1.8, 7]], "trd": [[2.0, 25]], "tv": 25},
+    ]}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def ladder(ps):
+    return [(p.price, p.size) for p in ps]
+
+
+def snapshot(m):
+    return (
+        m.market_id, m.publish_time, m.status, m.version, m.total_matched,
+        [
+            (
+                r.selection_id, r.status, r.adjustment_factor, r.removal_date, r.last_price_traded, r.total_matched,
+                ladder(r.ex.available_to_back), ladder(r.ex.available_to_lay), ladder(r.ex.traded_volume),
+                str(r.sp.near_price),
+            )
+            for r in m.runners
+        ],
+    )
+
+
+def test_market():
+    markets = [m for m in bfd.File("pickle.json", data, cumulative_runner_tv=False)]
+
+    for m in markets:
+        loaded = pickle.loads(pickle.dumps(m))
+        assert snapshot(loaded) == snapshot(m), (snapshot(loaded), snapshot(m))
+        assert loaded.clk == m.clk and loaded.venue == m.venue and loaded.market_name == m.market_name
+
+        runner = pickle.loads(pickle.dumps(m.runners[0]))
+        assert ladder(runner.ex.available_to_back) == ladder(m.runners[0].ex.available_to_back)
+
+    ps = pickle.loads(pickle.dumps(bfd.PriceSize(2.5, 10)))
+    assert (ps.price, ps.size) == (2.5, 10)
+
+
+def test_bflw():
+    books = [mb for mbs in bfd_bflw.File("pickle.json", data, cumulative_runner_tv=False) for mb in mbs]
+
+    for mb in books:
+        loaded = pickle.loads(pickle.dumps(mb))
+        assert snapshot(loaded) == snapshot(mb), (snapshot(loaded), snapshot(mb))
+        assert loaded.market_definition.venue == "Test Park"
+        assert loaded.market_definition.price_ladder_definition.type == "CLASSIC"
+        assert loaded.market_definition.runners[1].name == "Two"
+
+
+def test_copy_mutable():
+    for m in bfd.File("pickle.json", data, cumulative_runner_tv=False, mutable=True):
+        a = copy.copy(m)
+        b = copy.deepcopy(m)
+        assert a is not m and b is not m
+        assert snapshot(a) == snapshot(m) == snapshot(b)
+
+    # the file reuses the one MarketMut, the copies should still hold the first update
+    first = [copy.deepcopy(m) for m in bfd.File("pickle.json", data, cumulative_runner_tv=False, mutable=True)]
+    assert first[0].publish_time != first[1].publish_time
+
+
+def test_mutable():
+    for m in bfd.File("pickle.json", data, cumulative_runner_tv=False, mutable=True):
+        loaded = pickle.loads(pickle.dumps(m))
+        assert type(loaded) is type(m)
+        assert snapshot(loaded) == snapshot(m), (snapshot(loaded), snapshot(m))
+        assert loaded.clk == m.clk and loaded.venue == m.venue and loaded.market_name == m.market_name
+        assert [r.total_matched_delta for r in loaded.runners] == [r.total_matched_delta for r in m.runners]
+
+        runner = pickle.loads(pickle.dumps(m.runners[0]))
+        assert ladder(runner.ex.available_to_back) == ladder(m.runners[0].ex.available_to_back)
+
+
+if __name__ == "__main__":
+    test_market()
+    test_bflw()
+    test_copy_mutable()
+    test_mutable()
+    print("pickle tests passed")
@@ -0,0 +1,97 @@
+import json
+import betfair_data as bfd
+from betfair_data import bflw as bfd_bflw
+
+# runner changes have to find their runner after definitions add, drop and reorder runners,
+# and after an image replaces them, with few runners and with enough to be hashed
+
+def definition(version: int, ids):
+    return {
+        "bspMarket": False, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+        "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+        "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+        "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+        "numberOfActiveRunners": len(ids), "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+        "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+        "openDate": "2021-12-01T03:00:00.000Z", "version": version, "name": "R1 1000m", "eventName": "Test",
+        "runners": [{"status": "ACTIVE", "sortPriority": i + 1, "id": id} for i, id in enumerate(ids)],
+    }
+
+
+def stream(n: int):
+    ids = list(range(101, 101 + n))
+    # the first two swap places, a new runner goes in between them and the last is dropped
+    moved = [ids[1], 900, ids[0]] + ids[2:-1]
+    prices = lambda ids, base: [{"id": id, "atb": [[base * 100 + i, 1]]} for i, id in enumerate(ids)]
+
+    lines = [
+        {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition(1, ids), "rc": prices(ids, 2)}]},
+        # a runner the definition doesn't have yet
+        {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "rc": [{"id": 800, "atb": [[900, 1]]}]}]},
+        {"op": "mcm", "clk": "3", "pt": 1638320002000, "mc": [{"id": "1.190000000", "marketDefinition": definition(2, moved)}]},
+        {"op": "mcm", "clk": "4", "pt": 1638320003000, "mc": [{"id": "1.190000000", "rc": prices(moved, 3)}]},
+        # an image with the runners in their first order again
+        {"op": "mcm", "clk": "5", "pt": 1638320004000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition(3, ids), "rc": prices(ids, 4)}]},
+        {"op": "mcm", "clk": "6", "pt": 1638320005000, "mc": [{"id": "1.190000000", "rc": prices(ids, 5)}]},
+    ]
+    return "\n".join(json.dumps(m) for m in lines).encode(), ids, moved
+
+
+def backs(runners):
+    return {r.selection_id: r.ex.available_to_back[0].price for r in runners if r.ex.available_to_back}
+
+
+# bflw books keep their runners in the order they were first seen
+def check(updates, ids, moved, reordered: bool):
+    expect = lambda ids, base: {id: base * 100 + i for i, id in enumerate(ids)}
+
+    assert backs(updates[0]) == expect(ids, 2)
+    assert backs(updates[1])[800] == 900
+    assert [r.selection_id for r in updates[1]] == ids + [800]
+    if reordered:
+        assert [r.selection_id for r in updates[2]][:len(moved)] == moved
+
+    after_move = backs(updates[3])
+    assert {id: after_move[id] for id in moved} == expect(moved, 3)
+
+    assert [r.selection_id for r in updates[4]] == ids
+    assert backs(updates[4]) == expect(ids, 4)
+    assert backs(updates[5]) == expect(ids, 5)
+
+
+def test_market(mutable: bool):
+    for n in [4, 30]:
+        data, ids, moved = stream(n)
+        updates = [list(m.copy().runners) for m in bfd.File("index.json", data, cumulative_runner_tv=False, mutable=mutable)]
+        check(updates, ids, moved, reordered=True)
+
+
+# the change sets follow each runner by selection id, whatever position it has
+def test_changes():
+    for n in [4, 30]:
+        data, ids, moved = stream(n)
+        changes = [m.changes for m in bfd.File("index.json", data, cumulative_runner_tv=False, changes=True)]
+        levels = lambda rc: {(ps.price, ps.size) for ps in rc.available_to_back}
+
+        assert [rc.selection_id for rc in changes[1].runners] == [800]
+
+        after_move = {rc.selection_id: levels(rc) for rc in changes[3].runners}
+        assert after_move == {id: {(300 + i, 1)} for i, id in enumerate(moved)}
+
+        after_img = {rc.selection_id: levels(rc) for rc in changes[5].runners}
+        assert after_img == {id: {(500 + i, 1)} for i, id in enumerate(ids)}
+
+
+def test_bflw():
+    for n in [4, 30]:
+        data, ids, moved = stream(n)
+        updates = [list(mb.runners) for mbs in bfd_bflw.File("index.json", data, cumulative_runner_tv=False) for mb in mbs]
+        check(updates, ids, moved, reordered=False)
+
+
+if __name__ == "__main__":
+    test_market(mutable=True)
+    test_market(mutable=False)
+    test_changes()
+    test_bflw()
+    print("runner index tests passed")
@@ -0,0 +1,85 @@
+import json
+import betfair_data as bfd
+from betfair_data import bflw as bfd_bflw
+
+# to_json/to_dict produce a full image mc entry, reading it back should give the same market
+
+definition = {
+    "bspMarket": True, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 2, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney",
+    "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+    "priceLadderDefinition": {"type": "CLASSIC"},
+    "runners": [
+        {"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"},
+        {"status": "ACTIVE", "sortPriority": 2, "id": 102, "name": "Two"},
+    ],
+}
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition, "rc": [
+        {"id": 101, "atb": [[2.0, 10], [1.9, 5]], "atl": [[2.1, 4]], "trd": [[2.0, 20]], "ltp": 2.0, "tv": 20,
+         "spb": [[1000, 2]], "spl": [[1.01, 30]]},
+        {"id": 102, "atb": [[3.0, 8]], "atl": [[3.2, 6]], "spn": "NaN"},
+    ]}]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 101, "atb": [[1.8, 7]], "trd": [[2.0, 25]], "tv": 25},
+    ]}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def ladder(ps):
+    return [(p.price, p.size) for p in ps]
+
+
+def snapshot(m):
+    return (
+        m.market_id, m.status, m.version, m.total_matched,
+        [
+            (r.selection_id, r.status, r.last_price_traded, r.total_matched,
+             ladder(r.ex.available_to_back), ladder(r.ex.available_to_lay), ladder(r.ex.traded_volume),
+             ladder(r.sp.back_stake_taken), ladder(r.sp.lay_liability_taken))
+            for r in m.runners
+        ],
+    )
+
+
+def line(pt, mc):
+    return json.dumps({"op": "mcm", "clk": "1", "pt": pt, "mc": [mc]}).encode()
+
+
+def test_market(mutable: bool):
+    last = [m.copy() for m in bfd.File("to_json.json", data, cumulative_runner_tv=False, mutable=mutable)][-1]
+
+    mc = last.to_dict()
+    assert mc["img"] and mc["id"] == "1.190000000"
+    assert mc["marketDefinition"]["eventId"] == "31091000"
+    assert mc["rc"][1]["spn"] == "NaN"
+    assert mc["rc"][0]["spb"] == [[1000, 2]] and mc["rc"][0]["spl"] == [[1.01, 30]]
+    assert json.loads(last.to_json()) == mc
+
+    again = [m for m in bfd.File("to_json.json", line(1638320001000, mc), cumulative_runner_tv=False, mutable=mutable)]
+    assert snapshot(again[0]) == snapshot(last), (snapshot(again[0]), snapshot(last))
+
+
+def test_bflw():
+    last = [mb for mbs in bfd_bflw.File("to_json.json", data, cumulative_runner_tv=False) for mb in mbs][-1]
+
+    mc = last.to_dict()
+    assert mc["marketDefinition"]["priceLadderDefinition"] == {"type": "CLASSIC"}
+    assert mc["marketDefinition"]["runners"][0]["name"] == "One"
+
+    again = [mb for mbs in bfd_bflw.File("to_json.json", line(1638320001000, mc), cumulative_runner_tv=False) for mb in mbs]
+    assert snapshot(again[0]) == snapshot(last), (snapshot(again[0]), snapshot(last))
+
+
+if __name__ == "__main__":
+    test_market(mutable=False)
+    test_market(mutable=True)
+    test_bflw()
+    print("to_json tests passed")
@@ -0,0 +1,95 @@
+import json
+import os
+import tempfile
+import betfair_data as bfd
+
+# markets written back out with StreamWriter should read in the same as the original stream
+
+definition = {
+    "bspMarket": True, "turnInPlayEnabled": True, "persistenceEnabled": True, "marketBaseRate": 5,
+    "eventId": "31091000", "eventTypeId": "7", "numberOfWinners": 1, "bettingType": "ODDS",
+    "marketType": "WIN", "marketTime": "2021-12-01T03:00:00.000Z", "suspendTime": "2021-12-01T03:00:00.000Z",
+    "bspReconciled": False, "complete": True, "inPlay": False, "crossMatching": True, "runnersVoidable": False,
+    "numberOfActiveRunners": 2, "betDelay": 0, "status": "OPEN", "regulators": ["MR_INT"],
+    "countryCode": "AU", "discountAllowed": True, "timezone": "Australia/Sydney", "venue": "Test Park",
+    "openDate": "2021-12-01T03:00:00.000Z", "version": 1, "name": "R1 1000m", "eventName": "Test",
+    "runners": [
+        {"status": "ACTIVE", "sortPriority": 1, "id": 101, "name": "One"},
+        {"status": "REMOVED", "sortPriority": 2, "id": 102, "name": "Two", "adjustmentFactor": 12.5,
+         "removalDate": "2021-12-01T02:00:00.000Z", "bsp": "NaN"},
+    ],
+}
+
+stream = [
+    {"op": "mcm", "clk": "1", "pt": 1638320000000, "mc": [{"id": "1.190000000", "img": True, "marketDefinition": definition, "rc": [
+        {"id": 101, "atb": [[2.0, 10], [1.9, 5]], "atl": [[2.1, 4]], "trd": [[2.0, 20]], "ltp": 2.0, "tv": 20, "spn": 2.02, "spb": [[1000, 2]]},
+    ]}]},
+    {"op": "mcm", "clk": "2", "pt": 1638320001000, "mc": [{"id": "1.190000000", "rc": [
+        {"id": 101, "atb": [[1.8, 7]], "trd": [[2.0, 25]], "tv": 25},
+    ]}]},
+]
+
+data = "\n".join(json.dumps(m) for m in stream).encode()
+
+
+def ladder(ps):
+    return [(p.price, p.size) for p in ps]
+
+
+def snapshot(m):
+    return (
+        m.market_id, m.clk, m.publish_time, m.status, m.venue, m.version, m.total_matched,
+        [
+            (
+                r.selection_id, r.status, r.name, r.adjustment_factor, r.removal_date, r.last_price_traded, r.total_matched,
+                ladder(r.ex.available_to_back), ladder(r.ex.available_to_lay), ladder(r.ex.traded_volume),
+                r.sp.near_price, ladder(r.sp.back_stake_taken),
+            )
+            for r in m.runners
+        ],
+    )
+
+
+# Files rather than File, so the compressed output is decoded
+def read(path: str, mutable: bool):
+    return [m.copy() for f in bfd.Files([path], cumulative_runner_tv=False, mutable=mutable) for m in f]
+
+
+def test_write_market(ext: str, mutable: bool):
+    markets = [m.copy() for m in bfd.File("writer.json", data, cumulative_runner_tv=False, mutable=mutable)]
+
+    with tempfile.TemporaryDirectory() as d:
+        path = os.path.join(d, "1.190000000" + ext)
+        with bfd.StreamWriter(path) as w:
+            for m in markets:
+                w.write_market(m)
+
+        written = read(path, mutable)
+
+    assert len(written) == len(markets)
+    for a, b in zip(markets, written):
+        assert snapshot(a) == snapshot(b), (snapshot(a), snapshot(b))
+
+
+def test_write_changes():
+    with tempfile.TemporaryDirectory() as d:
+        path = os.path.join(d, "changes.json.gz")
+        with bfd.StreamWriter(path) as w:
+            for m in stream:
+                w.write_changes(m["pt"], m["mc"], clk=m["clk"])
+            # a single mc entry as a json string
+            w.write_changes(1638320002000, json.dumps({"id": "1.190000000", "rc": [{"id": 101, "ltp": 1.9}]}), clk="3")
+
+        written = read(path, mutable=False)
+
+    assert len(written) == 3
+    assert written[2].runners[0].last_price_traded == 1.9
+    assert written[2].clk == "3"
+
+
+if __name__ == "__main__":
+    for ext in (".json", ".gz", ".bz2"):
+        test_write_market(ext, mutable=False)
+        test_write_market(ext, mutable=True)
+    test_write_changes()
+    print("writer tests passed")
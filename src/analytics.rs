@@ -0,0 +1,235 @@
+use crate::price_size::PriceSize;
+
+// the betfair CLASSIC price ladder in hundredths - (start, end, increment)
+const TICK_BANDS: [(u32, u32, u32); 10] = [
+    (101, 200, 1),
+    (200, 300, 2),
+    (300, 400, 5),
+    (400, 600, 10),
+    (600, 1000, 20),
+    (1000, 2000, 50),
+    (2000, 3000, 100),
+    (3000, 5000, 200),
+    (5000, 10000, 500),
+    (10000, 100000, 1000),
+];
+
+// index of a price on the ladder, 1.01 == 0 and 1000 == 349
+// prices that dont sit on the ladder (line markets, bad data) return None
+pub fn price_to_tick(price: f64) -> Option<u32> {
+    if !price.is_finite() {
+        return None;
+    }
+
+    let cents = (price * 100.0).round() as u32;
+    let mut tick = 0;
+
+    for (start, end, inc) in TICK_BANDS {
+        if cents >= start && cents <= end {
+            let diff = cents - start;
+            return (diff % inc == 0).then_some(tick + diff / inc);
+        }
+
+        tick += (end - start) / inc;
+    }
+
+    None
+}
+
+// ladders are stored best price first, for atb this is descending and for atl ascending
+#[inline]
+pub fn best(ladder: &[PriceSize]) -> Option<PriceSize> {
+    ladder.first().copied()
+}
+
+// the first `depth` levels of a ladder, or the whole ladder without a depth
+#[inline]
+pub fn top(ladder: &[PriceSize], depth: Option<usize>) -> &[PriceSize] {
+    match depth {
+        Some(d) if d < ladder.len() => &ladder[..d],
+        _ => ladder,
+    }
+}
+
+// number of ticks between the best back and best lay prices
+pub fn spread(atb: &[PriceSize], atl: &[PriceSize]) -> Option<i32> {
+    let back = atb.first().and_then(|ps| price_to_tick(ps.price))?;
+    let lay = atl.first().and_then(|ps| price_to_tick(ps.price))?;
+
+    Some(lay as i32 - back as i32)
+}
+
+// the best back price is at or above the best lay price
+pub fn is_crossed(atb: &[PriceSize], atl: &[PriceSize]) -> bool {
+    match (atb.first(), atl.first()) {
+        (Some(back), Some(lay)) => back.price >= lay.price,
+        _ => false,
+    }
+}
+
+// volume weighted average of the traded prices
+pub fn wap(trd: &[PriceSize]) -> Option<f64> {
+    let (pv, v) = trd
+        .iter()
+        .fold((0.0, 0.0), |(pv, v), ps| (pv + ps.price * ps.size, v + ps.size));
+
+    (v > 0.0).then_some(pv / v)
+}
+
+pub fn volume_at_price(ladder: &[PriceSize], price: f64) -> f64 {
+    ladder
+        .iter()
+        .find(|ps| ps.price == price)
+        .map(|ps| ps.size)
+        .unwrap_or_default()
+}
+
+// sum of the size available within `ticks` of the best price of the ladder,
+// works for both sides as the distance is measured away from the first entry
+pub fn depth(ladder: &[PriceSize], ticks: u32) -> f64 {
+    let top = match ladder.first().and_then(|ps| price_to_tick(ps.price)) {
+        Some(t) => t,
+        None => return 0.0,
+    };
+
+    ladder
+        .iter()
+        .map_while(|ps| {
+            price_to_tick(ps.price)
+                .filter(|t| t.abs_diff(top) <= ticks)
+                .map(|_| ps.size)
+        })
+        .sum()
+}
+
+// the change in size at each price between two ascending ladders, used to turn the
+// cumulative traded volume ladder into the amount traded by a single update
+pub fn ladder_delta(prev: &[PriceSize], next: &[PriceSize]) -> Vec<PriceSize> {
+    let mut delta = Vec::new();
+    let (mut p, mut n) = (prev.iter().peekable(), next.iter().peekable());
+
+    loop {
+        let ps = match (p.peek().copied(), n.peek().copied()) {
+            (Some(a), Some(b)) if a.price == b.price => {
+                let ps = PriceSize::new(b.price, b.size - a.size);
+                p.next();
+                n.next();
+                ps
+            }
+            (Some(a), Some(b)) if a.price < b.price => {
+                p.next();
+                PriceSize::new(a.price, -a.size)
+            }
+            (Some(a), None) => {
+                p.next();
+                PriceSize::new(a.price, -a.size)
+            }
+            (_, Some(b)) => {
+                n.next();
+                *b
+            }
+            (None, None) => break,
+        };
+
+        if ps.size != 0.0 {
+            delta.push(ps);
+        }
+    }
+
+    delta
+}
+
+// sum of the implied probabilities (1 / price) of the given prices
+pub fn book_percentage<I: Iterator<Item = f64>>(prices: I) -> f64 {
+    prices.filter(|p| *p > 0.0).map(|p| 1.0 / p).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_to_tick() {
+        assert_eq!(price_to_tick(1.01), Some(0));
+        assert_eq!(price_to_tick(1.02), Some(1));
+        assert_eq!(price_to_tick(2.0), Some(99));
+        assert_eq!(price_to_tick(2.02), Some(100));
+        assert_eq!(price_to_tick(3.0), Some(149));
+        assert_eq!(price_to_tick(3.05), Some(150));
+        assert_eq!(price_to_tick(4.0), Some(169));
+        assert_eq!(price_to_tick(6.0), Some(189));
+        assert_eq!(price_to_tick(10.0), Some(209));
+        assert_eq!(price_to_tick(20.0), Some(229));
+        assert_eq!(price_to_tick(30.0), Some(239));
+        assert_eq!(price_to_tick(50.0), Some(249));
+        assert_eq!(price_to_tick(100.0), Some(259));
+        assert_eq!(price_to_tick(1000.0), Some(349));
+
+        assert_eq!(price_to_tick(1.0), None);
+        assert_eq!(price_to_tick(2.01), None);
+        assert_eq!(price_to_tick(1010.0), None);
+        assert_eq!(price_to_tick(f64::NAN), None);
+        assert_eq!(price_to_tick(f64::INFINITY), None);
+    }
+
+    #[test]
+    fn test_ladder_analytics() {
+        let atb = vec![
+            PriceSize::new(2.0, 10.0),
+            PriceSize::new(1.99, 5.0),
+            PriceSize::new(1.95, 20.0),
+        ];
+        let atl = vec![
+            PriceSize::new(2.04, 7.0),
+            PriceSize::new(2.06, 3.0),
+            PriceSize::new(2.2, 100.0),
+        ];
+        let trd = vec![PriceSize::new(2.0, 30.0), PriceSize::new(3.0, 10.0)];
+
+        assert_eq!(best(&atb), Some(PriceSize::new(2.0, 10.0)));
+        assert_eq!(best(&atl), Some(PriceSize::new(2.04, 7.0)));
+        assert_eq!(best(&[]), None);
+
+        assert_eq!(top(&atb, Some(2)), &atb[..2]);
+        assert_eq!(top(&atb, Some(5)), &atb[..]);
+        assert_eq!(top(&atb, None), &atb[..]);
+
+        assert_eq!(spread(&atb, &atl), Some(2));
+        assert_eq!(spread(&[], &atl), None);
+
+        assert!(!is_crossed(&atb, &atl));
+        assert!(is_crossed(&atb, &[PriceSize::new(2.0, 1.0)]));
+        assert!(!is_crossed(&atb, &[]));
+
+        assert_eq!(wap(&trd), Some(2.25));
+        assert_eq!(wap(&[]), None);
+
+        assert_eq!(volume_at_price(&trd, 3.0), 10.0);
+        assert_eq!(volume_at_price(&trd, 4.0), 0.0);
+
+        assert_eq!(depth(&atb, 0), 10.0);
+        assert_eq!(depth(&atb, 1), 15.0);
+        assert_eq!(depth(&atb, 5), 35.0);
+        assert_eq!(depth(&atl, 2), 10.0);
+        assert_eq!(depth(&[], 2), 0.0);
+
+        let next = vec![
+            PriceSize::new(1.9, 4.0),
+            PriceSize::new(2.0, 35.0),
+            PriceSize::new(3.0, 10.0),
+        ];
+        assert_eq!(
+            ladder_delta(&trd, &next),
+            vec![PriceSize::new(1.9, 4.0), PriceSize::new(2.0, 5.0)]
+        );
+        assert_eq!(ladder_delta(&trd, &trd), vec![]);
+        assert_eq!(ladder_delta(&[], &trd), trd);
+        assert_eq!(
+            ladder_delta(&trd, &[PriceSize::new(3.0, 10.0)]),
+            vec![PriceSize::new(2.0, -30.0)]
+        );
+
+        let bp = book_percentage([2.0, 4.0, 4.0, 0.0].into_iter());
+        assert!((bp - 1.0).abs() < 1e-9);
+    }
+}
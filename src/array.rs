@@ -0,0 +1,42 @@
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::lazy::SyncOnceCell;
+
+use crate::price_size::PriceSize;
+
+static NUMPY_FROMBUFFER: SyncOnceCell<Py<PyAny>> = SyncOnceCell::new();
+
+const F64_SIZE: usize = std::mem::size_of::<f64>();
+
+// numpy isn't a dependency, it's only imported the first time an array is requested
+fn frombuffer(py: Python) -> PyResult<&PyAny> {
+    if let Some(f) = NUMPY_FROMBUFFER.get() {
+        return Ok(f.as_ref(py));
+    }
+
+    let f: Py<PyAny> = py.import("numpy")?.getattr("frombuffer")?.into();
+    Ok(NUMPY_FROMBUFFER.get_or_init(|| f).as_ref(py))
+}
+
+// writes the values straight into a python bytes object, which numpy then uses as the
+// backing memory of a read-only (rows, cols) float64 array - the only allocation made
+pub fn matrix<I>(rows: usize, cols: usize, values: I, py: Python) -> PyResult<PyObject>
+where
+    I: Iterator<Item = f64>,
+{
+    let bytes = PyBytes::new_with(py, rows * cols * F64_SIZE, |buf| {
+        buf.chunks_exact_mut(F64_SIZE)
+            .zip(values)
+            .for_each(|(chunk, v)| chunk.copy_from_slice(&v.to_ne_bytes()));
+        Ok(())
+    })?;
+
+    let arr = frombuffer(py)?.call1((bytes, "float64"))?;
+    Ok(arr.call_method1("reshape", (rows, cols))?.into_py(py))
+}
+
+// an (n, 2) array of [price, size] rows
+pub fn ladder(ladder: &[PriceSize], py: Python) -> PyResult<PyObject> {
+    let values = ladder.iter().flat_map(|ps| [ps.price, ps.size]);
+    matrix(ladder.len(), 2, values, py)
+}
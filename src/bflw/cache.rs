@@ -0,0 +1,173 @@
+use log::warn;
+use pyo3::types::{PySequence, PyString};
+use pyo3::{exceptions, prelude::*};
+use std::path::Path;
+
+use super::config::Config;
+use super::file::BflwFile;
+use super::market_book::MarketBook;
+use crate::deser::DeserializerWithData;
+use crate::enums::MarketStatus;
+use crate::file_iter::Books;
+use crate::files::{self, FilesSource};
+use crate::ids::Clk;
+use crate::immutable::container::SyncObj;
+
+// The market cache of a betfairlightweight StreamListener, for loops that feed the stream in
+// themselves rather than iterating a File. Every update replaces the cached book for its market.
+#[pyclass(name = "Cache")]
+pub struct BflwCache {
+    #[pyo3(get)]
+    clk: Option<SyncObj<Clk>>,
+    #[pyo3(get)]
+    initial_clk: Option<SyncObj<Clk>>,
+    config: Config,
+    books: Books<MarketBook>,
+}
+
+impl BflwCache {
+    // applies every line in bs, returning the books in the order they were updated. A line that
+    // won't parse is logged and ends the chunk, the same as it ends a File
+    fn feed(&mut self, name: &Path, bs: Vec<u8>, py: Python) -> PyResult<Vec<Py<MarketBook>>> {
+        let mut deser = DeserializerWithData::build(bs)
+            .map_err(|err| PyErr::new::<exceptions::PyValueError, _>(err.to_string()))?;
+        let mut updated = Vec::new();
+
+        loop {
+            let next = BflwFile::drive_deserialize(&mut deser, &self.books, self.config, py);
+            self.books.report_skipped(name);
+
+            let update = match next {
+                Ok(update) => update,
+                Err(err) => {
+                    if !err.is_eof() {
+                        warn!(target: "betfair_data", "file: {} err: (JSON Parse Error) {}", name.to_string_lossy(), err);
+                    }
+
+                    break;
+                }
+            };
+
+            if let Some(clk) = update.clk {
+                self.clk = Some(SyncObj::new(clk));
+            }
+            if let Some(initial_clk) = update.initial_clk {
+                self.initial_clk = Some(SyncObj::new(initial_clk));
+            }
+            if let Some(pt) = update.pt && let Some(latency) = self.config.latency_exceeded(pt) {
+                warn!(target: "betfair_data", "file: {} latency high: {}s", name.to_string_lossy(), latency);
+            }
+
+            for mb in update.books {
+                self.books.insert(mb.clone_ref(py), py);
+                updated.push(mb);
+            }
+        }
+
+        Ok(updated)
+    }
+}
+
+#[pymethods]
+impl BflwCache {
+    #[new]
+    #[args(
+        cumulative_runner_tv = "true",
+        calculate_market_tv = "None",
+        streaming_unique_id = "None",
+        calculate_totals = "false",
+        update_clk = "true",
+        max_latency = "None"
+    )]
+    fn __new__(
+        cumulative_runner_tv: bool,
+        calculate_market_tv: Option<bool>,
+        streaming_unique_id: Option<u32>,
+        calculate_totals: bool,
+        update_clk: bool,
+        max_latency: Option<f64>,
+    ) -> Self {
+        let config = Config {
+            cumulative_runner_tv,
+            calculate_market_tv: calculate_market_tv.unwrap_or(cumulative_runner_tv),
+            streaming_unique_id,
+            calculate_totals,
+            update_clk,
+            max_latency,
+        };
+
+        Self {
+            clk: None,
+            initial_clk: None,
+            config,
+            books: Books::new(),
+        }
+    }
+
+    // one or more stream lines, as str or bytes
+    fn on_data(&mut self, data: &PyAny, py: Python) -> PyResult<Vec<Py<MarketBook>>> {
+        let bs = match data.downcast::<PyString>() {
+            Ok(s) => s.to_str()?.as_bytes().to_owned(),
+            Err(_) => data.extract::<&[u8]>()?.to_owned(),
+        };
+
+        self.feed(Path::new("<data>"), bs, py)
+    }
+
+    // whole stream files, with archives expanded and compressed files decompressed
+    fn on_files(&mut self, paths: &PySequence, py: Python) -> PyResult<Vec<Py<MarketBook>>> {
+        let mut updated = Vec::new();
+
+        for file in files::stream_files(FilesSource::get_paths(paths)) {
+            let (path, bs) =
+                file.map_err(|op| PyErr::new::<exceptions::PyRuntimeError, _>(op.err.to_string()))?;
+
+            updated.extend(self.feed(&path, bs, py)?);
+        }
+
+        Ok(updated)
+    }
+
+    // the current book of every market, or just those in market_ids, in the order they first appeared
+    #[args(market_ids = "None")]
+    fn snap(&self, market_ids: Option<Vec<&str>>, py: Python) -> Vec<Py<MarketBook>> {
+        match market_ids {
+            Some(ids) => ids
+                .into_iter()
+                .filter_map(|id| self.books.get(id))
+                .map(|mb| mb.clone_ref(py))
+                .collect(),
+            None => self.books.iter().map(|mb| mb.clone_ref(py)).collect(),
+        }
+    }
+
+    fn get(&self, market_id: &str, py: Python) -> Option<Py<MarketBook>> {
+        self.books.get(market_id).map(|mb| mb.clone_ref(py))
+    }
+
+    // drops closed markets from the cache, returning how many were removed
+    fn clear_closed(&mut self, py: Python) -> usize {
+        let len = self.books.len();
+        self.books
+            .retain(|mb| mb.borrow(py).status != MarketStatus::Closed, py);
+
+        len - self.books.len()
+    }
+
+    fn clear(&mut self) {
+        self.books = Books::new();
+    }
+
+    fn __len__(&self) -> usize {
+        self.books.len()
+    }
+
+    fn __contains__(&self, market_id: &str) -> bool {
+        self.books.position(market_id).is_some()
+    }
+
+    #[getter]
+    fn stream_unique_id(&self) -> Option<u32> {
+        self.config.streaming_unique_id
+    }
+}
@@ -1,14 +1,41 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::datetime::DateTime;
 use crate::market_source::ConfigProducer;
 
+// the betfairlightweight StreamListener options, calculate_market_tv follows cumulative_runner_tv
+// when it isn't given
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
     pub cumulative_runner_tv: bool,
+    pub calculate_market_tv: bool,
     pub streaming_unique_id: Option<u32>,
+    pub calculate_totals: bool,
+    pub update_clk: bool,
+    pub max_latency: Option<f64>,
+}
+
+impl Config {
+    // matches the listener, which warns when a message is processed more than max_latency
+    // seconds after it was published
+    pub fn latency_exceeded(&self, pt: DateTime) -> Option<f64> {
+        let max_latency = self.max_latency?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        let latency = now.saturating_sub(*pt) as f64 / 1000.0;
+
+        (latency > max_latency).then_some(latency)
+    }
 }
 
 pub struct ConfigBuilder {
     pub cumulative_runner_tv: bool,
+    pub calculate_market_tv: bool,
     pub streaming_unique_id: Option<u32>,
+    pub calculate_totals: bool,
+    pub update_clk: bool,
+    pub max_latency: Option<f64>,
 }
 // clone increments the streaming_unique_id, so that each File has a Config with a unique id
 impl ConfigProducer for ConfigBuilder {
@@ -17,11 +44,15 @@ impl ConfigProducer for ConfigBuilder {
     fn get(&mut self) -> Self::Config {
         let c = Config {
             cumulative_runner_tv: self.cumulative_runner_tv,
+            calculate_market_tv: self.calculate_market_tv,
             streaming_unique_id: self.streaming_unique_id,
+            calculate_totals: self.calculate_totals,
+            update_clk: self.update_clk,
+            max_latency: self.max_latency,
         };
 
         self.streaming_unique_id = self.streaming_unique_id.map(|id| id + 1);
-        
+
         c
     }
 }
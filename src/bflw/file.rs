@@ -1,13 +1,16 @@
 use log::warn;
 use pyo3::types::PySequence;
 use pyo3::{exceptions, prelude::*};
-use serde::de::DeserializeSeed;
 use std::path::PathBuf;
 
 use super::config::{Config, ConfigBuilder};
-use super::market_book::{MarketBook, MarketBooksDeser};
-use crate::deser::DeserializerWithData;
+use super::market_book::{MarketBook, MarketBooksDeser, StreamUpdate};
+use crate::datetime::DateTime;
+use crate::deser::{DeserError, DeserializerWithData};
+use crate::file_iter::{Books, MarketID};
 use crate::files::FilesSource;
+use crate::history::{self, DefinitionChange};
+use crate::ids::Clk;
 use crate::immutable::container::SyncObj;
 use crate::market_source::{Adapter, SourceItem};
 
@@ -19,19 +22,37 @@ pub struct BflwFiles {
 #[pymethods]
 impl BflwFiles {
     #[new]
-    #[args(cumulative_runner_tv = "true", streaming_unique_id = "None")]
+    #[args(
+        cumulative_runner_tv = "true",
+        calculate_market_tv = "None",
+        streaming_unique_id = "None",
+        split_markets = "false",
+        calculate_totals = "false",
+        update_clk = "true",
+        max_latency = "None"
+    )]
+    #[allow(clippy::too_many_arguments)]
     fn __new__(
         paths: &PySequence,
         cumulative_runner_tv: bool,
+        calculate_market_tv: Option<bool>,
         streaming_unique_id: Option<u32>,
+        split_markets: bool,
+        calculate_totals: bool,
+        update_clk: bool,
+        max_latency: Option<f64>,
     ) -> PyResult<Self> {
         let config = ConfigBuilder {
             cumulative_runner_tv,
+            calculate_market_tv: calculate_market_tv.unwrap_or(cumulative_runner_tv),
             streaming_unique_id,
+            calculate_totals,
+            update_clk,
+            max_latency,
         };
 
         let paths = FilesSource::get_paths(paths);
-        let fs = FilesSource::new(paths).map_err(|op: std::io::Error| {
+        let fs = FilesSource::new(paths, split_markets).map_err(|op: std::io::Error| {
             PyErr::new::<exceptions::PyRuntimeError, _>(op.to_string())
         })?;
 
@@ -53,27 +74,81 @@ impl BflwFiles {
 pub struct BflwFile {
     #[pyo3(get)]
     file_name: SyncObj<PathBuf>,
+    #[pyo3(get)]
+    clk: Option<SyncObj<Clk>>,
+    #[pyo3(get)]
+    initial_clk: Option<SyncObj<Clk>>,
     config: Config,
     deser: Option<DeserializerWithData>,
-    books: Vec<Py<MarketBook>>,
+    books: Books<MarketBook>,
 }
 
 impl BflwFile {
-    fn drive_deserialize(
+    pub fn drive_deserialize(
         deser: &mut DeserializerWithData,
-        books: &[Py<MarketBook>],
+        books: &Books<MarketBook>,
         config: Config,
         py: Python,
-    ) -> Result<Vec<Py<MarketBook>>, serde_json::Error> {
+    ) -> Result<StreamUpdate, DeserError> {
         deser.with_dependent_mut(|_, deser| {
-            MarketBooksDeser {
+            deser.deserialize_next(MarketBooksDeser {
                 markets: books,
                 py,
                 config,
-            }
-            .deserialize(&mut deser.0)
+            })
         })
     }
+
+    pub fn next_books(&mut self, py: Python) -> Option<Vec<Py<MarketBook>>> {
+        let next_books = {
+            let mut deser = self.deser.take().expect("Iter without deser");
+
+            let books = &self.books;
+
+            let next_books = match Self::drive_deserialize(&mut deser, books, self.config, py) {
+                Ok(update) => Some(update),
+                Err(err) => {
+                    if !err.is_eof() {
+                        warn!(target: "betfair_data", "file: {} err: (JSON Parse Error) {}", self.file_name.to_string_lossy(), err);
+                    }
+
+                    None
+                }
+            };
+
+            self.deser = Some(deser);
+            self.books.report_skipped(&self.file_name);
+
+            next_books
+        };
+
+        let update = next_books?;
+
+        if let Some(clk) = update.clk {
+            self.clk = Some(SyncObj::new(clk));
+        }
+        if let Some(initial_clk) = update.initial_clk {
+            self.initial_clk = Some(SyncObj::new(initial_clk));
+        }
+        if let Some(pt) = update.pt {
+            self.check_latency(pt);
+        }
+
+        update
+            .books
+            .into_iter()
+            .for_each(|mb| self.books.insert(mb, py));
+
+        // every market is returned, in the order they were first seen with new markets added to
+        // the end
+        Some(self.books.iter().map(|mb| mb.clone_ref(py)).collect())
+    }
+
+    fn check_latency(&self, pt: DateTime) {
+        if let Some(latency) = self.config.latency_exceeded(pt) {
+            warn!(target: "betfair_data", "file: {} latency high: {}s", self.file_name.to_string_lossy(), latency);
+        }
+    }
 }
 
 impl From<(SourceItem, Config)> for BflwFile {
@@ -82,8 +157,10 @@ impl From<(SourceItem, Config)> for BflwFile {
 
         Self {
             file_name: SyncObj::new(item.file),
+            clk: None,
+            initial_clk: None,
             deser: Some(item.deser),
-            books: Vec::new(),
+            books: Books::new(),
             config,
         }
     }
@@ -92,16 +169,32 @@ impl From<(SourceItem, Config)> for BflwFile {
 #[pymethods]
 impl BflwFile {
     #[new]
-    #[args(cumulative_runner_tv = "true", streaming_unique_id = "None")]
+    #[args(
+        cumulative_runner_tv = "true",
+        calculate_market_tv = "None",
+        streaming_unique_id = "None",
+        calculate_totals = "false",
+        update_clk = "true",
+        max_latency = "None"
+    )]
+    #[allow(clippy::too_many_arguments)]
     fn __new__(
         file: PathBuf,
         bytes: &[u8],
         cumulative_runner_tv: bool,
+        calculate_market_tv: Option<bool>,
         streaming_unique_id: Option<u32>,
+        calculate_totals: bool,
+        update_clk: bool,
+        max_latency: Option<f64>,
     ) -> PyResult<Self> {
         let config = Config {
             cumulative_runner_tv,
+            calculate_market_tv: calculate_market_tv.unwrap_or(cumulative_runner_tv),
             streaming_unique_id,
+            calculate_totals,
+            update_clk,
+            max_latency,
         };
 
         let deser = DeserializerWithData::build(bytes.to_owned())
@@ -109,8 +202,10 @@ impl BflwFile {
 
         Ok(Self {
             file_name: SyncObj::new(file),
+            clk: None,
+            initial_clk: None,
             deser: Some(deser),
-            books: Vec::new(),
+            books: Books::new(),
             config,
         })
     }
@@ -120,37 +215,29 @@ impl BflwFile {
     }
 
     fn __next__(mut slf: PyRefMut<Self>) -> Option<PyObject> {
-        let next_books = {
-            let config = slf.config;
-            let mut deser = slf.deser.take().expect("Iter without deser");
-
-            let books = &slf.books;
-
-            let next_books = match Self::drive_deserialize(&mut deser, books, config, slf.py()) {
-                Ok(bs) => Some(bs),
-                Err(err) => {
-                    if !err.is_eof() {
-                        warn!(target: "betfair_data", "file: {} err: (JSON Parse Error) {}", slf.file_name.to_string_lossy(), err);
-                    }
-
-                    None
-                }
-            };
-
-            slf.deser = Some(deser);
-
-            next_books
-        };
-
-        if let Some(next_books) = &next_books {
-            slf.books.clone_from(next_books);
-        }
-
-        next_books.map(|bs| bs.into_py(slf.py()))
+        let py = slf.py();
+        slf.next_books(py).map(|bs| bs.into_py(py))
     }
 
     #[getter]
     fn stream_unique_id(&self) -> Option<u32> {
         self.config.streaming_unique_id
     }
+
+    // every version of each market's definition in the file, with the fields that changed
+    fn definition_history(&self, py: Python) -> PyResult<Vec<DefinitionChange>> {
+        let bs = self
+            .deser
+            .as_ref()
+            .expect("Iter without deser")
+            .borrow_owner();
+
+        history::definition_history(&self.file_name, bs, py)
+    }
+}
+
+impl MarketID for MarketBook {
+    fn id(&self) -> &str {
+        self.market_id.as_str()
+    }
 }
@@ -7,9 +7,20 @@ use serde::{
 
 use std::fmt;
 
+use crate::pickle::{Decoder, Encoder, Pickle};
+
 #[derive(Debug, Copy, Clone, Default, PartialEq)]
 pub struct FloatStr(pub f64);
 
+impl Pickle for FloatStr {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.0.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        f64::decode(d, py).map(FloatStr)
+    }
+}
+
 impl From<FloatStr> for f64 {
     fn from(v: FloatStr) -> f64 {
         v.0
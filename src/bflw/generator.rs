@@ -0,0 +1,132 @@
+use pyo3::types::PyDict;
+use pyo3::{exceptions, prelude::*};
+use std::path::PathBuf;
+
+use super::config::{Config, ConfigBuilder};
+use super::file::BflwFile;
+use crate::files::FilesSource;
+use crate::market_source::Adapter;
+
+// Stands in for APIClient.streaming.create_historical_generator_stream, taking the options the
+// StreamListener would have been built with. Defaults are the StreamListener's, which differ
+// from bflw.Files: runner and market tv are read from the stream and latency is checked
+#[pyfunction]
+#[args(
+    listener_options = "None",
+    operation = "\"marketSubscription\"",
+    unique_id = "0"
+)]
+pub fn create_historical_generator_stream(
+    file_path: PathBuf,
+    listener_options: Option<&PyDict>,
+    operation: &str,
+    unique_id: u32,
+) -> PyResult<HistoricalGeneratorStream> {
+    if operation != "marketSubscription" {
+        return Err(PyErr::new::<exceptions::PyValueError, _>(format!(
+            "unsupported operation: {}, only marketSubscription streams can be read",
+            operation
+        )));
+    }
+
+    let mut config = Config {
+        cumulative_runner_tv: false,
+        calculate_market_tv: false,
+        streaming_unique_id: Some(unique_id),
+        calculate_totals: false,
+        update_clk: true,
+        max_latency: Some(0.5),
+    };
+
+    for (k, v) in listener_options.into_iter().flatten() {
+        match k.extract::<&str>()? {
+            "cumulative_runner_tv" => config.cumulative_runner_tv = v.extract()?,
+            "calculate_market_tv" => config.calculate_market_tv = v.extract()?,
+            "calculate_totals" => config.calculate_totals = v.extract()?,
+            "update_clk" => config.update_clk = v.extract()?,
+            "max_latency" => config.max_latency = v.extract()?,
+            "lightweight" if v.is_true()? => {
+                return Err(PyErr::new::<exceptions::PyValueError, _>(
+                    "lightweight listeners are not supported, use the MarketBook objects",
+                ));
+            }
+            // only change how the listener logs and where it sends updates
+            "lightweight" | "debug" | "output_queue" => {}
+            k => {
+                return Err(PyErr::new::<exceptions::PyTypeError, _>(format!(
+                    "unknown listener option: {}",
+                    k
+                )));
+            }
+        }
+    }
+
+    Ok(HistoricalGeneratorStream { file_path, config })
+}
+
+#[pyclass]
+pub struct HistoricalGeneratorStream {
+    #[pyo3(get)]
+    file_path: PathBuf,
+    config: Config,
+}
+
+#[pymethods]
+impl HistoricalGeneratorStream {
+    // betfairlightweight hands back its read loop, calling it starts reading the file
+    fn get_generator(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __call__(&self) -> PyResult<HistoricalGenerator> {
+        if !self.file_path.exists() {
+            return Err(PyErr::new::<exceptions::PyFileNotFoundError, _>(format!(
+                "No such file: {}",
+                self.file_path.to_string_lossy()
+            )));
+        }
+
+        let config = ConfigBuilder {
+            cumulative_runner_tv: self.config.cumulative_runner_tv,
+            calculate_market_tv: self.config.calculate_market_tv,
+            streaming_unique_id: self.config.streaming_unique_id,
+            calculate_totals: self.config.calculate_totals,
+            update_clk: self.config.update_clk,
+            max_latency: self.config.max_latency,
+        };
+
+        let fs = FilesSource::new(vec![self.file_path.clone()], false).map_err(
+            |op: std::io::Error| PyErr::new::<exceptions::PyRuntimeError, _>(op.to_string()),
+        )?;
+
+        Ok(HistoricalGenerator {
+            adapter: Adapter::new(fs, config),
+            file: None,
+        })
+    }
+}
+
+// yields the list of updated books for each line, running on through every stream file in an
+// archive the way reading them one after another would
+#[pyclass]
+pub struct HistoricalGenerator {
+    adapter: Adapter<ConfigBuilder, BflwFile>,
+    file: Option<BflwFile>,
+}
+
+#[pymethods]
+impl HistoricalGenerator {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> Option<PyObject> {
+        loop {
+            if let Some(file) = &mut self.file && let Some(books) = file.next_books(py) {
+                return Some(books.into_py(py));
+            }
+
+            self.file = Some(self.adapter.next()?);
+        }
+    }
+}
@@ -1,20 +1,25 @@
 use pyo3::prelude::*;
 use serde::de::{DeserializeSeed, Error, IgnoredAny, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
-use serde_json::value::RawValue;
+use std::borrow::Cow;
 use std::fmt;
 use std::sync::Arc;
 
 use super::config::Config;
+use super::market_catalogue::MarketCatalogue;
 use super::market_definition::{MarketDefinition, MarketDefinitionKeyLine, PriceLadderDescription};
 use super::runner_book::RunnerBook;
 use crate::bflw::market_definition::MarketDefinitionDeser;
 use crate::bflw::runner_book::RunnerChangeSeq;
 use crate::bflw::RoundToCents;
-use crate::datetime::{DateTime, DateTimeString};
+use crate::datetime::DateTime;
+use crate::deser::{self, Buffered};
 use crate::enums::MarketStatus;
-use crate::ids::MarketID;
+use crate::file_iter::Books;
+use crate::ids::{Clk, MarketID, RunnerIndex};
+use crate::image::{self, McImage};
 use crate::immutable::container::SyncObj;
+use crate::pickle::{self, Decoder, Encoder, Kind, Pickle};
 
 #[pyclass]
 pub struct MarketBook {
@@ -45,7 +50,7 @@ pub struct MarketBook {
     #[pyo3(get)]
     pub status: MarketStatus,
     #[pyo3(get)]
-    pub total_available: Option<()>, // f64 but bflw doesnt seem to use this on historic files
+    pub total_available: Option<f64>,
     #[pyo3(get)]
     pub total_matched: f64,
     #[pyo3(get)]
@@ -56,21 +61,103 @@ pub struct MarketBook {
     pub market_definition: Py<MarketDefinition>,
     #[pyo3(get)]
     pub market_id: SyncObj<MarketID>,
+    // total_available and last_match_time are only set with calculate_totals
     #[pyo3(get)]
-    pub last_match_time: Option<SyncObj<DateTimeString>>,
+    pub last_match_time: Option<DateTime>,
+    // positions of the runners by selection id, shared with the next book unless it adds runners
+    index: Arc<RunnerIndex>,
+}
+
+impl Pickle for MarketBook {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.streaming_unique_id.encode(e, py);
+        self.publish_time.encode(e, py);
+        self.bet_delay.encode(e, py);
+        self.bsp_reconciled.encode(e, py);
+        self.complete.encode(e, py);
+        self.cross_matching.encode(e, py);
+        self.inplay.encode(e, py);
+        self.is_market_data_delayed.encode(e, py);
+        self.number_of_active_runners.encode(e, py);
+        self.number_of_runners.encode(e, py);
+        self.number_of_winners.encode(e, py);
+        self.runners_voidable.encode(e, py);
+        self.status.encode(e, py);
+        self.total_available.encode(e, py);
+        self.total_matched.encode(e, py);
+        self.version.encode(e, py);
+        self.runners.encode(e, py);
+        self.market_definition.encode(e, py);
+        self.market_id.encode(e, py);
+        self.last_match_time.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        let mut mb = Self {
+            streaming_unique_id: Pickle::decode(d, py)?,
+            publish_time: Pickle::decode(d, py)?,
+            bet_delay: Pickle::decode(d, py)?,
+            bsp_reconciled: Pickle::decode(d, py)?,
+            complete: Pickle::decode(d, py)?,
+            cross_matching: Pickle::decode(d, py)?,
+            inplay: Pickle::decode(d, py)?,
+            is_market_data_delayed: Pickle::decode(d, py)?,
+            number_of_active_runners: Pickle::decode(d, py)?,
+            number_of_runners: Pickle::decode(d, py)?,
+            number_of_winners: Pickle::decode(d, py)?,
+            runners_voidable: Pickle::decode(d, py)?,
+            status: Pickle::decode(d, py)?,
+            total_available: Pickle::decode(d, py)?,
+            total_matched: Pickle::decode(d, py)?,
+            version: Pickle::decode(d, py)?,
+            runners: Pickle::decode(d, py)?,
+            market_definition: Pickle::decode(d, py)?,
+            market_id: Pickle::decode(d, py)?,
+            last_match_time: Pickle::decode(d, py)?,
+            index: Default::default(),
+        };
+        mb.index = Arc::new(RunnerIndex::new(
+            mb.runners.iter().map(|r| r.borrow(py).selection_id),
+        ));
+
+        Ok(mb)
+    }
 }
 
 #[derive(Default)]
-struct MarketBookUpdate<'a> {
-    market_id: Option<&'a str>,
+struct MarketBookUpdate {
     streaming_unique_id: Option<u32>,
     definition: Option<MarketDefinition>,
     runners: Option<Vec<Py<RunnerBook>>>,
+    // only set if the index had to change for the runners
+    index: Option<RunnerIndex>,
     total_volume: Option<f64>,
+    total_available: Option<f64>,
+    last_match_time: Option<DateTime>,
+    img: bool,
 }
 
 #[pymethods]
 impl MarketBook {
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        pickle::reduce(Kind::MarketBook, self, py)
+    }
+    // books are never changed once yielded, so copies can share the same object
+    fn __copy__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+    fn __deepcopy__(slf: PyRef<Self>, _memo: &PyAny) -> PyRef<Self> {
+        slf
+    }
+    // a full image of the market as a stream mc entry
+    fn to_json(&self, py: Python) -> PyResult<String> {
+        let def = self.market_definition.borrow(py);
+        image::to_json(&self.image(&def, py))
+    }
+    fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        let def = self.market_definition.borrow(py);
+        image::to_dict(&self.image(&def, py), py)
+    }
+
     #[getter(publish_time_epoch)]
     fn get_publish_time_epoch(&self, py: Python) -> PyObject {
         let ts = *self.publish_time;
@@ -94,36 +181,31 @@ impl MarketBook {
             .as_ref()
             .map(|p| p.clone_ref(py))
     }
+
+    // the catalogue betfairlightweight would have from list_market_catalogue, as far as the
+    // definition can fill it in
+    fn market_catalogue(&self, py: Python) -> PyResult<MarketCatalogue> {
+        let def = self.market_definition.borrow(py);
+        MarketCatalogue::new(self, &def, py)
+    }
 }
 
 impl MarketBook {
-    fn new(change: MarketBookUpdate, py: Python) -> Self {
-        // What to do when definition or market id arnt present (we currently panic)
-        let def = change.definition.unwrap(); // fix unwrap
-
-        // maybe theres a better way to calculate this
-        // let available = change
-        //     .runners
-        //     .as_ref()
-        //     .map(|rs| {
-        //         rs.iter()
-        //             .map(|r| {
-        //                 let r = r.borrow(py);
-        //                 let ex = r.ex.borrow(py);
-        //                 let back: f64 = ex.available_to_back.value.iter().map(|ps| ps.size).sum();
-        //                 let lay: f64 = ex.available_to_lay.value.iter().map(|ps| ps.size).sum();
-        //                 back + lay
-        //             })
-        //             .sum::<f64>()
-        //     })
-        //     .unwrap_or_default();
+    // the definition lives in its own python object, so the caller holds the borrow of it
+    pub fn image<'a>(&'a self, def: &'a MarketDefinition, py: Python) -> McImage<'a> {
+        McImage {
+            id: self.market_id.as_str(),
+            market_definition: def.image(py),
+            rc: self.runners.iter().map(|r| r.borrow(py).image(py)).collect(),
+            img: true,
+            tv: self.total_matched,
+        }
+    }
 
+    fn new(id: MarketID, def: MarketDefinition, change: MarketBookUpdate, py: Python) -> Self {
         Self {
             streaming_unique_id: change.streaming_unique_id,
-            market_id: change
-                .market_id
-                .map(|mid| SyncObj::new(MarketID::try_from(mid).unwrap()))
-                .unwrap(),
+            market_id: SyncObj::new(id),
             runners: SyncObj::new(Arc::new(change.runners.unwrap_or_default())),
             total_matched: change.total_volume.unwrap_or_default(),
             bet_delay: def.bet_delay,
@@ -138,35 +220,27 @@ impl MarketBook {
             status: def.status,
             number_of_winners: def.number_of_winners,
             version: def.version,
-            total_available: None, // available,
+            total_available: change.total_available,
             market_definition: Py::new(py, def).unwrap(),
 
             publish_time: DateTime::new(0),
-            last_match_time: None,
+            last_match_time: change.last_match_time,
+            index: Arc::new(change.index.unwrap_or_default()),
         }
     }
 
     fn update_from_change(&self, change: MarketBookUpdate, py: Python) -> Self {
-        // let available = change.runners.as_ref().map(|rs| {
-        //     rs.iter()
-        //         .map(|r| {
-        //             let r = r.borrow(py);
-        //             let ex = r.ex.borrow(py);
-        //             let back: f64 = ex.available_to_back.value.iter().map(|ps| ps.size).sum();
-        //             let lay: f64 = ex.available_to_lay.value.iter().map(|ps| ps.size).sum();
-        //             back + lay
-        //         })
-        //         .sum::<f64>()
-        // });
-
         Self {
             streaming_unique_id: self.streaming_unique_id,
             market_id: self.market_id.clone(),
-            runners: change
-                .runners
-                .map(|r| SyncObj::new(Arc::new(r)))
-                .unwrap_or_else(|| self.runners.clone()),
-            total_matched: change.total_volume.unwrap_or(self.total_matched),
+            runners: match change.runners {
+                Some(r) => SyncObj::new(Arc::new(r)),
+                None if change.img => Default::default(),
+                None => self.runners.clone(),
+            },
+            total_matched: change
+                .total_volume
+                .unwrap_or(if change.img { 0.0 } else { self.total_matched }),
             bet_delay: change
                 .definition
                 .as_ref()
@@ -223,25 +297,38 @@ impl MarketBook {
                 .as_ref()
                 .map(|def| def.version)
                 .unwrap_or(self.version),
-            total_available: None, // available.unwrap_or(self.total_available),
+            total_available: change.total_available.or(self.total_available),
             market_definition: change
                 .definition
                 .map(|def| Py::new(py, def).unwrap())
                 .unwrap_or_else(|| self.market_definition.clone()),
 
             publish_time: self.publish_time,
-            last_match_time: None,
+            last_match_time: change.last_match_time.or(self.last_match_time),
+            index: change
+                .index
+                .map(Arc::new)
+                .unwrap_or_else(|| self.index.clone()),
         }
     }
 }
 
+// a stream message, with the market books it updated and the parts of the envelope the
+// listener tracks
+pub struct StreamUpdate {
+    pub books: Vec<Py<MarketBook>>,
+    pub pt: Option<DateTime>,
+    pub clk: Option<Clk>,
+    pub initial_clk: Option<Clk>,
+}
+
 pub struct MarketBooksDeser<'a, 'py> {
-    pub markets: &'a [Py<MarketBook>],
+    pub markets: &'a Books<MarketBook>,
     pub py: Python<'py>,
     pub config: Config,
 }
 impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketBooksDeser<'a, 'py> {
-    type Value = Vec<Py<MarketBook>>;
+    type Value = StreamUpdate;
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
@@ -252,17 +339,22 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketBooksDeser<'a, 'py> {
         enum Field {
             Op,
             Clk,
+            #[serde(rename = "initialClk")]
+            InitialClk,
             Pt,
             Mc,
+            // ct, status and the fields of connection/status lines
+            #[serde(other)]
+            Other,
         }
 
         struct MarketBooksDeserVisitor<'a, 'py> {
-            markets: &'a [Py<MarketBook>],
+            markets: &'a Books<MarketBook>,
             py: Python<'py>,
             config: Config,
         }
         impl<'de, 'a, 'py> Visitor<'de> for MarketBooksDeserVisitor<'a, 'py> {
-            type Value = Vec<Py<MarketBook>>;
+            type Value = StreamUpdate;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("")
@@ -273,22 +365,31 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketBooksDeser<'a, 'py> {
                 V: MapAccess<'de>,
             {
                 let mut pt: Option<DateTime> = None;
+                let mut clk: Option<Clk> = None;
+                let mut initial_clk: Option<Clk> = None;
                 let mut next_books: Vec<Py<MarketBook>> = Vec::new();
 
                 while let Some(key) = map.next_key()? {
                     match key {
-                        Field::Op => {
+                        Field::Op | Field::Other => {
                             map.next_value::<IgnoredAny>()?;
                         }
                         Field::Pt => {
                             pt = Some(DateTime::new(map.next_value::<u64>()?));
                         }
-                        Field::Clk => {
+                        Field::Clk if self.config.update_clk => {
+                            clk = Some(map.next_value::<Clk>()?);
+                        }
+                        Field::InitialClk if self.config.update_clk => {
+                            initial_clk = Some(map.next_value::<Clk>()?);
+                        }
+                        Field::Clk | Field::InitialClk => {
                             map.next_value::<IgnoredAny>()?;
                         }
                         Field::Mc => {
                             next_books = map.next_value_seed(MarketMcSeq {
                                 markets: self.markets,
+                                pt,
                                 py: self.py,
                                 config: self.config,
                             })?;
@@ -302,31 +403,16 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketBooksDeser<'a, 'py> {
                         .for_each(|mb| mb.borrow_mut(self.py).publish_time = pt);
                 }
 
-                // merge next_books and markets with missing clone_ref's
-                for (i, mb) in self.markets.iter().enumerate() {
-                    let rmb = mb.borrow(self.py);
-                    let pos = next_books
-                        .iter()
-                        .position(|nmb| *nmb.borrow(self.py).market_id == *rmb.market_id);
-
-                    match pos {
-                        Some(ni) if i != ni => next_books.swap(i, ni),
-                        None => {
-                            next_books.push(mb.clone_ref(self.py));
-
-                            let ni = next_books.len() - 1;
-                            next_books.swap(i, ni);
-                        }
-                        // Some with ni and i in same location - do nothing
-                        _ => {}
-                    }
-                }
-
-                Ok(next_books)
+                Ok(StreamUpdate {
+                    books: next_books,
+                    pt,
+                    clk,
+                    initial_clk,
+                })
             }
         }
 
-        const FIELDS: &[&str] = &["op", "pt", "clk", "mc"];
+        const FIELDS: &[&str] = &["op", "pt", "clk", "initialClk", "mc"];
         deserializer.deserialize_struct(
             "MarketBook",
             FIELDS,
@@ -339,9 +425,11 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketBooksDeser<'a, 'py> {
     }
 }
 
-// Used for serializing in place over the marketChange `mc` array
+// Used for serializing in place over the marketChange `mc` array, pt is taken from the message
+// as far as it's been read, betfair writes it ahead of mc
 struct MarketMcSeq<'a, 'py> {
-    markets: &'a [Py<MarketBook>],
+    markets: &'a Books<MarketBook>,
+    pt: Option<DateTime>,
     py: Python<'py>,
     config: Config,
 }
@@ -353,7 +441,8 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMcSeq<'a, 'py> {
         D: Deserializer<'de>,
     {
         struct MarketMcSeqVisitor<'a, 'py> {
-            markets: &'a [Py<MarketBook>],
+            markets: &'a Books<MarketBook>,
+            pt: Option<DateTime>,
             py: Python<'py>,
             config: Config,
         }
@@ -369,50 +458,47 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMcSeq<'a, 'py> {
                 A: serde::de::SeqAccess<'de>,
             {
                 #[derive(Deserialize)]
-                struct MarketWithID<'a> {
-                    id: &'a str,
+                struct MarketWithID {
+                    id: MarketID,
                     img: Option<bool>,
                 }
 
                 let mut next_books: Vec<Py<MarketBook>> = Vec::with_capacity(self.markets.len());
 
-                while let Some(raw) = seq.next_element::<&RawValue>()? {
-                    let mut deser = serde_json::Deserializer::from_str(raw.get());
-                    let mid: MarketWithID =
-                        serde_json::from_str(raw.get()).map_err(Error::custom)?;
+                while let Some(raw) = seq.next_element::<Buffered>()? {
+                    let mid: MarketWithID = deser::peek(&raw).map_err(Error::custom)?;
 
                     let (mb, i) = {
                         let i = next_books
                             .iter()
-                            .position(|m| (*m).borrow(self.py).market_id.as_str() == mid.id);
+                            .position(|m| (*m).borrow(self.py).market_id.as_str() == mid.id.as_str());
 
                         match i {
-                            Some(i) if !mid.img.contains(&true) => {
-                                (next_books.get(i).map(|m| m.borrow(self.py)), Some(i))
-                            }
-                            Some(i) if mid.img.contains(&true) => (None, Some(i)),
-                            None if !mid.img.contains(&true) => (
-                                self.markets
-                                    .iter()
-                                    .find(|m| (*m).borrow(self.py).market_id.as_str() == mid.id)
-                                    .map(|o| o.borrow(self.py)),
+                            Some(i) => (next_books.get(i).map(|m| m.borrow(self.py)), Some(i)),
+                            None => (
+                                self.markets.get(mid.id.as_str()).map(|o| o.borrow(self.py)),
                                 None,
                             ),
-                            _ => (None, None),
                         }
                     };
 
-                    let next_mb = MarketMc {
-                        market: mb,
-                        py: self.py,
-                        config: self.config,
-                    }
-                    .deserialize(&mut deser)
+                    let next_mb = deser::replay(
+                        raw,
+                        MarketMc {
+                            id: mid.id,
+                            market: mb,
+                            img: mid.img.contains(&true),
+                            pt: self.pt,
+                            py: self.py,
+                            config: self.config,
+                        },
+                    )
                     .map_err(Error::custom)?;
 
-                    match i {
-                        Some(i) => next_books[i] = Py::new(self.py, next_mb).unwrap(),
-                        None => next_books.push(Py::new(self.py, next_mb).unwrap()),
+                    match (next_mb, i) {
+                        (Some(mb), Some(i)) => next_books[i] = Py::new(self.py, mb).unwrap(),
+                        (Some(mb), None) => next_books.push(Py::new(self.py, mb).unwrap()),
+                        (None, _) => self.markets.skip(mid.id.as_str()),
                     }
                 }
 
@@ -422,6 +508,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMcSeq<'a, 'py> {
 
         deserializer.deserialize_seq(MarketMcSeqVisitor {
             markets: self.markets,
+            pt: self.pt,
             py: self.py,
             config: self.config,
         })
@@ -429,12 +516,15 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMcSeq<'a, 'py> {
 }
 
 struct MarketMc<'py> {
+    id: MarketID,
     market: Option<PyRef<'py, MarketBook>>,
+    img: bool,
+    pt: Option<DateTime>,
     py: Python<'py>,
     config: Config,
 }
 impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
-    type Value = MarketBook;
+    type Value = Option<MarketBook>;
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
@@ -456,12 +546,15 @@ impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
         }
 
         struct MarketMcVisitor<'py> {
+            id: MarketID,
             market: Option<PyRef<'py, MarketBook>>,
+            img: bool,
+            pt: Option<DateTime>,
             py: Python<'py>,
             config: Config,
         }
         impl<'de, 'py> Visitor<'de> for MarketMcVisitor<'py> {
-            type Value = MarketBook;
+            type Value = Option<MarketBook>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("")
@@ -473,27 +566,38 @@ impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
             {
                 let mut upt = MarketBookUpdate {
                     streaming_unique_id: self.config.streaming_unique_id,
-                    market_id: None,
                     definition: None,
                     runners: None,
+                    index: None,
                     total_volume: None,
+                    total_available: None,
+                    last_match_time: None,
+                    img: self.img,
+                };
+
+                // runners (and their ladders) from before an image are thrown away
+                let prev_runners: Option<&Vec<Py<RunnerBook>>> = match &self.market {
+                    Some(mb) if !self.img => Some(mb.runners.as_ref()),
+                    _ => None,
+                };
+                let mut index: Cow<RunnerIndex> = match &self.market {
+                    Some(mb) if !self.img => Cow::Borrowed(&mb.index),
+                    _ => Cow::Owned(RunnerIndex::default()),
                 };
 
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::Id => {
-                            let s = map.next_value::<&str>()?;
-                            upt.market_id = Some(s);
+                            map.next_value::<IgnoredAny>()?;
                         }
                         Field::MarketDefinition => {
                             let def = self
                                 .market
                                 .as_ref()
                                 .map(|mb| mb.market_definition.borrow(self.py));
-                            let runners = upt
-                                .runners
-                                .as_ref()
-                                .or_else(|| self.market.as_ref().map(|mb| mb.runners.as_ref()));
+                            let runners = upt.runners.as_ref().or(prev_runners);
+
+                            let known = runners.map_or(0, |rs| rs.len());
 
                             let (d, r) = map.next_value_seed(MarketDefinitionDeser {
                                 def,
@@ -502,24 +606,34 @@ impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
                                 config: self.config,
                             })?;
 
+                            // runners new to the definition are added after the existing ones
+                            if let Some(rs) = &r && rs.len() > known {
+                                let index = index.to_mut();
+                                rs[known..]
+                                    .iter()
+                                    .for_each(|r| index.push(r.borrow(self.py).selection_id));
+                            }
+
                             upt.definition = d;
                             upt.runners = r;
                         }
                         Field::Rc => {
-                            let runners = upt
-                                .runners
-                                .as_ref()
-                                .or_else(|| self.market.as_ref().map(|mb| mb.runners.as_ref()));
-                            upt.runners = Some(map.next_value_seed(RunnerChangeSeq {
+                            let runners = upt.runners.as_ref().or(prev_runners);
+                            let (runners, traded) = map.next_value_seed(RunnerChangeSeq {
                                 runners,
+                                index: &mut index,
                                 py: self.py,
                                 config: self.config,
-                            })?);
+                            })?;
+                            upt.runners = Some(runners);
+
+                            if traded && self.config.calculate_totals {
+                                upt.last_match_time = self.pt;
+                            }
 
-                            // if cumulative_runner_tv is on, then tv shouldnt be sent at a market level and will have
-                            // to be derived from the sum of runner tv's. This happens when using the data provided
-                            // from betfair historical data service, not saved from the actual stream
-                            if self.config.cumulative_runner_tv {
+                            // with calculate_market_tv the market tv is derived from the sum of the runner tv's,
+                            // as the betfair historical data service files don't send it at a market level
+                            if self.config.calculate_market_tv {
                                 upt.total_volume = upt
                                     .runners
                                     .as_ref()
@@ -530,7 +644,7 @@ impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
                             }
                         }
                         Field::Tv => {
-                            if !self.config.cumulative_runner_tv {
+                            if !self.config.calculate_market_tv {
                                 upt.total_volume = Some(map.next_value::<f64>()?.round_cent());
                             } else {
                                 map.next_value::<IgnoredAny>()?;
@@ -548,16 +662,35 @@ impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
                     }
                 }
 
-                let mb = match (self.market, &upt.definition) {
-                    (Some(mb), Some(_)) => mb.update_from_change(upt, self.py),
-                    (Some(mb), None) => mb.update_from_change(upt, self.py),
-                    (None, Some(_)) => MarketBook::new(upt, self.py),
-                    (None, None) => {
-                        return Err(Error::custom("missing definition on initial market update"))
-                    }
+                if let Cow::Owned(index) = index {
+                    upt.index = Some(index);
+                }
+
+                if self.config.calculate_totals {
+                    upt.total_available = upt.runners.as_ref().map(|rs| {
+                        rs.iter()
+                            .map(|r| {
+                                let r = r.borrow(self.py);
+                                let ex = r.ex.borrow(self.py);
+                                let back: f64 = ex.available_to_back.iter().map(|ps| ps.size).sum();
+                                let lay: f64 = ex.available_to_lay.iter().map(|ps| ps.size).sum();
+                                back + lay
+                            })
+                            .sum::<f64>()
+                            .round_cent()
+                    });
+                }
+
+                let mb = match self.market {
+                    Some(mb) => mb.update_from_change(upt, self.py),
+                    None => match upt.definition.take() {
+                        Some(def) => MarketBook::new(self.id, def, upt, self.py),
+                        // nothing to build the market from until a definition arrives
+                        None => return Ok(None),
+                    },
                 };
 
-                Ok(mb)
+                Ok(Some(mb))
             }
         }
 
@@ -566,7 +699,10 @@ impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
             "MarketChange",
             FIELDS,
             MarketMcVisitor {
+                id: self.id,
                 market: self.market,
+                img: self.img,
+                pt: self.pt,
                 py: self.py,
                 config: self.config,
             },
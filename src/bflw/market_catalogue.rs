@@ -0,0 +1,233 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::Arc;
+
+use super::market_book::MarketBook;
+use super::market_definition::{MarketDefinition, PriceLadderDescription};
+use super::market_definition_runner::MarketDefinitionRunner;
+use crate::datetime::DateTimeString;
+use crate::enums::MarketBettingType;
+use crate::ids::MarketID;
+use crate::immutable::container::SyncObj;
+use crate::strings::FixedSizeString;
+
+// The betfairlightweight MarketCatalogue, filled in from what the stream definition carries.
+// Competitions, rules, wallet and event type names only come from the betting api, so are None
+
+#[pyclass]
+pub struct MarketCatalogue {
+    #[pyo3(get)]
+    market_id: SyncObj<MarketID>,
+    #[pyo3(get)]
+    market_name: Option<SyncObj<Arc<str>>>,
+    #[pyo3(get)]
+    market_start_time: SyncObj<DateTimeString>,
+    #[pyo3(get)]
+    total_matched: f64,
+    #[pyo3(get)]
+    description: Py<MarketCatalogueDescription>,
+    #[pyo3(get)]
+    event: Py<Event>,
+    #[pyo3(get)]
+    event_type: Py<EventType>,
+    #[pyo3(get)]
+    runners: Vec<Py<RunnerCatalogue>>,
+}
+
+#[pymethods]
+impl MarketCatalogue {
+    #[getter(competition)]
+    fn get_competition(&self, py: Python) -> PyObject {
+        py.None()
+    }
+}
+
+impl MarketCatalogue {
+    pub fn new(book: &MarketBook, def: &MarketDefinition, py: Python) -> PyResult<Self> {
+        let runners = def
+            .runners
+            .iter()
+            .map(|r| Py::new(py, RunnerCatalogue::new(&r.borrow(py))))
+            .collect::<PyResult<Vec<_>>>()?;
+
+        Ok(Self {
+            market_id: book.market_id.clone(),
+            market_name: def.name.clone(),
+            market_start_time: def.market_time.clone(),
+            total_matched: book.total_matched,
+            description: Py::new(py, MarketCatalogueDescription::new(def, py))?,
+            event: Py::new(py, Event::new(def))?,
+            event_type: Py::new(py, EventType::new(def))?,
+            runners,
+        })
+    }
+}
+
+#[pyclass]
+pub struct MarketCatalogueDescription {
+    #[pyo3(get)]
+    betting_type: MarketBettingType,
+    #[pyo3(get)]
+    bsp_market: bool,
+    #[pyo3(get)]
+    discount_allowed: bool,
+    #[pyo3(get)]
+    market_base_rate: f32,
+    #[pyo3(get)]
+    market_time: SyncObj<DateTimeString>,
+    #[pyo3(get)]
+    market_type: SyncObj<Arc<str>>,
+    #[pyo3(get)]
+    persistence_enabled: bool,
+    #[pyo3(get)]
+    regulator: Option<String>,
+    #[pyo3(get)]
+    suspend_time: Option<SyncObj<DateTimeString>>,
+    #[pyo3(get)]
+    turn_in_play_enabled: bool,
+    #[pyo3(get)]
+    race_type: Option<SyncObj<Arc<str>>>,
+    #[pyo3(get)]
+    each_way_divisor: Option<f64>,
+    #[pyo3(get)]
+    price_ladder_description: Option<Py<PriceLadderDescription>>,
+}
+
+#[pymethods]
+impl MarketCatalogueDescription {
+    #[getter(rules)]
+    fn get_rules(&self, py: Python) -> PyObject {
+        py.None()
+    }
+    #[getter(rules_has_date)]
+    fn get_rules_has_date(&self, py: Python) -> PyObject {
+        py.None()
+    }
+    #[getter(wallet)]
+    fn get_wallet(&self, py: Python) -> PyObject {
+        py.None()
+    }
+    #[getter(clarifications)]
+    fn get_clarifications(&self, py: Python) -> PyObject {
+        py.None()
+    }
+    #[getter(line_range_info)]
+    fn get_line_range_info(&self, py: Python) -> PyObject {
+        py.None()
+    }
+}
+
+impl MarketCatalogueDescription {
+    fn new(def: &MarketDefinition, py: Python) -> Self {
+        Self {
+            betting_type: def.betting_type,
+            bsp_market: def.bsp_market,
+            discount_allowed: def.discount_allowed,
+            market_base_rate: def.market_base_rate,
+            market_time: def.market_time.clone(),
+            market_type: def.market_type.clone(),
+            persistence_enabled: def.persistence_enabled,
+            regulator: def.regulators.first().cloned(),
+            suspend_time: def.suspend_time.clone(),
+            turn_in_play_enabled: def.turn_in_play_enabled,
+            race_type: def.race_type.clone(),
+            each_way_divisor: def.each_way_divisor,
+            price_ladder_description: def
+                .price_ladder_definition
+                .as_ref()
+                .map(|p| p.clone_ref(py)),
+        }
+    }
+}
+
+#[pyclass]
+pub struct Event {
+    id: u32,
+    #[pyo3(get)]
+    name: Option<SyncObj<Arc<str>>>,
+    #[pyo3(get)]
+    country_code: Option<SyncObj<FixedSizeString<2>>>,
+    #[pyo3(get)]
+    time_zone: SyncObj<Arc<str>>,
+    #[pyo3(get)]
+    venue: Option<SyncObj<Arc<str>>>,
+    #[pyo3(get)]
+    open_date: SyncObj<DateTimeString>,
+}
+
+#[pymethods]
+impl Event {
+    #[getter(id)]
+    fn get_id(&self) -> String {
+        self.id.to_string()
+    }
+}
+
+impl Event {
+    fn new(def: &MarketDefinition) -> Self {
+        Self {
+            id: def.event_id,
+            name: def.event_name.clone(),
+            country_code: def.country_code.clone(),
+            time_zone: def.timezone.clone(),
+            venue: def.venue.clone(),
+            open_date: def.open_date.clone(),
+        }
+    }
+}
+
+#[pyclass]
+pub struct EventType {
+    id: u32,
+}
+
+impl EventType {
+    fn new(def: &MarketDefinition) -> Self {
+        Self {
+            id: def.event_type_id,
+        }
+    }
+}
+
+#[pymethods]
+impl EventType {
+    #[getter(id)]
+    fn get_id(&self) -> String {
+        self.id.to_string()
+    }
+    #[getter(name)]
+    fn get_name(&self, py: Python) -> PyObject {
+        py.None()
+    }
+}
+
+#[pyclass]
+pub struct RunnerCatalogue {
+    #[pyo3(get)]
+    selection_id: u32,
+    #[pyo3(get)]
+    runner_name: Option<SyncObj<Arc<str>>>,
+    #[pyo3(get)]
+    sort_priority: u16,
+    #[pyo3(get)]
+    handicap: f64,
+}
+
+#[pymethods]
+impl RunnerCatalogue {
+    #[getter(metadata)]
+    fn get_metadata<'py>(&self, py: Python<'py>) -> &'py PyDict {
+        PyDict::new(py)
+    }
+}
+
+impl RunnerCatalogue {
+    fn new(runner: &MarketDefinitionRunner) -> Self {
+        Self {
+            selection_id: runner.selection_id.id(),
+            runner_name: runner.name.clone(),
+            sort_priority: runner.sort_priority,
+            handicap: runner.selection_id.handicap().unwrap_or(0.0) as f64,
+        }
+    }
+}
@@ -13,7 +13,9 @@ use crate::datetime::DateTimeString;
 use crate::enums::{MarketBettingType, MarketStatus, PriceLadderDefinition};
 use crate::errors::DataError;
 use crate::ids::{EventID, EventTypeID};
+use crate::image::{DefinitionImage, KeyLineImage, KeyLineSelectionImage, PriceLadderImage};
 use crate::immutable::container::SyncObj;
+use crate::pickle::{Decoder, Encoder, Pickle};
 use crate::strings::FixedSizeString;
 
 #[derive(Debug)]
@@ -95,6 +97,89 @@ pub struct MarketDefinition {
     pub event_type_id: EventTypeID,
 }
 
+impl Pickle for MarketDefinition {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.bet_delay.encode(e, py);
+        self.betting_type.encode(e, py);
+        self.bsp_market.encode(e, py);
+        self.bsp_reconciled.encode(e, py);
+        self.complete.encode(e, py);
+        self.cross_matching.encode(e, py);
+        self.discount_allowed.encode(e, py);
+        self.in_play.encode(e, py);
+        self.market_base_rate.encode(e, py);
+        self.market_time.encode(e, py);
+        self.market_type.encode(e, py);
+        self.number_of_active_runners.encode(e, py);
+        self.number_of_winners.encode(e, py);
+        self.open_date.encode(e, py);
+        self.persistence_enabled.encode(e, py);
+        self.regulators.encode(e, py);
+        self.runners.encode(e, py);
+        self.runners_voidable.encode(e, py);
+        self.settled_time.encode(e, py);
+        self.status.encode(e, py);
+        self.suspend_time.encode(e, py);
+        self.timezone.encode(e, py);
+        self.turn_in_play_enabled.encode(e, py);
+        self.venue.encode(e, py);
+        self.version.encode(e, py);
+        self.country_code.encode(e, py);
+        self.name.encode(e, py);
+        self.event_name.encode(e, py);
+        self.race_type.encode(e, py);
+        self.each_way_divisor.encode(e, py);
+        self.line_max_unit.encode(e, py);
+        self.line_min_unit.encode(e, py);
+        self.line_interval.encode(e, py);
+        self.price_ladder_definition.encode(e, py);
+        self.key_line_definitions.encode(e, py);
+        self.event_id.encode(e, py);
+        self.event_type_id.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            bet_delay: Pickle::decode(d, py)?,
+            betting_type: Pickle::decode(d, py)?,
+            bsp_market: Pickle::decode(d, py)?,
+            bsp_reconciled: Pickle::decode(d, py)?,
+            complete: Pickle::decode(d, py)?,
+            cross_matching: Pickle::decode(d, py)?,
+            discount_allowed: Pickle::decode(d, py)?,
+            in_play: Pickle::decode(d, py)?,
+            market_base_rate: Pickle::decode(d, py)?,
+            market_time: Pickle::decode(d, py)?,
+            market_type: Pickle::decode(d, py)?,
+            number_of_active_runners: Pickle::decode(d, py)?,
+            number_of_winners: Pickle::decode(d, py)?,
+            open_date: Pickle::decode(d, py)?,
+            persistence_enabled: Pickle::decode(d, py)?,
+            regulators: Pickle::decode(d, py)?,
+            runners: Pickle::decode(d, py)?,
+            runners_voidable: Pickle::decode(d, py)?,
+            settled_time: Pickle::decode(d, py)?,
+            status: Pickle::decode(d, py)?,
+            suspend_time: Pickle::decode(d, py)?,
+            timezone: Pickle::decode(d, py)?,
+            turn_in_play_enabled: Pickle::decode(d, py)?,
+            venue: Pickle::decode(d, py)?,
+            version: Pickle::decode(d, py)?,
+            country_code: Pickle::decode(d, py)?,
+            name: Pickle::decode(d, py)?,
+            event_name: Pickle::decode(d, py)?,
+            race_type: Pickle::decode(d, py)?,
+            each_way_divisor: Pickle::decode(d, py)?,
+            line_max_unit: Pickle::decode(d, py)?,
+            line_min_unit: Pickle::decode(d, py)?,
+            line_interval: Pickle::decode(d, py)?,
+            price_ladder_definition: Pickle::decode(d, py)?,
+            key_line_definitions: Pickle::decode(d, py)?,
+            event_id: Pickle::decode(d, py)?,
+            event_type_id: Pickle::decode(d, py)?,
+        })
+    }
+}
+
 #[pymethods]
 impl MarketDefinition {
     #[getter(event_id)]
@@ -115,6 +200,17 @@ pub struct PriceLadderDescription {
     r#type: PriceLadderDefinition,
 }
 
+impl Pickle for PriceLadderDescription {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.r#type.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            r#type: Pickle::decode(d, py)?,
+        })
+    }
+}
+
 impl PriceLadderDescription {
     fn new(p: PriceLadderDefinition) -> Self {
         Self { r#type: p }
@@ -128,6 +224,17 @@ pub struct MarketDefinitionKeyLine {
     key_line: Vec<MarketDefinitionKeyLineSelection>,
 }
 
+impl Pickle for MarketDefinitionKeyLine {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.key_line.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            key_line: Pickle::decode(d, py)?,
+        })
+    }
+}
+
 impl MarketDefinitionKeyLine {
     fn new(key_line: Vec<MarketDefinitionKeyLineSelection>) -> Self {
         Self { key_line }
@@ -143,6 +250,19 @@ pub struct MarketDefinitionKeyLineSelection {
     handicap: f32,
 }
 
+impl Pickle for MarketDefinitionKeyLineSelection {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.selection_id.encode(e, py);
+        self.handicap.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            selection_id: Pickle::decode(d, py)?,
+            handicap: Pickle::decode(d, py)?,
+        })
+    }
+}
+
 impl MarketDefinitionKeyLineSelection {
     fn new(selection_id: u32, handicap: f32) -> Self {
         Self {
@@ -194,6 +314,63 @@ struct MarketDefinitionUpdate<'a> {
 }
 
 impl MarketDefinition {
+    pub fn image(&self, py: Python) -> DefinitionImage {
+        DefinitionImage {
+            bet_delay: self.bet_delay,
+            betting_type: self.betting_type,
+            bsp_market: self.bsp_market,
+            bsp_reconciled: self.bsp_reconciled,
+            complete: self.complete,
+            country_code: self.country_code.as_ref().map(|s| s.as_str()),
+            cross_matching: self.cross_matching,
+            discount_allowed: self.discount_allowed,
+            each_way_divisor: self.each_way_divisor,
+            event_id: self.event_id.to_string(),
+            event_name: self.event_name.as_deref().map(|s| s.as_ref()),
+            event_type_id: self.event_type_id.to_string(),
+            in_play: self.in_play,
+            market_base_rate: self.market_base_rate,
+            market_time: self.market_time.as_ref(),
+            market_type: self.market_type.as_ref(),
+            name: self.name.as_deref().map(|s| s.as_ref()),
+            number_of_active_runners: self.number_of_active_runners,
+            number_of_winners: self.number_of_winners,
+            open_date: self.open_date.as_ref(),
+            persistence_enabled: self.persistence_enabled,
+            race_type: self.race_type.as_deref().map(|s| s.as_ref()),
+            regulators: self.regulators.as_slice(),
+            runners: self.runners.iter().map(|r| r.borrow(py).image()).collect(),
+            runners_voidable: self.runners_voidable,
+            settled_time: self.settled_time.as_deref().map(|s| s.as_ref()),
+            status: self.status,
+            suspend_time: self.suspend_time.as_deref().map(|s| s.as_ref()),
+            timezone: self.timezone.as_ref(),
+            turn_in_play_enabled: self.turn_in_play_enabled,
+            venue: self.venue.as_deref().map(|s| s.as_ref()),
+            version: self.version,
+            line_max_unit: self.line_max_unit,
+            line_min_unit: self.line_min_unit,
+            line_interval: self.line_interval,
+            price_ladder_definition: self
+                .price_ladder_definition
+                .as_ref()
+                .map(|p| PriceLadderImage {
+                    r#type: p.borrow(py).r#type,
+                }),
+            key_line_definition: self.key_line_definitions.as_ref().map(|kl| KeyLineImage {
+                kl: kl
+                    .borrow(py)
+                    .key_line
+                    .iter()
+                    .map(|k| KeyLineSelectionImage {
+                        id: k.selection_id,
+                        hc: k.handicap,
+                    })
+                    .collect(),
+            }),
+        }
+    }
+
     fn new(change: MarketDefinitionUpdate, py: Python) -> Result<Self, DataError> {
         Ok(Self {
             bet_delay: change.bet_delay.ok_or(DataError {
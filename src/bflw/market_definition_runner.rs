@@ -1,9 +1,10 @@
 use super::config::Config;
 use super::{float_str::FloatStr, runner_book::RunnerBook};
 use crate::{
-    datetime::DateTimeString, enums::SelectionStatus, ids::SelectionID,
-    immutable::container::SyncObj, py_rep::PyRep,
+    datetime::DateTimeString, enums::SelectionStatus, ids::SelectionID, image::RunnerDefinitionImage,
+    immutable::container::SyncObj, price_size::F64OrStr, py_rep::PyRep,
 };
+use crate::pickle::{Decoder, Encoder, Pickle};
 use core::fmt;
 use pyo3::{prelude::*, types::PyList};
 use serde::{
@@ -22,21 +23,44 @@ def __repr__(self):
 
 #[pyclass]
 pub struct MarketDefinitionRunner {
-    selection_id: SelectionID,
+    pub selection_id: SelectionID,
     #[pyo3(get)]
     adjustment_factor: Option<f64>,
     #[pyo3(get)]
     removal_date: Option<SyncObj<DateTimeString>>,
     #[pyo3(get)]
-    sort_priority: u16,
+    pub sort_priority: u16,
     #[pyo3(get)]
     status: SelectionStatus,
     #[pyo3(get)]
-    name: Option<SyncObj<Arc<str>>>,
+    pub name: Option<SyncObj<Arc<str>>>,
     #[pyo3(get)]
     bsp: Option<FloatStr>,
 }
 
+impl Pickle for MarketDefinitionRunner {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.selection_id.encode(e, py);
+        self.adjustment_factor.encode(e, py);
+        self.removal_date.encode(e, py);
+        self.sort_priority.encode(e, py);
+        self.status.encode(e, py);
+        self.name.encode(e, py);
+        self.bsp.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            selection_id: Pickle::decode(d, py)?,
+            adjustment_factor: Pickle::decode(d, py)?,
+            removal_date: Pickle::decode(d, py)?,
+            sort_priority: Pickle::decode(d, py)?,
+            status: Pickle::decode(d, py)?,
+            name: Pickle::decode(d, py)?,
+            bsp: Pickle::decode(d, py)?,
+        })
+    }
+}
+
 #[pymethods]
 impl MarketDefinitionRunner {
     #[getter(selection_id)]
@@ -64,6 +88,19 @@ pub struct MarketDefRunnerUpdate<'a> {
 }
 
 impl MarketDefinitionRunner {
+    pub fn image(&self) -> RunnerDefinitionImage {
+        RunnerDefinitionImage {
+            adjustment_factor: self.adjustment_factor,
+            status: self.status,
+            sort_priority: self.sort_priority,
+            removal_date: self.removal_date.as_ref().map(|s| s.to_string()),
+            id: self.selection_id.id(),
+            name: self.name.as_ref().map(|s| s.to_string()),
+            hc: self.selection_id.handicap(),
+            bsp: self.bsp.map(|f| F64OrStr::from(f.0)),
+        }
+    }
+
     fn new(change: &MarketDefRunnerUpdate) -> Self {
         let sid = SelectionID::from((change.id, change.hc));
 
@@ -1,5 +1,8 @@
+pub mod cache;
 pub mod file;
+pub mod generator;
 pub mod market_book;
+pub mod market_catalogue;
 pub mod market_definition;
 pub mod market_definition_runner;
 pub mod runner_book;
@@ -3,20 +3,25 @@ use pyo3::prelude::*;
 use pyo3::types::PyList;
 use serde::de::{DeserializeSeed, Error, IgnoredAny, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
-use serde_json::value::RawValue;
+use std::borrow::Cow;
 use std::sync::Arc;
 
 use super::config::Config;
 use super::market_definition_runner::MarketDefRunnerUpdate;
 use super::runner_book_sp::RunnerBookSP;
+use crate::analytics::volume_at_price;
 use crate::bflw::float_str::FloatStr;
 use crate::bflw::RoundToCents;
 use crate::datetime::DateTimeString;
 use crate::enums::SelectionStatus;
-use crate::ids::SelectionID;
+use crate::ids::{RunnerIndex, SelectionID};
+use crate::image::RunnerChangeImage;
 use crate::immutable::container::SyncObj;
-use crate::immutable::price_size::{ImmutablePriceSizeBackLadder, ImmutablePriceSizeLayLadder};
+use crate::immutable::price_size::{
+    apply_back_ladder, apply_back_ladder_at, apply_lay_ladder, apply_lay_ladder_at,
+};
 use crate::immutable::runner_book_ex::RunnerBookEX;
+use crate::pickle::{Decoder, Encoder, Pickle};
 use crate::price_size::{F64OrStr, PriceSize};
 use crate::py_rep::PyRep;
 
@@ -43,6 +48,34 @@ pub struct RunnerBook {
     pub orders: Vec<()>,
 }
 
+// matches and orders are always empty on historic data
+impl Pickle for RunnerBook {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.selection_id.encode(e, py);
+        self.status.encode(e, py);
+        self.total_matched.encode(e, py);
+        self.adjustment_factor.encode(e, py);
+        self.last_price_traded.encode(e, py);
+        self.removal_date.encode(e, py);
+        self.ex.encode(e, py);
+        self.sp.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            selection_id: Pickle::decode(d, py)?,
+            status: Pickle::decode(d, py)?,
+            total_matched: Pickle::decode(d, py)?,
+            adjustment_factor: Pickle::decode(d, py)?,
+            last_price_traded: Pickle::decode(d, py)?,
+            removal_date: Pickle::decode(d, py)?,
+            ex: Pickle::decode(d, py)?,
+            sp: Pickle::decode(d, py)?,
+            matches: Vec::new(),
+            orders: Vec::new(),
+        })
+    }
+}
+
 #[pymethods]
 impl RunnerBook {
     #[getter(selection_id)]
@@ -70,6 +103,25 @@ impl PyRep for Vec<Py<RunnerBook>> {
 }
 
 impl RunnerBook {
+    pub fn image(&self, py: Python) -> RunnerChangeImage {
+        let ex = self.ex.borrow(py);
+        let sp = self.sp.borrow(py);
+
+        RunnerChangeImage {
+            id: self.selection_id.id(),
+            hc: self.selection_id.handicap(),
+            atb: ex.available_to_back.to_vec(),
+            atl: ex.available_to_lay.to_vec(),
+            trd: ex.traded_volume.to_vec(),
+            spb: sp.lay_liability_taken.to_vec(),
+            spl: sp.back_stake_taken.to_vec(),
+            spn: sp.near_price.map(|f| F64OrStr::from(f.0)),
+            spf: sp.far_price.map(|f| F64OrStr::from(f.0)),
+            ltp: self.last_price_traded.map(|f| F64OrStr::from(f.0)),
+            tv: self.total_matched,
+        }
+    }
+
     pub fn new(id: SelectionID, py: Python) -> Self {
         Self {
             selection_id: id,
@@ -158,25 +210,29 @@ impl RunnerBook {
     }
 }
 
-pub struct RunnerChangeSeq<'a, 'py> {
+// index finds runners in runners, it's only copied if a runner has to be added to it
+pub struct RunnerChangeSeq<'a, 'i, 'py> {
     pub runners: Option<&'a Vec<Py<RunnerBook>>>,
+    pub index: &'a mut Cow<'i, RunnerIndex>,
     pub py: Python<'py>,
     pub config: Config,
 }
-impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeSeq<'a, 'py> {
-    type Value = Vec<Py<RunnerBook>>;
+// the runners after the change, and whether any of them traded in it
+impl<'de, 'a, 'i, 'py> DeserializeSeed<'de> for RunnerChangeSeq<'a, 'i, 'py> {
+    type Value = (Vec<Py<RunnerBook>>, bool);
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct RunnerSeqVisitor<'a, 'py> {
+        struct RunnerSeqVisitor<'a, 'i, 'py> {
             runners: Option<&'a Vec<Py<RunnerBook>>>,
+            index: &'a mut Cow<'i, RunnerIndex>,
             py: Python<'py>,
             config: Config,
         }
-        impl<'de, 'a, 'py> Visitor<'de> for RunnerSeqVisitor<'a, 'py> {
-            type Value = Vec<Py<RunnerBook>>;
+        impl<'de, 'a, 'i, 'py> Visitor<'de> for RunnerSeqVisitor<'a, 'i, 'py> {
+            type Value = (Vec<Py<RunnerBook>>, bool);
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("")
@@ -190,73 +246,175 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeSeq<'a, 'py> {
                     .runners
                     .map(|v| v.iter().map(|r| r.clone_ref(self.py)).collect::<Vec<_>>())
                     .unwrap_or_else(|| Vec::with_capacity(10));
+                let mut traded = false;
 
-                #[derive(Deserialize)]
-                struct RunnerWithID {
-                    id: u32,
-                    hc: Option<f32>,
-                }
-
-                while let Some(raw) = seq.next_element::<&RawValue>()? {
-                    let mut deser = serde_json::Deserializer::from_str(raw.get());
-                    let parts: RunnerWithID =
-                        serde_json::from_str(raw.get()).map_err(Error::custom)?;
-                    let sid = SelectionID::from((parts.id, parts.hc));
-
-                    let index = v
-                        .iter()
-                        .map(|r| r.borrow(self.py))
-                        .position(|r| r.selection_id == sid);
-
-                    match index {
+                while let Some(change) = seq.next_element_seed(RunnerBookChangeDeser {
+                    config: self.config,
+                })? {
+                    match self.index.get(change.id) {
                         Some(index) => {
                             let runner = {
                                 let runner = unsafe { v.get_unchecked(index).borrow(self.py) };
-                                RunnerBookChangeDeser {
-                                    runner: &runner,
-                                    py: self.py,
-                                    config: self.config,
-                                }
-                                .deserialize(&mut deser)
-                                .map_err(Error::custom)?
+                                traded |= change.traded(&runner, self.py);
+                                change.apply(&runner, self.config, self.py)
                             };
 
                             v[index] = Py::new(self.py, runner).unwrap();
                         }
                         None => {
-                            let runner = RunnerBook::new(sid, self.py);
-                            let runner = RunnerBookChangeDeser {
-                                runner: &runner,
-                                py: self.py,
-                                config: self.config,
-                            }
-                            .deserialize(&mut deser)
-                            .map_err(Error::custom)?;
+                            self.index.to_mut().push(change.id);
+                            let runner = RunnerBook::new(change.id, self.py);
+                            traded |= change.traded(&runner, self.py);
+                            let runner = change.apply(&runner, self.config, self.py);
 
                             v.push(Py::new(self.py, runner).unwrap());
                         }
                     }
                 }
 
-                Ok(v)
+                Ok((v, traded))
             }
         }
 
         deserializer.deserialize_seq(RunnerSeqVisitor {
             runners: self.runners,
+            index: self.index,
             py: self.py,
             config: self.config,
         })
     }
 }
 
-struct RunnerBookChangeDeser<'a, 'py> {
-    runner: &'a RunnerBook,
-    py: Python<'py>,
+// a runner change as it was sent, the ladders are the levels to apply rather than the full ladder
+#[derive(Default)]
+struct RunnerBookChange {
+    id: SelectionID,
+    atb: Option<Vec<PriceSize>>,
+    atl: Option<Vec<PriceSize>>,
+    trd: Option<Vec<PriceSize>>,
+    spb: Option<Vec<PriceSize>>,
+    spl: Option<Vec<PriceSize>>,
+    spn: Option<FloatStr>,
+    spf: Option<FloatStr>,
+    tv: Option<f64>,
+    ltp: Option<FloatStr>,
+}
+
+impl RunnerBookChange {
+    // traded volume only ever grows, so a level above the runner's size at that price is a match
+    fn traded(&self, runner: &RunnerBook, py: Python) -> bool {
+        self.trd.as_ref().map_or(false, |trd| {
+            let ex = runner.ex.borrow(py);
+            trd.iter()
+                .any(|ps| ps.size > volume_at_price(&ex.traded_volume, ps.price))
+        })
+    }
+
+    fn apply(self, runner: &RunnerBook, config: Config, py: Python) -> RunnerBook {
+        let mut tv = self.tv;
+
+        let ex = if self.atb.is_some() || self.atl.is_some() || self.trd.is_some() {
+            let ex = runner.ex.borrow(py);
+
+            let trd = self
+                .trd
+                .map(|l| apply_back_ladder(Some(ex.traded_volume.as_slice()), &l));
+            if config.cumulative_runner_tv && let Some(trd) = &trd {
+                tv = Some(trd.iter().map(|ps| ps.size).sum::<f64>().round_cent());
+            }
+
+            // a side the levels left as it was keeps its ladder, and its truncated view while
+            // nothing inside the depth changed
+            let (atb, atb_at) = match &self.atb {
+                Some(l) => apply_lay_ladder_at(Some(ex.available_to_back.as_slice()), l),
+                None => (Vec::new(), None),
+            };
+            let (atl, atl_at) = match &self.atl {
+                Some(l) => apply_back_ladder_at(Some(ex.available_to_lay.as_slice()), l),
+                None => (Vec::new(), None),
+            };
+            let back_view = ex.carry_view(&ex.back_view, atb_at);
+            let lay_view = ex.carry_view(&ex.lay_view, atl_at);
+
+            Some(
+                Py::new(
+                    py,
+                    RunnerBookEX {
+                        available_to_back: match atb_at {
+                            Some(_) => SyncObj::new(Arc::new(atb)),
+                            None => ex.available_to_back.clone(),
+                        },
+                        available_to_lay: match atl_at {
+                            Some(_) => SyncObj::new(Arc::new(atl)),
+                            None => ex.available_to_lay.clone(),
+                        },
+                        traded_volume: trd.map_or_else(
+                            || ex.traded_volume.clone(),
+                            |ps| SyncObj::new(Arc::new(ps)),
+                        ),
+                        ladder_depth: ex.ladder_depth,
+                        back_view,
+                        lay_view,
+                    },
+                )
+                .unwrap(),
+            )
+        } else {
+            None
+        };
+
+        let sp = if self.spl.is_some()
+            || self.spb.is_some()
+            || self.spn.is_some()
+            || self.spf.is_some()
+        {
+            let sp = runner.sp.borrow(py);
+            Some(
+                Py::new(
+                    py,
+                    RunnerBookSP {
+                        actual_sp: sp.actual_sp,
+                        far_price: self.spf.or(sp.far_price),
+                        near_price: self.spn.or(sp.near_price),
+                        back_stake_taken: self.spb.map_or_else(
+                            || sp.back_stake_taken.clone(),
+                            |l| {
+                                let l = apply_back_ladder(Some(sp.back_stake_taken.as_slice()), &l);
+                                SyncObj::new(Arc::new(l))
+                            },
+                        ),
+                        lay_liability_taken: self.spl.map_or_else(
+                            || sp.lay_liability_taken.clone(),
+                            |l| {
+                                let l = apply_lay_ladder(Some(sp.lay_liability_taken.as_slice()), &l);
+                                SyncObj::new(Arc::new(l))
+                            },
+                        ),
+                    },
+                )
+                .unwrap(),
+            )
+        } else {
+            None
+        };
+
+        let update = RunnerChangeUpdate {
+            last_price_traded: self.ltp,
+            total_matched: tv,
+            ex,
+            sp,
+        };
+
+        runner.update_from_change(update, py)
+    }
+}
+
+// Reads a runner change in a single pass, the levels are buffered until the id and hc are known
+struct RunnerBookChangeDeser {
     config: Config,
 }
-impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerBookChangeDeser<'a, 'py> {
-    type Value = RunnerBook;
+impl<'de> DeserializeSeed<'de> for RunnerBookChangeDeser {
+    type Value = RunnerBookChange;
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
@@ -278,13 +436,11 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerBookChangeDeser<'a, 'py> {
             Hc,
         }
 
-        struct RunnerChangeVisitor<'a, 'py> {
-            runner: &'a RunnerBook,
-            py: Python<'py>,
+        struct RunnerChangeVisitor {
             config: Config,
         }
-        impl<'de, 'a, 'py> Visitor<'de> for RunnerChangeVisitor<'a, 'py> {
-            type Value = RunnerBook;
+        impl<'de> Visitor<'de> for RunnerChangeVisitor {
+            type Value = RunnerBookChange;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("")
@@ -294,70 +450,41 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerBookChangeDeser<'a, 'py> {
             where
                 V: MapAccess<'de>,
             {
-                let mut atb: Option<Vec<PriceSize>> = None;
-                let mut atl: Option<Vec<PriceSize>> = None;
-                let mut trd: Option<Vec<PriceSize>> = None;
-
-                let mut spb: Option<Vec<PriceSize>> = None;
-                let mut spl: Option<Vec<PriceSize>> = None;
-                let mut spn: Option<FloatStr> = None;
-                let mut spf: Option<FloatStr> = None;
-
-                let mut tv: Option<f64> = None;
-                let mut ltp: Option<FloatStr> = None;
+                let mut id: Option<u32> = None;
+                let mut hc: Option<f32> = None;
+                let mut change = RunnerBookChange::default();
 
                 while let Some(key) = map.next_key()? {
                     match key {
+                        Field::Id => {
+                            id = Some(map.next_value::<u32>()?);
+                        }
+                        Field::Hc => {
+                            hc = map.next_value::<Option<f32>>()?;
+                        }
                         Field::Atb => {
-                            let ex = self.runner.ex.borrow(self.py);
-                            atb = Some(map.next_value_seed(ImmutablePriceSizeLayLadder(Some(
-                                &ex.available_to_back,
-                            )))?);
+                            change.atb = Some(map.next_value::<Vec<PriceSize>>()?);
                         }
                         Field::Atl => {
-                            let ex = self.runner.ex.borrow(self.py);
-                            atl = Some(map.next_value_seed(ImmutablePriceSizeBackLadder(Some(
-                                &ex.available_to_lay,
-                            )))?);
+                            change.atl = Some(map.next_value::<Vec<PriceSize>>()?);
                         }
                         Field::Trd => {
-                            let ex = self.runner.ex.borrow(self.py);
-                            let l = map.next_value_seed(ImmutablePriceSizeBackLadder(Some(
-                                &ex.traded_volume,
-                            )))?;
-
-                            if self.config.cumulative_runner_tv {
-                                tv = Some(l.iter().map(|ps| ps.size).sum::<f64>().round_cent());
-                            }
-
-                            trd = Some(l);
+                            change.trd = Some(map.next_value::<Vec<PriceSize>>()?);
                         }
                         Field::Spb => {
-                            let sp = self.runner.sp.borrow(self.py);
-                            spl = Some(map.next_value_seed(ImmutablePriceSizeLayLadder(Some(
-                                &sp.lay_liability_taken,
-                            )))?);
+                            change.spl = Some(map.next_value::<Vec<PriceSize>>()?);
                         }
                         Field::Spl => {
-                            let sp = self.runner.sp.borrow(self.py);
-                            spb = Some(map.next_value_seed(ImmutablePriceSizeBackLadder(Some(
-                                &sp.back_stake_taken,
-                            )))?);
+                            change.spb = Some(map.next_value::<Vec<PriceSize>>()?);
                         }
                         Field::Spn => {
-                            spn = Some(map.next_value::<FloatStr>()?);
+                            change.spn = Some(map.next_value::<FloatStr>()?);
                         }
                         Field::Spf => {
-                            spf = Some(map.next_value::<FloatStr>()?);
+                            change.spf = Some(map.next_value::<FloatStr>()?);
                         }
                         Field::Ltp => {
-                            ltp = Some(map.next_value::<FloatStr>()?);
-                        }
-                        Field::Hc => {
-                            map.next_value::<IgnoredAny>()?;
-                        }
-                        Field::Id => {
-                            map.next_value::<IgnoredAny>()?;
+                            change.ltp = Some(map.next_value::<FloatStr>()?);
                         }
                         // The betfair historic data files differ from the stream here, they send tv deltas
                         // that need to be accumulated, whereas the stream sends the value itself.
@@ -367,71 +494,16 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerBookChangeDeser<'a, 'py> {
                             } else {
                                 let v: f64 = map.next_value::<F64OrStr>()?.into();
                                 let v = v.round_cent();
-                                tv = Some(v);
+                                change.tv = Some(v);
                             }
                         }
                     };
                 }
 
-                let ex = if atb.is_some() || atl.is_some() || trd.is_some() {
-                    let ex = self.runner.ex.borrow(self.py);
-                    Some(
-                        Py::new(
-                            self.py,
-                            RunnerBookEX {
-                                available_to_back: atb.map_or_else(
-                                    || ex.available_to_back.clone(),
-                                    |ps| SyncObj::new(Arc::new(ps)),
-                                ),
-                                available_to_lay: atl.map_or_else(
-                                    || ex.available_to_lay.clone(),
-                                    |ps| SyncObj::new(Arc::new(ps)),
-                                ),
-                                traded_volume: trd.map_or_else(
-                                    || ex.traded_volume.clone(),
-                                    |ps| SyncObj::new(Arc::new(ps)),
-                                ),
-                            },
-                        )
-                        .unwrap(),
-                    )
-                } else {
-                    None
-                };
-
-                let sp = if spl.is_some() || spb.is_some() || spn.is_some() || spf.is_some() {
-                    let sp = self.runner.sp.borrow(self.py);
-                    Some(
-                        Py::new(
-                            self.py,
-                            RunnerBookSP {
-                                actual_sp: sp.actual_sp,
-                                far_price: spf.or(sp.far_price),
-                                near_price: spn.or(sp.near_price),
-                                back_stake_taken: spb.map_or_else(
-                                    || sp.back_stake_taken.clone(),
-                                    |ps| SyncObj::new(Arc::new(ps)),
-                                ),
-                                lay_liability_taken: spl.map_or_else(
-                                    || sp.lay_liability_taken.clone(),
-                                    |ps| SyncObj::new(Arc::new(ps)),
-                                ),
-                            },
-                        )
-                        .unwrap(),
-                    )
-                } else {
-                    None
-                };
-
-                let update = RunnerChangeUpdate {
-                    last_price_traded: ltp,
-                    total_matched: tv,
-                    ex,
-                    sp,
-                };
-
-                Ok(self.runner.update_from_change(update, self.py))
+                let id = id.ok_or_else(|| Error::missing_field("id"))?;
+                change.id = SelectionID::from((id, hc));
+
+                Ok(change)
             }
         }
 
@@ -442,8 +514,6 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerBookChangeDeser<'a, 'py> {
             "RunnerChange",
             FIELDS,
             RunnerChangeVisitor {
-                runner: self.runner,
-                py: self.py,
                 config: self.config,
             },
         )
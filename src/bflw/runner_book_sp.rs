@@ -3,6 +3,7 @@ use std::sync::Arc;
 use pyo3::prelude::*;
 
 use crate::immutable::container::SyncObj;
+use crate::pickle::{Decoder, Encoder, Pickle};
 use crate::price_size::PriceSize;
 
 use super::float_str::FloatStr;
@@ -20,6 +21,25 @@ pub struct RunnerBookSP {
     pub lay_liability_taken: SyncObj<Arc<Vec<PriceSize>>>,
 }
 
+impl Pickle for RunnerBookSP {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.actual_sp.encode(e, py);
+        self.far_price.encode(e, py);
+        self.near_price.encode(e, py);
+        self.back_stake_taken.encode(e, py);
+        self.lay_liability_taken.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            actual_sp: Pickle::decode(d, py)?,
+            far_price: Pickle::decode(d, py)?,
+            near_price: Pickle::decode(d, py)?,
+            back_stake_taken: Pickle::decode(d, py)?,
+            lay_liability_taken: Pickle::decode(d, py)?,
+        })
+    }
+}
+
 #[pymethods]
 impl RunnerBookSP {
     #[getter(back_stake_taken)]
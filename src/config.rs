@@ -3,6 +3,9 @@ use crate::market_source::ConfigProducer;
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
     pub cumulative_runner_tv: bool,
+    pub changes: bool,
+    pub ladder_depth: Option<usize>,
+    pub heartbeats: bool,
 }
 
 impl ConfigProducer for Config {
@@ -80,6 +80,12 @@ impl Deref for DateTimeString {
     }
 }
 
+impl PartialEq for DateTimeString {
+    fn eq(&self, other: &Self) -> bool {
+        self.str == other.str
+    }
+}
+
 impl PartialEq<str> for DateTimeString {
     fn eq(&self, s: &str) -> bool {
         s == self.str
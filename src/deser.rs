@@ -1,5 +1,6 @@
 use self_cell::self_cell;
-use serde_json::{de::StrRead, Deserializer};
+use serde::de::{Deserialize, DeserializeSeed};
+use serde_json::{de::StrRead, value::RawValue, Deserializer};
 use simdutf8::basic::from_utf8;
 use std::io::{Error, ErrorKind};
 
@@ -15,9 +16,38 @@ impl DeserializerWithData {
     pub fn build(bs: Vec<u8>) -> Result<Self, Error> {
         DeserializerWithData::try_new(bs, |bs| {
             let s = from_utf8(bs).map_err(|_| Error::from(ErrorKind::InvalidData))?;
-            Ok(Deser(serde_json::Deserializer::from_str(s)))
+            Ok(Deser::new(s))
         })
     }
 }
 
-pub struct Deser<'a>(pub Deserializer<StrRead<'a>>);
+pub struct Deser<'a>(Deserializer<StrRead<'a>>);
+
+pub type DeserError = serde_json::Error;
+
+impl<'a> Deser<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self(Deserializer::from_str(s))
+    }
+
+    pub fn deserialize_next<S>(&mut self, seed: S) -> Result<S::Value, DeserError>
+    where
+        S: DeserializeSeed<'a>,
+    {
+        seed.deserialize(&mut self.0)
+    }
+}
+
+// an element that has to be read again once a few of its fields are known
+pub type Buffered<'de> = &'de RawValue;
+
+pub fn peek<'de, T: Deserialize<'de>>(raw: &Buffered<'de>) -> Result<T, serde_json::Error> {
+    serde_json::from_str(raw.get())
+}
+
+pub fn replay<'de, S: DeserializeSeed<'de>>(
+    raw: Buffered<'de>,
+    seed: S,
+) -> Result<S::Value, serde_json::Error> {
+    seed.deserialize(&mut Deserializer::from_str(raw.get()))
+}
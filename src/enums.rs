@@ -86,6 +86,34 @@ impl IntoPy<PyObject> for SelectionStatus {
     }
 }
 
+// the `ct` of a stream message, absent for a regular delta
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize, AsRefStr, IntoStaticStr)]
+pub enum ChangeType {
+    #[strum(serialize = "SUB_IMAGE")]
+    #[serde(rename = "SUB_IMAGE")]
+    SubImage,
+    #[strum(serialize = "RESUB_DELTA")]
+    #[serde(rename = "RESUB_DELTA")]
+    ResubDelta,
+    #[strum(serialize = "HEARTBEAT")]
+    #[serde(rename = "HEARTBEAT")]
+    Heartbeat,
+}
+
+static CHANGE_TYPE_INTERNED: SyncOnceCell<[PyObject; std::mem::variant_count::<ChangeType>()]> = SyncOnceCell::new();
+impl IntoPy<PyObject> for ChangeType {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        CHANGE_TYPE_INTERNED.get_or_init(|| {
+            [
+                PyUnicode::new(py, ChangeType::SubImage.as_ref()).into_py(py),
+                PyUnicode::new(py, ChangeType::ResubDelta.as_ref()).into_py(py),
+                PyUnicode::new(py, ChangeType::Heartbeat.as_ref()).into_py(py),
+            ]
+        })
+        [self as usize].clone_ref(py)
+    }
+}
+
 #[derive(
     Debug, Default, PartialEq, Copy, Clone, Serialize, Deserialize, AsRefStr, IntoStaticStr,
 )]
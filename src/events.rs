@@ -0,0 +1,255 @@
+use pyo3::prelude::*;
+
+use crate::datetime::{DateTime, DateTimeString};
+use crate::enums::{MarketStatus, SelectionStatus};
+use crate::ids::{MarketID, SelectionID};
+use crate::immutable::container::SyncObj;
+use crate::immutable::definition::Transition;
+use crate::immutable::market::Market;
+use crate::immutable::runner::Runner;
+use crate::mutable::market::MarketMut;
+
+pub const RUNNER_REMOVED: &str = "runner_removed";
+pub const RUNNER_REINSTATED: &str = "runner_reinstated";
+pub const IN_PLAY: &str = "in_play";
+pub const SUSPENDED: &str = "suspended";
+pub const RESUMED: &str = "resumed";
+pub const BSP_RECONCILED: &str = "bsp_reconciled";
+pub const SETTLED: &str = "settled";
+
+// A point in the life of a market or one of its runners. selection_id and handicap are set for
+// runner events, a removal also carries the reduction factor, removal date and the runner as it
+// was before it was removed, from the same reader as the file's markets. winners is only filled
+// in when the market settles.
+#[pyclass(name = "LifecycleEvent")]
+pub struct LifecycleEvent {
+    #[pyo3(get)]
+    pub kind: &'static str,
+    #[pyo3(get)]
+    pub market_id: SyncObj<MarketID>,
+    #[pyo3(get)]
+    pub publish_time: DateTime,
+    #[pyo3(get)]
+    pub selection_id: Option<u32>,
+    #[pyo3(get)]
+    pub handicap: Option<f64>,
+    #[pyo3(get)]
+    pub adjustment_factor: Option<f64>,
+    #[pyo3(get)]
+    pub removal_date: Option<SyncObj<DateTimeString>>,
+    #[pyo3(get)]
+    pub runner: Option<PyObject>,
+    #[pyo3(get)]
+    pub winners: Vec<u32>,
+}
+
+#[pymethods]
+impl LifecycleEvent {
+    fn __repr__(&self) -> String {
+        match self.selection_id {
+            Some(id) => format!(
+                "<LifecycleEvent {} {} {}>",
+                self.kind,
+                self.market_id.as_str(),
+                id
+            ),
+            None => format!("<LifecycleEvent {} {}>", self.kind, self.market_id.as_str()),
+        }
+    }
+}
+
+impl LifecycleEvent {
+    fn market<M: Lifecycle>(kind: &'static str, m: &M) -> Self {
+        Self {
+            kind,
+            market_id: m.market_id().clone(),
+            publish_time: m.publish_time(),
+            selection_id: None,
+            handicap: None,
+            adjustment_factor: None,
+            removal_date: None,
+            runner: None,
+            winners: Vec::new(),
+        }
+    }
+
+    fn runner<M: Lifecycle>(kind: &'static str, m: &M, sid: SelectionID) -> Self {
+        Self {
+            selection_id: Some(sid.id()),
+            handicap: sid.handicap().map(|hc| hc as f64),
+            ..Self::market(kind, m)
+        }
+    }
+}
+
+// What the events need from a market update, whichever reader built it
+pub trait Lifecycle {
+    fn market_id(&self) -> &SyncObj<MarketID>;
+    fn publish_time(&self) -> DateTime;
+    fn transitions(&self) -> &[Transition];
+    // the reduction factor and removal date the update's definition gave the runner
+    fn removal(
+        &self,
+        sid: SelectionID,
+        py: Python,
+    ) -> (Option<f64>, Option<SyncObj<DateTimeString>>);
+    // the runner as it was before this update removed it, prev is the market's previous update
+    fn removed_runner(&self, prev: Option<&Self>, sid: SelectionID, py: Python)
+        -> Option<PyObject>;
+    fn winners(&self, py: Python) -> Vec<u32>;
+}
+
+// Turns the status transitions the update's definition made into events. Transitions are only
+// made against a previous definition, so a market's first definition, and the first one a runner
+// has, start its statuses off without events.
+pub fn events<M: Lifecycle>(m: &M, prev: Option<&M>, py: Python) -> Vec<Py<LifecycleEvent>> {
+    m.transitions()
+        .iter()
+        .filter_map(|t| match *t {
+            Transition::Runner(sid, from, to) if is_removed(to) && !is_removed(from) => {
+                let (adjustment_factor, removal_date) = m.removal(sid, py);
+                Some(LifecycleEvent {
+                    adjustment_factor,
+                    removal_date,
+                    runner: m.removed_runner(prev, sid, py),
+                    ..LifecycleEvent::runner(RUNNER_REMOVED, m, sid)
+                })
+            }
+            Transition::Runner(sid, from, SelectionStatus::Active) if is_removed(from) => {
+                Some(LifecycleEvent::runner(RUNNER_REINSTATED, m, sid))
+            }
+            Transition::Runner(..) => None,
+            Transition::InPlay => Some(LifecycleEvent::market(IN_PLAY, m)),
+            Transition::BspReconciled => Some(LifecycleEvent::market(BSP_RECONCILED, m)),
+            Transition::Market(_, MarketStatus::Suspended) => {
+                Some(LifecycleEvent::market(SUSPENDED, m))
+            }
+            Transition::Market(MarketStatus::Suspended, MarketStatus::Open) => {
+                Some(LifecycleEvent::market(RESUMED, m))
+            }
+            Transition::Market(_, MarketStatus::Closed) => Some(LifecycleEvent {
+                winners: m.winners(py),
+                ..LifecycleEvent::market(SETTLED, m)
+            }),
+            Transition::Market(..) => None,
+        })
+        .map(|e| Py::new(py, e).unwrap())
+        .collect()
+}
+
+impl Lifecycle for Market {
+    fn market_id(&self) -> &SyncObj<MarketID> {
+        &self.market_id
+    }
+
+    fn publish_time(&self) -> DateTime {
+        self.publish_time
+    }
+
+    fn transitions(&self) -> &[Transition] {
+        &self.transitions
+    }
+
+    fn removal(
+        &self,
+        sid: SelectionID,
+        py: Python,
+    ) -> (Option<f64>, Option<SyncObj<DateTimeString>>) {
+        match runner(self, sid) {
+            Some(r) => {
+                let r = r.borrow(py);
+                (r.adjustment_factor, r.removal_date.clone())
+            }
+            None => (None, None),
+        }
+    }
+
+    // updates are immutable, so the previous one still holds the runner as it was
+    fn removed_runner(
+        &self,
+        prev: Option<&Self>,
+        sid: SelectionID,
+        py: Python,
+    ) -> Option<PyObject> {
+        prev.and_then(|p| runner(p, sid))
+            .map(|r| r.clone_ref(py).into_py(py))
+    }
+
+    fn winners(&self, py: Python) -> Vec<u32> {
+        winners(
+            self.runners
+                .iter()
+                .map(|r| r.borrow(py))
+                .map(|r| (r.selection_id, r.status)),
+        )
+    }
+}
+
+impl Lifecycle for MarketMut {
+    fn market_id(&self) -> &SyncObj<MarketID> {
+        &self.market_id
+    }
+
+    fn publish_time(&self) -> DateTime {
+        self.publish_time
+    }
+
+    fn transitions(&self) -> &[Transition] {
+        &self.transitions
+    }
+
+    fn removal(
+        &self,
+        sid: SelectionID,
+        py: Python,
+    ) -> (Option<f64>, Option<SyncObj<DateTimeString>>) {
+        self.runners
+            .iter()
+            .map(|r| r.borrow(py))
+            .find(|r| r.selection_id == sid)
+            .map_or((None, None), |r| {
+                (r.adjustment_factor, r.removal_date.map(SyncObj::new))
+            })
+    }
+
+    // the market is updated in place, so the definition kept a copy of the runner before it
+    // removed it
+    fn removed_runner(
+        &self,
+        _prev: Option<&Self>,
+        sid: SelectionID,
+        py: Python,
+    ) -> Option<PyObject> {
+        self.removed
+            .iter()
+            .find(|r| r.borrow(py).selection_id == sid)
+            .map(|r| r.clone_ref(py).into_py(py))
+    }
+
+    fn winners(&self, py: Python) -> Vec<u32> {
+        winners(
+            self.runners
+                .iter()
+                .map(|r| r.borrow(py))
+                .map(|r| (r.selection_id, r.status)),
+        )
+    }
+}
+
+fn runner(m: &Market, sid: SelectionID) -> Option<&Py<Runner>> {
+    m.index.get(sid).and_then(|i| m.runners.get(i))
+}
+
+fn winners(runners: impl Iterator<Item = (SelectionID, SelectionStatus)>) -> Vec<u32> {
+    runners
+        .filter(|(_, status)| *status == SelectionStatus::Winner)
+        .map(|(sid, _)| sid.id())
+        .collect()
+}
+
+pub fn is_removed(status: SelectionStatus) -> bool {
+    matches!(
+        status,
+        SelectionStatus::Removed | SelectionStatus::RemovedVacant
+    )
+}
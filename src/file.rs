@@ -1,7 +1,10 @@
 use pyo3::prelude::*;
 use std::path::{Path, PathBuf};
 
+use crate::config::Config;
+use crate::events::LifecycleEvent;
 use crate::file_iter::FileIter;
+use crate::history::{self, DefinitionChange};
 use crate::immutable::file::ImmutableRep;
 use crate::immutable::market::Market;
 use crate::mutable::file::MutableRep;
@@ -22,16 +25,29 @@ impl File {
     #[new]
     #[args(cumulative_runner_tv = "true")]
     #[args(mutable = "false")]
+    #[args(changes = "false")]
+    #[args(ladder_depth = "None")]
+    #[args(heartbeats = "false")]
     fn __new__(
         file: PathBuf,
         bytes: &[u8],
         cumulative_runner_tv: bool,
         mutable: bool,
+        changes: bool,
+        ladder_depth: Option<usize>,
+        heartbeats: bool,
     ) -> PyResult<Self> {
+        let config = Config {
+            cumulative_runner_tv,
+            changes,
+            ladder_depth,
+            heartbeats,
+        };
+
         Ok(Self {
             inner: match mutable {
-                true => FileType::Mutable(FileIter::new(file, bytes, cumulative_runner_tv)?),
-                false => FileType::Immutable(FileIter::new(file, bytes, cumulative_runner_tv)?),
+                true => FileType::Mutable(FileIter::new(file, bytes, config)?),
+                false => FileType::Immutable(FileIter::new(file, bytes, config)?),
             },
         })
     }
@@ -54,4 +70,24 @@ impl File {
             FileType::Mutable(inner) => inner.file_name(),
         }
     }
+
+    // every version of each market's definition in the file, with the fields that changed
+    fn definition_history(&self, py: Python) -> PyResult<Vec<DefinitionChange>> {
+        let (name, bs) = match &self.inner {
+            FileType::Immutable(inner) => (inner.file_name(), inner.bytes()),
+            FileType::Mutable(inner) => (inner.file_name(), inner.bytes()),
+        };
+
+        history::definition_history(name, bs, py)
+    }
+
+    // runner removals and reinstatements, in play, suspensions, bsp reconciliation and
+    // settlement, in the order they happened. The events come from the file's own reader, so
+    // whatever's left of the file is read to get them all.
+    fn lifecycle_events(&mut self, py: Python) -> Vec<Py<LifecycleEvent>> {
+        match &mut self.inner {
+            FileType::Immutable(inner) => inner.lifecycle_events(py),
+            FileType::Mutable(inner) => inner.lifecycle_events(py),
+        }
+    }
 }
@@ -1,21 +1,23 @@
 use log::warn;
 use pyo3::{exceptions, prelude::*};
 use serde::de::DeserializeSeed;
-use std::collections::VecDeque;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 
 use crate::config::Config;
 use crate::deser::DeserializerWithData;
+use crate::events::{self, Lifecycle, LifecycleEvent};
 use crate::immutable::container::SyncObj;
 use crate::market_source::SourceItem;
 
 pub trait IntoMarketIter {
-    type Market: pyo3::PyClass + MarketID;
+    type Market: pyo3::PyClass + MarketID + Lifecycle;
     type Deser<'a, 'de, 'py>: DeserializeSeed<'de, Value = VecDeque<Py<Self::Market>>>;
 
     fn new<'a, 'de, 'py>(
-        books: &'a [Py<Self::Market>],
+        books: &'a Books<Self::Market>,
         py: Python<'py>,
         config: Config,
     ) -> Self::Deser<'a, 'de, 'py>;
@@ -25,12 +27,107 @@ pub trait MarketID {
     fn id(&self) -> &str;
 }
 
+// The latest version of every market seen in a file, in the order they first appeared and
+// indexed by market id so multi-market files don't need a scan per update
+pub struct Books<T: pyo3::PyClass + MarketID> {
+    markets: Vec<Py<T>>,
+    index: HashMap<String, usize>,
+    // updates skipped for markets that hadn't had a definition yet, files started mid stream
+    // can't build a market until one arrives
+    skipped: RefCell<Vec<(String, usize)>>,
+    reported: Cell<usize>,
+}
+
+impl<T: pyo3::PyClass + MarketID> Books<T> {
+    pub fn new() -> Self {
+        Self {
+            markets: Vec::new(),
+            index: HashMap::new(),
+            skipped: RefCell::new(Vec::new()),
+            reported: Cell::new(0),
+        }
+    }
+
+    pub fn position(&self, id: &str) -> Option<usize> {
+        self.index.get(id).copied()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Py<T>> {
+        self.position(id).map(|i| &self.markets[i])
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Py<T>> {
+        self.markets.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.markets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.markets.is_empty()
+    }
+
+    pub fn skip(&self, id: &str) {
+        let mut skipped = self.skipped.borrow_mut();
+
+        match skipped.iter_mut().find(|(sid, _)| sid == id) {
+            Some((_, n)) => *n += 1,
+            None => skipped.push((id.to_owned(), 1)),
+        }
+    }
+
+    // warns once for each market that's started skipping updates since the last call
+    pub fn report_skipped(&self, file: &Path) {
+        let skipped = self.skipped.borrow();
+
+        for (id, _) in &skipped[self.reported.get()..] {
+            warn!(target: "betfair_data", "file: {} err: (Data Error) market {} updated before its market definition, skipping updates until one arrives", file.to_string_lossy(), id);
+        }
+
+        self.reported.set(skipped.len());
+    }
+
+    // replaces the previous version of the market, or adds it to the end if it's new
+    pub fn insert(&mut self, market: Py<T>, py: Python) {
+        let pos = self.position(market.borrow(py).id());
+
+        match pos {
+            Some(i) => self.markets[i] = market,
+            None => {
+                let id = market.borrow(py).id().to_owned();
+                self.index.insert(id, self.markets.len());
+                self.markets.push(market);
+            }
+        }
+    }
+
+    // drops the markets f returns false for, keeping the order of the rest
+    pub fn retain<F: FnMut(&Py<T>) -> bool>(&mut self, f: F, py: Python) {
+        self.markets.retain(f);
+        self.index = self
+            .markets
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.borrow(py).id().to_owned(), i))
+            .collect();
+    }
+}
+
+impl<T: pyo3::PyClass + MarketID> Default for Books<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct FileIter<T: pyo3::PyClass + MarketID, I: IntoMarketIter<Market = T>> {
     file_name: SyncObj<PathBuf>,
     config: Config,
     deser: Option<DeserializerWithData>,
-    books: Vec<Py<T>>,
+    books: Books<T>,
     iter_stack: VecDeque<Py<T>>,
+    // lifecycle events from the updates read so far
+    events: Vec<Py<LifecycleEvent>>,
     pd: PhantomData<I>,
 }
 
@@ -42,33 +139,34 @@ impl<T: pyo3::PyClass + MarketID, I: IntoMarketIter<Market = T>> From<(SourceIte
 
         let config = Config {
             cumulative_runner_tv: config.cumulative_runner_tv,
+            changes: config.changes,
+            ladder_depth: config.ladder_depth,
+            heartbeats: config.heartbeats,
         };
 
         Self {
             file_name: SyncObj::new(item.file),
             deser: Some(item.deser),
-            books: Vec::new(),
+            books: Books::new(),
             iter_stack: VecDeque::new(),
+            events: Vec::new(),
             config,
             pd: Default::default(),
         }
     }
 }
 
-impl<T: pyo3::PyClass + MarketID, I: IntoMarketIter<Market = T>> FileIter<T, I> {
-    pub fn new(file: PathBuf, bytes: &[u8], cumulative_runner_tv: bool) -> PyResult<Self> {
-        let config = Config {
-            cumulative_runner_tv,
-        };
-
+impl<T: pyo3::PyClass + MarketID + Lifecycle, I: IntoMarketIter<Market = T>> FileIter<T, I> {
+    pub fn new(file: PathBuf, bytes: &[u8], config: Config) -> PyResult<Self> {
         let deser = DeserializerWithData::build(bytes.to_owned())
             .map_err(|err| PyErr::new::<exceptions::PyRuntimeError, _>(err.to_string()))?;
 
         Ok(Self {
             file_name: SyncObj::new(file),
             deser: Some(deser),
-            books: Vec::new(),
+            books: Books::new(),
             iter_stack: VecDeque::new(),
+            events: Vec::new(),
             config,
             pd: Default::default(),
         })
@@ -78,66 +176,74 @@ impl<T: pyo3::PyClass + MarketID, I: IntoMarketIter<Market = T>> FileIter<T, I>
         &*self.file_name
     }
 
+    // the whole file, however far through it the iterator is
+    pub fn bytes(&self) -> &[u8] {
+        self.deser
+            .as_ref()
+            .expect("Iter without deser")
+            .borrow_owner()
+    }
+
+    // every event in the file, reading whatever's left of it
+    pub fn lifecycle_events(&mut self, py: Python) -> Vec<Py<LifecycleEvent>> {
+        while self.next(py).is_some() {}
+
+        self.events.iter().map(|e| e.clone_ref(py)).collect()
+    }
+
+    // the events are made against the market's previous version before it's replaced
+    fn insert(&mut self, m: Py<T>, py: Python) {
+        {
+            let next = m.borrow(py);
+            let prev = self.books.get(next.id()).map(|p| p.borrow(py));
+            self.events
+                .extend(events::events(&*next, prev.as_deref(), py));
+        }
+
+        self.books.insert(m, py);
+    }
+
     pub fn next(&mut self, py: Python) -> Option<PyObject> {
         if let Some(m) = self.iter_stack.pop_front() {
-            let index = {
-                let market = m.borrow(py);
-                self.books
-                    .iter()
-                    .position(|m2| market.id() == (*m2).borrow(py).id())
-            };
-
-            let mc = m.clone_ref(py);
-            match index {
-                Some(i) => self.books[i] = mc,
-                None => self.books.push(mc),
-            }
+            self.insert(m.clone_ref(py), py);
 
             Some(m.into_py(py))
         } else {
-            let next_books = {
-                let mut deser = self.deser.take().expect("Iter without deser");
-
-                let books = self.books.as_slice();
-                let next_books = deser.with_dependent_mut(|_, deser| {
-                    I::new(books, py, self.config).deserialize(&mut deser.0)
-                });
-
-                let next_books = match next_books {
-                    Ok(bs) => Some(bs),
-                    Err(err) => {
-                        if !err.is_eof() {
-                            warn!(target: "betfair_data", "file: {} err: (JSON Parse Error) {}", self.file_name.to_string_lossy(), err);
+            // lines that don't yield a market (heartbeats, status messages) are skipped over
+            loop {
+                let next_books = {
+                    let mut deser = self.deser.take().expect("Iter without deser");
+
+                    let books = &self.books;
+                    let next_books = deser.with_dependent_mut(|_, deser| {
+                        deser.deserialize_next(I::new(books, py, self.config))
+                    });
+
+                    let next_books = match next_books {
+                        Ok(bs) => Some(bs),
+                        Err(err) => {
+                            if !err.is_eof() {
+                                warn!(target: "betfair_data", "file: {} err: (JSON Parse Error) {}", self.file_name.to_string_lossy(), err);
+                            }
+
+                            None
                         }
+                    };
 
-                        None
-                    }
-                };
-
-                self.deser = Some(deser);
-
-                next_books
-            };
+                    self.deser = Some(deser);
+                    self.books.report_skipped(&self.file_name);
 
-            next_books.and_then(|mut next_books| {
-                next_books.pop_front().map(|m| {
-                    let index = {
-                        let market = &(*m.borrow(py));
-                        self.books
-                            .iter()
-                            .position(|m2| market.id() == (*m2).borrow(py).id())
-                    };
+                    next_books
+                };
 
-                    let mc = m.clone_ref(py);
-                    match index {
-                        Some(i) => self.books[i] = mc,
-                        None => self.books.push(mc),
-                    }
+                let mut next_books = next_books?;
+                if let Some(m) = next_books.pop_front() {
+                    self.insert(m.clone_ref(py), py);
 
                     self.iter_stack = next_books;
-                    m.into_py(py)
-                })
-            })
+                    return Some(m.into_py(py));
+                }
+            }
         }
     }
 }
@@ -23,6 +23,7 @@ use crate::errors::IOErr;
 use crate::immutable::file::File as ImmutFile;
 use crate::market_source::{Adapter, SourceItem};
 use crate::mutable::file::File as MutFile;
+use crate::split;
 
 const NUM_BUFFERED: usize = 50;
 
@@ -56,14 +57,32 @@ pub struct Files {
 #[pymethods]
 impl Files {
     #[new]
-    #[args(cumulative_runner_tv = "true", mutable = "false")]
-    fn __new__(paths: &PySequence, cumulative_runner_tv: bool, mutable: bool) -> PyResult<Self> {
+    #[args(
+        cumulative_runner_tv = "true",
+        mutable = "false",
+        changes = "false",
+        ladder_depth = "None",
+        heartbeats = "false",
+        split_markets = "false"
+    )]
+    fn __new__(
+        paths: &PySequence,
+        cumulative_runner_tv: bool,
+        mutable: bool,
+        changes: bool,
+        ladder_depth: Option<usize>,
+        heartbeats: bool,
+        split_markets: bool,
+    ) -> PyResult<Self> {
         let config = Config {
             cumulative_runner_tv,
+            changes,
+            ladder_depth,
+            heartbeats,
         };
 
         let paths = FilesSource::get_paths(paths);
-        let fs = FilesSource::new(paths).map_err(|op: std::io::Error| {
+        let fs = FilesSource::new(paths, split_markets).map_err(|op: std::io::Error| {
             PyErr::new::<exceptions::PyRuntimeError, _>(op.to_string())
         })?;
 
@@ -100,25 +119,16 @@ impl Iterator for FilesSource {
 }
 
 impl FilesSource {
-    pub fn new(paths: Vec<PathBuf>) -> Result<Self, Error> {
+    pub fn new(paths: Vec<PathBuf>, split_markets: bool) -> Result<Self, Error> {
         let (data_send, data_recv) = bounded(NUM_BUFFERED);
 
         rayon::spawn(move || {
-            let _ = paths
-                .into_iter()
-                .map(|path| (File::open(&path), path))
-                .map(|(file, path)| match file {
-                    Ok(f) => handle_file(path, f),
-                    Err(err) => Err(IOErr {
-                        file: Some(path),
-                        err,
-                    }),
-                })
+            let _ = stream_files(paths)
                 .flat_map(|r| match r {
-                    Ok(iter) => iter,
-                    Err(err) => Box::new(iter::once(Err(err))),
+                    Ok((name, bs)) if split_markets => split_buffer(name, bs),
+                    r => Box::new(iter::once(r)),
                 })
-                .map(|x| x.and_then(|(name, buf)| handle_buffer(name, buf)))
+                .map(|x| x.and_then(|(name, bs)| build_source(name, bs)))
                 .try_for_each(|r: Result<SourceItem, IOErr>| data_send.send(r));
         });
 
@@ -135,6 +145,27 @@ impl FilesSource {
     }
 }
 
+// every stream file in paths, with archives expanded and each file decompressed
+pub fn stream_files(
+    paths: Vec<PathBuf>,
+) -> impl Iterator<Item = Result<(PathBuf, Vec<u8>), IOErr>> {
+    paths
+        .into_iter()
+        .map(|path| (File::open(&path), path))
+        .map(|(file, path)| match file {
+            Ok(f) => handle_file(path, f),
+            Err(err) => Err(IOErr {
+                file: Some(path),
+                err,
+            }),
+        })
+        .flat_map(|r| match r {
+            Ok(iter) => iter,
+            Err(err) => Box::new(iter::once(Err(err))),
+        })
+        .map(|x| x.and_then(|(name, buf)| decode_buffer(name, buf)))
+}
+
 enum Buffer {
     Gz(Vec<u8>),
     Bz2(Vec<u8>),
@@ -191,7 +222,7 @@ fn handle_file(path: PathBuf, file: File) -> Result<BoxedArchiveIter, IOErr> {
     }
 }
 
-fn handle_buffer(path: PathBuf, buf: Buffer) -> Result<SourceItem, IOErr> {
+fn decode_buffer(path: PathBuf, buf: Buffer) -> Result<(PathBuf, Vec<u8>), IOErr> {
     let r = match buf {
         Buffer::Gz(buf) => {
             let mut dec = GzDecoder::new(&buf[..]);
@@ -205,10 +236,32 @@ fn handle_buffer(path: PathBuf, buf: Buffer) -> Result<SourceItem, IOErr> {
             dec.read_to_end(&mut out_buf).map(|_| out_buf)
         }
         Buffer::PlainText(buf) => Ok(buf),
-    }
-    .and_then(DeserializerWithData::build);
+    };
 
     match r {
+        Ok(bs) => Ok((path, bs)),
+        Err(err) => Err(IOErr {
+            file: Some(path),
+            err,
+        }),
+    }
+}
+
+type BoxedSplitIter = Box<dyn Iterator<Item = Result<(PathBuf, Vec<u8>), IOErr>>>;
+
+// each market in the file becomes its own virtual file, named <path>/<market_id>
+fn split_buffer(path: PathBuf, bs: Vec<u8>) -> BoxedSplitIter {
+    match split::split_markets(&path, &bs) {
+        Ok(markets) => Box::new(markets.into_iter().map(Ok)),
+        Err(err) => Box::new(iter::once(Err(IOErr {
+            file: Some(path),
+            err,
+        }))),
+    }
+}
+
+fn build_source(path: PathBuf, bs: Vec<u8>) -> Result<SourceItem, IOErr> {
+    match DeserializerWithData::build(bs) {
         Ok(deser) => Ok(SourceItem::new(path, deser)),
         Err(err) => Err(IOErr {
             file: Some(path),
@@ -0,0 +1,194 @@
+use pyo3::{exceptions, prelude::*};
+use serde::Deserialize;
+use simdutf8::basic::from_utf8;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::datetime::DateTime;
+use crate::deser::{self, Buffered, Deser, DeserError};
+use crate::ids::SelectionID;
+use crate::immutable::definition::{
+    DefinitionDiff, MarketDefinition, MarketDefinitionDeser, RunnerDefChange,
+};
+use crate::immutable::runner::Runner;
+
+// One version of a market's definition and the fields that changed from the version before it.
+// Fields are named as they are on the Market, runner fields as they are on the Runner under
+// runners.<selection_id>.<field> (runners.<selection_id>:<handicap>.<field> for handicap
+// markets). The first definition of each market has no changed fields. A definition without a
+// version can't be applied, it raises like any other missing field.
+#[pyclass(name = "DefinitionChange")]
+pub struct DefinitionChange {
+    #[pyo3(get)]
+    pub market_id: String,
+    #[pyo3(get)]
+    pub version: u64,
+    #[pyo3(get)]
+    pub publish_time: Option<DateTime>,
+    #[pyo3(get)]
+    pub changed: Vec<String>,
+    definition: String,
+}
+
+#[pymethods]
+impl DefinitionChange {
+    // the definition as it was sent, parsed into python objects
+    #[getter]
+    fn definition(&self, py: Python) -> PyResult<PyObject> {
+        Ok(py
+            .import("json")?
+            .call_method1("loads", (self.definition.as_str(),))?
+            .into_py(py))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<DefinitionChange {} version: {} changed: [{}]>",
+            self.market_id,
+            self.version,
+            self.changed.join(", ")
+        )
+    }
+}
+
+// Every definition in the file that changed something about its market. The messages are read
+// with the same deserializer the readers use and the definitions applied with the one the
+// markets are built with, which records the fields it changes as it goes. A message that
+// doesn't parse raises, the history would otherwise be missing whatever it changed.
+pub fn definition_history(file: &Path, bs: &[u8], py: Python) -> PyResult<Vec<DefinitionChange>> {
+    let parse_err = |err: DeserError| {
+        PyErr::new::<exceptions::PyRuntimeError, _>(format!(
+            "file: {} err: (JSON Parse Error) {}",
+            file.to_string_lossy(),
+            err
+        ))
+    };
+
+    let s = from_utf8(bs).map_err(|_| {
+        PyErr::new::<exceptions::PyRuntimeError, _>(format!(
+            "file: {} err: (Data Error) not valid utf8",
+            file.to_string_lossy()
+        ))
+    })?;
+
+    let config = Config {
+        cumulative_runner_tv: false,
+        changes: false,
+        ladder_depth: None,
+        heartbeats: false,
+    };
+
+    let mut deser = Deser::new(s);
+    let mut history = Vec::new();
+    let mut markets: HashMap<Cow<str>, Latest> = HashMap::new();
+
+    loop {
+        let msg = match deser.deserialize_next(PhantomData::<Msg>) {
+            Ok(msg) => msg,
+            Err(err) if err.is_eof() => break,
+            Err(err) => return Err(parse_err(err)),
+        };
+
+        for mc in msg.mc.into_iter().flatten() {
+            let raw = match mc.definition {
+                Some(raw) => raw,
+                None => continue,
+            };
+
+            let prev = markets.get(&mc.id);
+            let seed = MarketDefinitionDeser {
+                def: prev.map(|(def, _)| &**def),
+                runners: prev.map(|(_, runners)| runners.as_slice()),
+                next_runners: None,
+                py,
+                config,
+            };
+            let (def, runners, diff) = match deser::replay(raw, seed).map_err(parse_err)? {
+                (Some(def), runners, diff) => (def, runners, diff),
+                _ => continue,
+            };
+
+            let changed = match prev {
+                Some(_) => {
+                    let changed = changed_names(&diff);
+                    if changed.is_empty() {
+                        continue;
+                    }
+                    changed
+                }
+                None => Vec::new(),
+            };
+
+            history.push(DefinitionChange {
+                market_id: mc.id.to_string(),
+                version: def.version,
+                publish_time: msg.pt.map(DateTime::new),
+                changed,
+                definition: raw.get().to_owned(),
+            });
+
+            let runners = match (runners, markets.remove(&mc.id)) {
+                (Some(runners), _) => runners,
+                (None, Some((_, runners))) => runners,
+                (None, None) => Vec::new(),
+            };
+            markets.insert(mc.id, (def, runners));
+        }
+    }
+
+    Ok(history)
+}
+
+// a market's latest definition and the runners it was applied to
+type Latest = (Arc<MarketDefinition>, Vec<Py<Runner>>);
+
+// market fields first then runners, runner fields as runners.<key>.<field> and runners that
+// were added or removed as runners.<key>
+fn changed_names(diff: &DefinitionDiff) -> Vec<String> {
+    let mut changed = diff
+        .fields
+        .iter()
+        .map(|f| f.to_string())
+        .collect::<Vec<_>>();
+
+    for r in &diff.runners {
+        match r {
+            RunnerDefChange::Added(sid) | RunnerDefChange::Removed(sid) => {
+                changed.push(format!("runners.{}", runner_key(*sid)))
+            }
+            RunnerDefChange::Changed(sid, fields) => changed.extend(
+                fields
+                    .iter()
+                    .map(|f| format!("runners.{}.{}", runner_key(*sid), f)),
+            ),
+        }
+    }
+
+    changed
+}
+
+fn runner_key(sid: SelectionID) -> String {
+    match sid.handicap() {
+        Some(hc) if hc != 0.0 => format!("{}:{}", sid.id(), hc),
+        _ => sid.id().to_string(),
+    }
+}
+
+#[derive(Deserialize)]
+struct Msg<'a> {
+    pt: Option<u64>,
+    #[serde(borrow)]
+    mc: Option<Vec<Mc<'a>>>,
+}
+
+#[derive(Deserialize)]
+struct Mc<'a> {
+    #[serde(borrow)]
+    id: Cow<'a, str>,
+    #[serde(borrow, rename = "marketDefinition")]
+    definition: Option<Buffered<'a>>,
+}
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
 use crate::strings::FixedSizeString;
 
 pub type MarketID = FixedSizeString<11>;
@@ -24,3 +27,79 @@ impl SelectionID {
         self.1
     }
 }
+
+// handicaps are never nan, and -0.0 is folded into 0.0 so equal ids hash the same
+impl Eq for SelectionID {}
+impl Hash for SelectionID {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+        self.1.map(|hc| (hc + 0.0).to_bits()).hash(state);
+    }
+}
+
+// markets past this many runners get their selection ids hashed
+const RUNNER_INDEX_MIN: usize = 24;
+
+// Finds the position of a runner in a market by selection id. Most markets only have a handful
+// of runners, where scanning a copy of the ids is quicker than hashing them. Markets keep theirs
+// between updates and only rebuild it when their runners are added to or reordered
+#[derive(Clone)]
+pub enum RunnerIndex {
+    Scan(Vec<SelectionID>),
+    Hashed(HashMap<SelectionID, usize>),
+}
+
+impl Default for RunnerIndex {
+    fn default() -> Self {
+        Self::Scan(Vec::new())
+    }
+}
+
+impl RunnerIndex {
+    pub fn new<I: Iterator<Item = SelectionID>>(ids: I) -> Self {
+        let ids = ids.collect::<Vec<_>>();
+
+        if ids.len() < RUNNER_INDEX_MIN {
+            Self::Scan(ids)
+        } else {
+            Self::Hashed(ids.into_iter().enumerate().map(|(i, id)| (id, i)).collect())
+        }
+    }
+
+    pub fn get(&self, id: SelectionID) -> Option<usize> {
+        match self {
+            Self::Scan(ids) => ids.iter().position(|sid| *sid == id),
+            Self::Hashed(ids) => ids.get(&id).copied(),
+        }
+    }
+
+    // a runner was added to the end of the market
+    pub fn push(&mut self, id: SelectionID) {
+        match self {
+            Self::Scan(ids) => ids.push(id),
+            Self::Hashed(ids) => {
+                let i = ids.len();
+                ids.insert(id, i);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runner_index() {
+        for n in [3, RUNNER_INDEX_MIN + 3] {
+            let ids = (0..n as u32).map(|id| SelectionID::from((id * 10, None)));
+            let mut index = RunnerIndex::new(ids);
+
+            assert_eq!(index.get(SelectionID::from((20, None))), Some(2));
+            assert_eq!(index.get(SelectionID::from((20, Some(0.5)))), None);
+
+            index.push(SelectionID::from((20, Some(-0.0))));
+            assert_eq!(index.get(SelectionID::from((20, Some(0.0)))), Some(n));
+        }
+    }
+}
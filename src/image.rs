@@ -0,0 +1,149 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::Serialize;
+
+use crate::enums::{MarketBettingType, MarketStatus, PriceLadderDefinition, SelectionStatus};
+use crate::price_size::{F64OrStr, PriceSize};
+
+// A full image of a market in the shape of a stream mc entry. The immutable, mutable and bflw
+// markets all build one of these so they write out identical json. The definition borrows from
+// the market, runners sit behind python objects so their values are copied out.
+#[derive(Serialize)]
+pub struct McImage<'a> {
+    pub id: &'a str,
+    #[serde(rename = "marketDefinition")]
+    pub market_definition: DefinitionImage<'a>,
+    pub rc: Vec<RunnerChangeImage>,
+    pub img: bool,
+    pub tv: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefinitionImage<'a> {
+    pub bet_delay: u16,
+    pub betting_type: MarketBettingType,
+    pub bsp_market: bool,
+    pub bsp_reconciled: bool,
+    pub complete: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<&'a str>,
+    pub cross_matching: bool,
+    pub discount_allowed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub each_way_divisor: Option<f64>,
+    // ids are strings in the stream
+    pub event_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_name: Option<&'a str>,
+    pub event_type_id: String,
+    pub in_play: bool,
+    pub market_base_rate: f32,
+    pub market_time: &'a str,
+    pub market_type: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'a str>,
+    pub number_of_active_runners: u16,
+    pub number_of_winners: u8,
+    pub open_date: &'a str,
+    pub persistence_enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub race_type: Option<&'a str>,
+    pub regulators: &'a [String],
+    pub runners: Vec<RunnerDefinitionImage>,
+    pub runners_voidable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settled_time: Option<&'a str>,
+    pub status: MarketStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suspend_time: Option<&'a str>,
+    pub timezone: &'a str,
+    pub turn_in_play_enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub venue: Option<&'a str>,
+    pub version: u64,
+    // only kept by the bflw definition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_max_unit: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_min_unit: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_interval: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_ladder_definition: Option<PriceLadderImage>,
+    // spelt as it is in the stream
+    #[serde(rename = "keyLineDefiniton", skip_serializing_if = "Option::is_none")]
+    pub key_line_definition: Option<KeyLineImage>,
+}
+
+#[derive(Serialize)]
+pub struct PriceLadderImage {
+    pub r#type: PriceLadderDefinition,
+}
+
+#[derive(Serialize)]
+pub struct KeyLineImage {
+    pub kl: Vec<KeyLineSelectionImage>,
+}
+
+#[derive(Serialize)]
+pub struct KeyLineSelectionImage {
+    pub id: u32,
+    pub hc: f32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunnerDefinitionImage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adjustment_factor: Option<f64>,
+    pub status: SelectionStatus,
+    pub sort_priority: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removal_date: Option<String>,
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hc: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bsp: Option<F64OrStr>,
+}
+
+// an empty ladder and a missing one mean the same thing in an image
+#[derive(Serialize)]
+pub struct RunnerChangeImage {
+    pub id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hc: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub atb: Vec<PriceSize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub atl: Vec<PriceSize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub trd: Vec<PriceSize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spb: Vec<PriceSize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spl: Vec<PriceSize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spn: Option<F64OrStr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spf: Option<F64OrStr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ltp: Option<F64OrStr>,
+    pub tv: f64,
+}
+
+pub fn to_json<T: Serialize>(image: &T) -> PyResult<String> {
+    serde_json::to_string(image).map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+// goes through json so the dict matches the json exactly
+pub fn to_dict<T: Serialize>(image: &T, py: Python) -> PyResult<PyObject> {
+    let json = to_json(image)?;
+    Ok(py
+        .import("json")?
+        .call_method1("loads", (json,))?
+        .into_py(py))
+}
@@ -90,6 +90,12 @@ impl<T: PyRep + Default + ?Sized> Default for SyncObj<Arc<T>> {
     }
 }
 
+impl<T: PartialEq> PartialEq for SyncObj<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
 impl<T: AsRef<str>> AsRef<str> for SyncObj<T> {
     fn as_ref(&self) -> &str {
         self.value.as_ref()
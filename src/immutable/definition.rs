@@ -11,9 +11,10 @@ use crate::config::Config;
 use crate::datetime::DateTimeString;
 use crate::enums::{MarketBettingType, MarketStatus, SelectionStatus};
 use crate::errors::DataError;
-use crate::ids::{EventID, EventTypeID, SelectionID};
+use crate::ids::{EventID, EventTypeID, RunnerIndex, SelectionID};
 use crate::immutable::container::SyncObj;
 use crate::immutable::runner::Runner;
+use crate::pickle::{Decoder, Encoder, Pickle};
 use crate::price_size::F64OrStr;
 use crate::strings::FixedSizeString;
 
@@ -51,6 +52,158 @@ pub struct MarketDefinition {
     pub each_way_divisor: Option<f64>,
 }
 
+impl Pickle for MarketDefinition {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.bet_delay.encode(e, py);
+        self.bsp_market.encode(e, py);
+        self.bsp_reconciled.encode(e, py);
+        self.complete.encode(e, py);
+        self.cross_matching.encode(e, py);
+        self.discount_allowed.encode(e, py);
+        self.event_id.encode(e, py);
+        self.event_name.encode(e, py);
+        self.event_type_id.encode(e, py);
+        self.in_play.encode(e, py);
+        self.market_base_rate.encode(e, py);
+        self.market_type.encode(e, py);
+        self.race_type.encode(e, py);
+        self.market_name.encode(e, py);
+        self.number_of_active_runners.encode(e, py);
+        self.number_of_winners.encode(e, py);
+        self.persistence_enabled.encode(e, py);
+        self.runners_voidable.encode(e, py);
+        self.timezone.encode(e, py);
+        self.turn_in_play_enabled.encode(e, py);
+        self.venue.encode(e, py);
+        self.version.encode(e, py);
+        self.status.encode(e, py);
+        self.betting_type.encode(e, py);
+        self.market_time.encode(e, py);
+        self.open_date.encode(e, py);
+        self.suspend_time.encode(e, py);
+        self.settled_time.encode(e, py);
+        self.country_code.encode(e, py);
+        self.regulators.encode(e, py);
+        self.each_way_divisor.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            bet_delay: Pickle::decode(d, py)?,
+            bsp_market: Pickle::decode(d, py)?,
+            bsp_reconciled: Pickle::decode(d, py)?,
+            complete: Pickle::decode(d, py)?,
+            cross_matching: Pickle::decode(d, py)?,
+            discount_allowed: Pickle::decode(d, py)?,
+            event_id: Pickle::decode(d, py)?,
+            event_name: Pickle::decode(d, py)?,
+            event_type_id: Pickle::decode(d, py)?,
+            in_play: Pickle::decode(d, py)?,
+            market_base_rate: Pickle::decode(d, py)?,
+            market_type: Pickle::decode(d, py)?,
+            race_type: Pickle::decode(d, py)?,
+            market_name: Pickle::decode(d, py)?,
+            number_of_active_runners: Pickle::decode(d, py)?,
+            number_of_winners: Pickle::decode(d, py)?,
+            persistence_enabled: Pickle::decode(d, py)?,
+            runners_voidable: Pickle::decode(d, py)?,
+            timezone: Pickle::decode(d, py)?,
+            turn_in_play_enabled: Pickle::decode(d, py)?,
+            venue: Pickle::decode(d, py)?,
+            version: Pickle::decode(d, py)?,
+            status: Pickle::decode(d, py)?,
+            betting_type: Pickle::decode(d, py)?,
+            market_time: Pickle::decode(d, py)?,
+            open_date: Pickle::decode(d, py)?,
+            suspend_time: Pickle::decode(d, py)?,
+            settled_time: Pickle::decode(d, py)?,
+            country_code: Pickle::decode(d, py)?,
+            regulators: Pickle::decode(d, py)?,
+            each_way_divisor: Pickle::decode(d, py)?,
+        })
+    }
+}
+
+macro_rules! changed_fields {
+    ($prev:expr, $next:expr, $($field:ident),+ $(,)?) => {{
+        let mut fields = Vec::new();
+        $(
+            if $prev.$field != $next.$field {
+                fields.push(stringify!($field));
+            }
+        )+
+        fields
+    }};
+}
+
+impl MarketDefinition {
+    // the fields that differ in next, named as they are on the Market
+    pub fn changed_fields(&self, next: &MarketDefinition) -> Vec<&'static str> {
+        changed_fields!(
+            self,
+            next,
+            bet_delay,
+            betting_type,
+            bsp_market,
+            bsp_reconciled,
+            complete,
+            country_code,
+            cross_matching,
+            discount_allowed,
+            each_way_divisor,
+            event_id,
+            event_name,
+            event_type_id,
+            in_play,
+            market_base_rate,
+            market_name,
+            market_time,
+            market_type,
+            number_of_active_runners,
+            number_of_winners,
+            open_date,
+            persistence_enabled,
+            race_type,
+            regulators,
+            runners_voidable,
+            settled_time,
+            status,
+            suspend_time,
+            timezone,
+            turn_in_play_enabled,
+            venue,
+            version,
+        )
+    }
+}
+
+// What a definition changed, recorded as the deserializer applies it. Market fields are named as
+// they are on the Market and runner fields as they are on the Runner.
+#[derive(Default)]
+pub struct DefinitionDiff {
+    pub fields: Vec<&'static str>,
+    pub runners: Vec<RunnerDefChange>,
+    // runners were added, dropped or moved, so their positions from before no longer hold
+    pub moved: bool,
+    pub transitions: Vec<Transition>,
+}
+
+pub enum RunnerDefChange {
+    Added(SelectionID),
+    Removed(SelectionID),
+    Changed(SelectionID, Vec<&'static str>),
+}
+
+// A status the definition moved a market or runner out of, into the one after it. Only recorded
+// against a previous definition, the first one a market or runner has isn't a transition, even
+// for a runner an rc created before it.
+#[derive(Clone, Copy)]
+pub enum Transition {
+    Market(MarketStatus, MarketStatus),
+    InPlay,
+    BspReconciled,
+    Runner(SelectionID, SelectionStatus, SelectionStatus),
+}
+
 #[derive(Debug, Default)]
 struct MarketDefinitionUpdate<'a> {
     bet_delay: Option<u16>,
@@ -373,7 +526,11 @@ pub struct MarketDefinitionDeser<'a, 'py> {
     pub config: Config,
 }
 impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketDefinitionDeser<'a, 'py> {
-    type Value = (Option<Arc<MarketDefinition>>, Option<Vec<Py<Runner>>>);
+    type Value = (
+        Option<Arc<MarketDefinition>>,
+        Option<Vec<Py<Runner>>>,
+        DefinitionDiff,
+    );
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
@@ -429,7 +586,11 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketDefinitionDeser<'a, 'py> {
             config: Config,
         }
         impl<'de, 'a, 'py> Visitor<'de> for MarketDefinitionVisitor<'a, 'py> {
-            type Value = (Option<Arc<MarketDefinition>>, Option<Vec<Py<Runner>>>);
+            type Value = (
+                Option<Arc<MarketDefinition>>,
+                Option<Vec<Py<Runner>>>,
+                DefinitionDiff,
+            );
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("")
@@ -441,6 +602,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketDefinitionDeser<'a, 'py> {
             {
                 let mut upt: MarketDefinitionUpdate = MarketDefinitionUpdate::default();
                 let mut next_runners: Option<Vec<Py<Runner>>> = self.next_runners;
+                let mut diff = DefinitionDiff::default();
 
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -448,6 +610,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketDefinitionDeser<'a, 'py> {
                             next_runners = map.next_value_seed(RunnerDefSeq {
                                 runners: self.runners,
                                 next: next_runners,
+                                diff: &mut diff,
                                 py: self.py,
                                 config: self.config,
                             })?;
@@ -579,9 +742,22 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketDefinitionDeser<'a, 'py> {
                     Error::custom(format!("missing required field <{}>", err.missing_field))
                 })?;
 
-                let next_def = Some(Arc::new(next_def));
+                if let Some(def) = self.def {
+                    diff.fields = def.changed_fields(&next_def);
+
+                    if def.status != next_def.status {
+                        diff.transitions
+                            .push(Transition::Market(def.status, next_def.status));
+                    }
+                    if next_def.in_play && !def.in_play {
+                        diff.transitions.push(Transition::InPlay);
+                    }
+                    if next_def.bsp_reconciled && !def.bsp_reconciled {
+                        diff.transitions.push(Transition::BspReconciled);
+                    }
+                }
 
-                Ok((next_def, next_runners))
+                Ok((Some(Arc::new(next_def)), next_runners, diff))
             }
         }
 
@@ -642,6 +818,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketDefinitionDeser<'a, 'py> {
 pub struct RunnerDefSeq<'a, 'py> {
     pub runners: Option<&'a [Py<Runner>]>,
     pub next: Option<Vec<Py<Runner>>>,
+    pub diff: &'a mut DefinitionDiff,
     pub py: Python<'py>,
     pub config: Config,
 }
@@ -655,6 +832,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeq<'a, 'py> {
         struct RunnerSeqVisitor<'a, 'py> {
             runners: Option<&'a [Py<Runner>]>,
             next: Option<Vec<Py<Runner>>>,
+            diff: &'a mut DefinitionDiff,
             py: Python<'py>,
             #[allow(dead_code)]
             config: Config,
@@ -706,7 +884,26 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeq<'a, 'py> {
                                 // if present mutate them inplace and check if theyre in the right order
                                 match runner_index {
                                     Some((r, j)) => {
-                                        change.update_mut(r, self.py);
+                                        if r.defined && r.status != change.status {
+                                            self.diff.transitions.push(Transition::Runner(
+                                                sid,
+                                                r.status,
+                                                change.status,
+                                            ));
+                                        }
+
+                                        let fields = change.changed(&r, self.py);
+                                        // a runner an rc created takes its status from its
+                                        // first definition
+                                        let update = !fields.is_empty() || !r.defined;
+                                        if !fields.is_empty() {
+                                            self.diff
+                                                .runners
+                                                .push(RunnerDefChange::Changed(sid, fields));
+                                        }
+                                        if update {
+                                            change.update_mut(r, self.py);
+                                        }
 
                                         if i != j {
                                             Action::Swap(i, j)
@@ -714,16 +911,25 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeq<'a, 'py> {
                                             Action::Nothing
                                         }
                                     }
-                                    None => Action::Insert(
-                                        Py::new(self.py, change.create(self.py)).unwrap(),
-                                        i,
-                                    ),
+                                    None => {
+                                        self.diff.runners.push(RunnerDefChange::Added(sid));
+                                        Action::Insert(
+                                            Py::new(self.py, change.create(self.py)).unwrap(),
+                                            i,
+                                        )
+                                    }
                                 }
                             };
 
                             match action {
-                                Action::Insert(r, i) => n.insert(i, r),
-                                Action::Swap(a, b) => n.swap(a, b),
+                                Action::Insert(r, i) => {
+                                    n.insert(i, r);
+                                    self.diff.moved = true;
+                                }
+                                Action::Swap(a, b) => {
+                                    n.swap(a, b);
+                                    self.diff.moved = true;
+                                }
                                 Action::Nothing => {}
                             }
 
@@ -736,10 +942,12 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeq<'a, 'py> {
                     // no previous runner serialization this update, serialize in rd order
                     None => {
                         let mut next: Option<Vec<Py<Runner>>> = None;
+                        let mut seen: Vec<SelectionID> = Vec::new();
                         let mut i = 0;
 
                         while let Some(change) = seq.next_element::<RunnerDefUpdate>()? {
                             let sid = SelectionID::from((change.id, change.hc));
+                            seen.push(sid);
 
                             let r = self.runners.and_then(|rs| {
                                 rs.iter()
@@ -747,11 +955,43 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeq<'a, 'py> {
                                     .map(|i| (unsafe { rs.get_unchecked(i) }, i))
                             });
 
+                            let (fields, defined) = match r {
+                                Some((r, _)) => {
+                                    let r = r.borrow(self.py);
+                                    if r.defined && r.status != change.status {
+                                        self.diff.transitions.push(Transition::Runner(
+                                            sid,
+                                            r.status,
+                                            change.status,
+                                        ));
+                                    }
+                                    (change.changed(&r, self.py), r.defined)
+                                }
+                                None => (Vec::new(), true),
+                            };
+                            let listed = !fields.is_empty();
+                            // a runner an rc created takes its status from its first definition
+                            let changed = listed || !defined;
+                            match r {
+                                Some((_, ri)) => {
+                                    if listed {
+                                        self.diff
+                                            .runners
+                                            .push(RunnerDefChange::Changed(sid, fields));
+                                    }
+                                    self.diff.moved |= ri != i;
+                                }
+                                None => {
+                                    self.diff.runners.push(RunnerDefChange::Added(sid));
+                                    self.diff.moved = true;
+                                }
+                            }
+
                             match (r, next.as_mut()) {
                                 (Some((r, ri)), None) => {
                                     let sel = r.borrow(self.py);
 
-                                    if change.diff(&sel, self.py) {
+                                    if changed {
                                         let rs = self.runners.unwrap();
                                         let mut n: Vec<Py<Runner>> =
                                             Vec::with_capacity(rs.len() + 1);
@@ -780,9 +1020,8 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeq<'a, 'py> {
                                 }
 
                                 (Some((r, _ri)), Some(n)) => {
-                                    let sel = r.borrow(self.py);
-
-                                    let nr = if change.diff(&sel, self.py) {
+                                    let nr = if changed {
+                                        let sel = r.borrow(self.py);
                                         Py::new(self.py, change.update(&sel, self.py)).unwrap()
                                     } else {
                                         r.clone_ref(self.py)
@@ -815,6 +1054,21 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeq<'a, 'py> {
                             i += 1;
                         }
 
+                        // runners left out of the definition are dropped with the rest of the
+                        // previous list
+                        if next.is_some() && let Some(rs) = self.runners {
+                            let seen = RunnerIndex::new(seen.into_iter());
+                            let removed = rs
+                                .iter()
+                                .map(|r| r.borrow(self.py).selection_id)
+                                .filter(|sid| seen.get(*sid).is_none())
+                                .map(RunnerDefChange::Removed)
+                                .collect::<Vec<_>>();
+
+                            self.diff.moved |= !removed.is_empty();
+                            self.diff.runners.extend(removed);
+                        }
+
                         Ok(next)
                     }
                 }
@@ -824,6 +1078,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeq<'a, 'py> {
         deserializer.deserialize_seq(RunnerSeqVisitor {
             runners: self.runners,
             next: self.next,
+            diff: self.diff,
             py: self.py,
             config: self.config,
         })
@@ -865,22 +1120,46 @@ impl<'a> RunnerDefUpdate<'a> {
             ex: Py::new(py, RunnerBookEX::default()).unwrap(),
             total_matched: 0.0,
             last_price_traded: None,
+            traded_volume_delta: Default::default(),
+            total_matched_delta: 0.0,
+            is_crossed: false,
+            last_update_time: None,
+            defined: true,
         }
     }
 
-    fn diff(&self, runner: &Runner, py: Python) -> bool {
-        runner.status != self.status
-            || runner.adjustment_factor != self.adjustment_factor
-            || runner.sort_priority != self.sort_priority
-            || runner.sp.borrow(py).actual_sp != self.bsp.map(|f| *f)
-            || ((runner.name.is_none() && self.name.is_some())
-                || runner
-                    .name
-                    .is_some_and(|s| !self.name.contains(&s.as_ref())))
-            || ((runner.removal_date.is_some() != self.removal_date.is_some())
-                || runner
-                    .removal_date
-                    .is_some_and(|s| !self.removal_date.contains(&s.as_str())))
+    // the fields that would change on the runner, named as they are on the Runner
+    fn changed(&self, runner: &Runner, py: Python) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+
+        if runner.adjustment_factor != self.adjustment_factor {
+            fields.push("adjustment_factor");
+        }
+        if (runner.name.is_none() && self.name.is_some())
+            || runner
+                .name
+                .is_some_and(|s| !self.name.contains(&s.as_ref()))
+        {
+            fields.push("name");
+        }
+        if (runner.removal_date.is_some() != self.removal_date.is_some())
+            || runner
+                .removal_date
+                .is_some_and(|s| !self.removal_date.contains(&s.as_str()))
+        {
+            fields.push("removal_date");
+        }
+        if runner.sort_priority != self.sort_priority {
+            fields.push("sort_priority");
+        }
+        if runner.sp.borrow(py).actual_sp != self.bsp.map(|f| *f) {
+            fields.push("sp.actual_sp");
+        }
+        if runner.status != self.status {
+            fields.push("status");
+        }
+
+        fields
     }
 
     fn update(&self, runner: &Runner, py: Python) -> Runner {
@@ -933,6 +1212,11 @@ impl<'a> RunnerDefUpdate<'a> {
             last_price_traded: runner.last_price_traded,
             ex: runner.ex.clone(),
             sp,
+            traded_volume_delta: Default::default(),
+            total_matched_delta: 0.0,
+            is_crossed: runner.is_crossed,
+            last_update_time: runner.last_update_time,
+            defined: true,
         }
     }
 
@@ -979,6 +1263,6 @@ impl<'a> RunnerDefUpdate<'a> {
                 None => { runner.removal_date = None; }
             }
         }
-
+        runner.defined = true;
     }
 }
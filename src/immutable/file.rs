@@ -3,7 +3,7 @@ use std::path::Path;
 
 use super::market::{Market, MarketsDeser};
 use crate::config::Config;
-use crate::file_iter::{FileIter, IntoMarketIter, MarketID};
+use crate::file_iter::{Books, FileIter, IntoMarketIter, MarketID};
 use crate::market_source::SourceItem;
 
 #[pyclass(name = "File")]
@@ -41,7 +41,7 @@ impl IntoMarketIter for ImmutableRep {
     type Deser<'a, 'de, 'py> = MarketsDeser<'a, 'py>;
 
     fn new<'a, 'de, 'py>(
-        books: &'a [Py<Self::Market>],
+        books: &'a Books<Self::Market>,
         py: Python<'py>,
         config: Config,
     ) -> Self::Deser<'a, 'de, 'py> {
@@ -2,18 +2,27 @@ use core::fmt;
 use pyo3::prelude::*;
 use serde::de::{DeserializeSeed, Error, IgnoredAny, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
-use serde_json::value::RawValue;
+use std::borrow::Cow;
 use std::collections::VecDeque;
 use std::sync::Arc;
 
 use super::container::SyncObj;
 use super::definition::MarketDefinition;
+use super::market_change::{MarketChange, RunnerChange};
 use super::runner::Runner;
+use crate::analytics;
+use crate::array;
 use crate::config::Config;
 use crate::datetime::DateTime;
-use crate::ids::{MarketID, Clk};
-use crate::immutable::definition::MarketDefinitionDeser;
+use crate::deser::{self, Buffered};
+use crate::enums::{ChangeType, MarketStatus, SelectionStatus};
+use crate::file_iter::Books;
+use crate::ids::{Clk, MarketID, RunnerIndex, SelectionID};
+use crate::image::{self, DefinitionImage, McImage, RunnerChangeImage, RunnerDefinitionImage};
+use crate::immutable::definition::{DefinitionDiff, MarketDefinitionDeser, Transition};
 use crate::immutable::runner::RunnerChangeSeq;
+use crate::pickle::{self, Decoder, Encoder, Kind, Pickle};
+use crate::price_size::{F64OrStr, PriceSize};
 
 #[derive(Clone)]
 #[pyclass(name = "Market")]
@@ -29,7 +38,73 @@ pub struct Market {
     #[pyo3(get)]
     total_matched: f64,
     // uses getters to make the fields appear on the root market object
-    def: Arc<MarketDefinition>,
+    pub def: Arc<MarketDefinition>,
+    // what changed from the previous update, only built when the changes option is set
+    #[pyo3(get)]
+    changes: Option<Py<MarketChange>>,
+    // taken from the stream message that carried the update
+    #[pyo3(get)]
+    change_type: Option<ChangeType>,
+    #[pyo3(get)]
+    conflated: bool,
+    #[pyo3(get)]
+    stream_status: Option<u16>,
+    // publish time of the last update that traded on any runner
+    #[pyo3(get)]
+    last_trade_time: Option<DateTime>,
+    // positions of the runners by selection id, shared with the next update unless it adds or
+    // moves runners
+    pub index: Arc<RunnerIndex>,
+    // runners holding traded or matched deltas, reset by the next update that doesn't touch them
+    deltas: Vec<SelectionID>,
+    // statuses the update's definition moved the market and its runners through
+    pub transitions: Vec<Transition>,
+}
+
+// changes are per update and aren't carried through a pickle
+impl Pickle for Market {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.market_id.encode(e, py);
+        self.publish_time.encode(e, py);
+        self.clk.encode(e, py);
+        self.runners.encode(e, py);
+        self.total_matched.encode(e, py);
+        self.def.as_ref().encode(e, py);
+        self.change_type.encode(e, py);
+        self.conflated.encode(e, py);
+        self.stream_status.encode(e, py);
+        self.last_trade_time.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        let mut m = Self {
+            market_id: Pickle::decode(d, py)?,
+            publish_time: Pickle::decode(d, py)?,
+            clk: Pickle::decode(d, py)?,
+            runners: Pickle::decode(d, py)?,
+            total_matched: Pickle::decode(d, py)?,
+            def: Arc::new(Pickle::decode(d, py)?),
+            changes: None,
+            change_type: Pickle::decode(d, py)?,
+            conflated: Pickle::decode(d, py)?,
+            stream_status: Pickle::decode(d, py)?,
+            last_trade_time: Pickle::decode(d, py)?,
+            index: Default::default(),
+            deltas: Vec::new(),
+            transitions: Vec::new(),
+        };
+        m.index = Arc::new(RunnerIndex::new(
+            m.runners.iter().map(|r| r.borrow(py).selection_id),
+        ));
+        m.deltas = m
+            .runners
+            .iter()
+            .map(|r| r.borrow(py))
+            .filter(|r| r.has_deltas())
+            .map(|r| r.selection_id)
+            .collect();
+
+        Ok(m)
+    }
 }
 
 #[pymethods]
@@ -37,6 +112,23 @@ impl Market {
     fn copy(slf: PyRef<Self>) -> PyRef<Self> {
         slf
     }
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        pickle::reduce(Kind::Market, self, py)
+    }
+    // immutable, so copies can share the same object
+    fn __copy__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+    fn __deepcopy__(slf: PyRef<Self>, _memo: &PyAny) -> PyRef<Self> {
+        slf
+    }
+    // a full image of the market as a stream mc entry
+    fn to_json(&self, py: Python) -> PyResult<String> {
+        image::to_json(&self.image(py))
+    }
+    fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        image::to_dict(&self.image(py), py)
+    }
     #[getter(event_id)]
     fn get_event_id(&self, py: Python) -> PyObject {
         self.def.event_id.into_py(py)
@@ -161,10 +253,191 @@ impl Market {
     fn get_regulators(&self, py: Python) -> PyObject {
         self.def.regulators.to_object(py)
     }
+    #[getter(back_overround)]
+    fn get_back_overround(&self, py: Python) -> f64 {
+        analytics::book_percentage(self.active_runners(py).filter_map(|r| {
+            analytics::best(&r.ex.borrow(py).available_to_back).map(|ps| ps.price)
+        }))
+    }
+    #[getter(lay_overround)]
+    fn get_lay_overround(&self, py: Python) -> f64 {
+        analytics::book_percentage(self.active_runners(py).filter_map(|r| {
+            analytics::best(&r.ex.borrow(py).available_to_lay).map(|ps| ps.price)
+        }))
+    }
+    #[getter(implied_probability)]
+    fn get_implied_probability(&self, py: Python) -> f64 {
+        analytics::book_percentage(
+            self.active_runners(py)
+                .filter_map(|r| r.last_price_traded),
+        )
+    }
+
+    // one row per runner of [selection_id, last_price_traded, total_matched, best_back, best_lay]
+    // with missing values as nan
+    fn runner_matrix(&self, py: Python) -> PyResult<PyObject> {
+        let values = self.runners.iter().flat_map(|r| {
+            let r = r.borrow(py);
+            let ex = r.ex.borrow(py);
+
+            [
+                r.selection_id.id() as f64,
+                r.last_price_traded.unwrap_or(f64::NAN),
+                r.total_matched,
+                analytics::best(&ex.available_to_back).map_or(f64::NAN, |ps| ps.price),
+                analytics::best(&ex.available_to_lay).map_or(f64::NAN, |ps| ps.price),
+            ]
+        });
+
+        array::matrix(self.runners.len(), 5, values, py)
+    }
+}
+
+impl Market {
+    // a heartbeat changes nothing, so the copy it yields has empty deltas and change set
+    fn heartbeat(&self, config: Config, py: Python) -> Self {
+        let runners = if self.deltas.is_empty() {
+            self.runners.clone()
+        } else {
+            let empty = Default::default();
+            let rs = self
+                .runners
+                .iter()
+                .map(|r| {
+                    let runner = r.borrow(py);
+                    if runner.has_deltas() {
+                        Py::new(py, runner.without_deltas(&empty, py)).unwrap()
+                    } else {
+                        r.clone_ref(py)
+                    }
+                })
+                .collect();
+            SyncObj::new(Arc::new(rs))
+        };
+
+        let changes = config.changes.then(|| {
+            let changes = MarketChange::new(
+                None,
+                false,
+                Vec::new(),
+                &self.def,
+                &runners,
+                &self.index,
+                self.total_matched,
+                self.total_matched,
+                py,
+            );
+            Py::new(py, changes).unwrap()
+        });
+
+        Self {
+            runners,
+            changes,
+            conflated: false,
+            deltas: Vec::new(),
+            transitions: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    pub fn image(&self, py: Python) -> McImage {
+        let def = &self.def;
+
+        McImage {
+            id: self.market_id.as_str(),
+            market_definition: DefinitionImage {
+                bet_delay: def.bet_delay,
+                betting_type: def.betting_type,
+                bsp_market: def.bsp_market,
+                bsp_reconciled: def.bsp_reconciled,
+                complete: def.complete,
+                country_code: def.country_code.as_ref().map(|s| s.as_str()),
+                cross_matching: def.cross_matching,
+                discount_allowed: def.discount_allowed,
+                each_way_divisor: def.each_way_divisor,
+                event_id: def.event_id.to_string(),
+                event_name: def.event_name.as_deref().map(|s| s.as_ref()),
+                event_type_id: def.event_type_id.to_string(),
+                in_play: def.in_play,
+                market_base_rate: def.market_base_rate,
+                market_time: def.market_time.as_ref(),
+                market_type: def.market_type.as_ref(),
+                name: def.market_name.as_deref().map(|s| s.as_ref()),
+                number_of_active_runners: def.number_of_active_runners,
+                number_of_winners: def.number_of_winners,
+                open_date: def.open_date.as_ref(),
+                persistence_enabled: def.persistence_enabled,
+                race_type: def.race_type.as_deref().map(|s| s.as_ref()),
+                regulators: def.regulators.as_slice(),
+                runners: self
+                    .runners
+                    .iter()
+                    .map(|r| {
+                        let r = r.borrow(py);
+                        RunnerDefinitionImage {
+                            adjustment_factor: r.adjustment_factor,
+                            status: r.status,
+                            sort_priority: r.sort_priority,
+                            removal_date: r.removal_date.as_ref().map(|s| s.to_string()),
+                            id: r.selection_id.id(),
+                            name: r.name.as_ref().map(|s| s.to_string()),
+                            hc: r.selection_id.handicap(),
+                            bsp: r.sp.borrow(py).actual_sp.map(F64OrStr::from),
+                        }
+                    })
+                    .collect(),
+                runners_voidable: def.runners_voidable,
+                settled_time: def.settled_time.as_deref().map(|s| s.as_ref()),
+                status: def.status,
+                suspend_time: def.suspend_time.as_deref().map(|s| s.as_ref()),
+                timezone: def.timezone.as_ref(),
+                turn_in_play_enabled: def.turn_in_play_enabled,
+                venue: def.venue.as_deref().map(|s| s.as_ref()),
+                version: def.version,
+                line_max_unit: None,
+                line_min_unit: None,
+                line_interval: None,
+                price_ladder_definition: None,
+                key_line_definition: None,
+            },
+            rc: self
+                .runners
+                .iter()
+                .map(|r| {
+                    let r = r.borrow(py);
+                    let ex = r.ex.borrow(py);
+                    let sp = r.sp.borrow(py);
+
+                    RunnerChangeImage {
+                        id: r.selection_id.id(),
+                        hc: r.selection_id.handicap(),
+                        atb: ex.available_to_back.to_vec(),
+                        atl: ex.available_to_lay.to_vec(),
+                        trd: ex.traded_volume.to_vec(),
+                        spb: sp.lay_liability_taken.to_vec(),
+                        spl: sp.back_stake_taken.to_vec(),
+                        spn: sp.near_price.map(F64OrStr::from),
+                        spf: sp.far_price.map(F64OrStr::from),
+                        ltp: r.last_price_traded.map(F64OrStr::from),
+                        tv: r.total_matched,
+                    }
+                })
+                .collect(),
+            img: true,
+            tv: self.total_matched,
+        }
+    }
+
+    fn active_runners<'a>(&'a self, py: Python<'a>) -> impl Iterator<Item = PyRef<'a, Runner>> {
+        self.runners
+            .iter()
+            .map(move |r| r.borrow(py))
+            .filter(|r| r.status == SelectionStatus::Active)
+    }
 }
 
 pub struct MarketsDeser<'a, 'py> {
-    pub markets: &'a [Py<Market>],
+    pub markets: &'a Books<Market>,
     pub py: Python<'py>,
     pub config: Config,
 }
@@ -180,12 +453,19 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketsDeser<'a, 'py> {
         enum Field {
             Op,
             Clk,
+            #[serde(rename = "initialClk")]
+            InitialClk,
             Pt,
+            Ct,
+            Status,
             Mc,
+            // id, heartbeatMs, conflateMs, segmentType and the fields of connection/status lines
+            #[serde(other)]
+            Other,
         }
 
         struct MarketsDeserVisitor<'a, 'py> {
-            markets: &'a [Py<Market>],
+            markets: &'a Books<Market>,
             py: Python<'py>,
             config: Config,
         }
@@ -202,16 +482,26 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketsDeser<'a, 'py> {
             {
                 let mut pt: Option<DateTime> = None;
                 let mut clk: Option<Clk> = None;
+                let mut initial_clk: Option<Clk> = None;
+                let mut ct: Option<ChangeType> = None;
+                let mut status: Option<u16> = None;
                 let mut books: VecDeque<Py<Market>> = VecDeque::new();
+                let mut late_mc: Option<Buffered> = None;
 
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::Pt => {
                             pt = Some(DateTime::new(map.next_value::<u64>()?));
                         }
+                        // betfair writes pt ahead of mc, anything else is held back until pt
+                        // has been read so trades can be timed
+                        Field::Mc if pt.is_none() => {
+                            late_mc = Some(map.next_value::<Buffered>()?);
+                        }
                         Field::Mc => {
                             books = map.next_value_seed(MarketMcSeq {
                                 markets: self.markets,
+                                pt,
                                 py: self.py,
                                 config: self.config,
                             })?;
@@ -219,13 +509,48 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketsDeser<'a, 'py> {
                         Field::Clk => {
                             clk = Some(map.next_value::<Clk>()?);
                         }
-                        Field::Op => {
+                        Field::InitialClk => {
+                            initial_clk = Some(map.next_value::<Clk>()?);
+                        }
+                        Field::Ct => {
+                            ct = map.next_value::<Option<ChangeType>>()?;
+                        }
+                        Field::Status => {
+                            status = map.next_value::<Option<u16>>()?;
+                        }
+                        Field::Op | Field::Other => {
                             map.next_value::<IgnoredAny>()?;
                         }
                     }
                 }
 
-                if let (Some(pt), Some(clk)) = (pt, clk) {
+                if let Some(raw) = late_mc {
+                    books = deser::replay(
+                        raw,
+                        MarketMcSeq {
+                            markets: self.markets,
+                            pt,
+                            py: self.py,
+                            config: self.config,
+                        },
+                    )
+                    .map_err(Error::custom)?;
+                }
+
+                // heartbeats carry no market changes, but re-yield every open market so gaps in
+                // a recording show up as gaps in publish_time
+                if self.config.heartbeats && ct == Some(ChangeType::Heartbeat) && books.is_empty() {
+                    books = self
+                        .markets
+                        .iter()
+                        .map(|m| m.borrow(self.py))
+                        .filter(|m| m.def.status != MarketStatus::Closed)
+                        .map(|m| Py::new(self.py, m.heartbeat(self.config, self.py)).unwrap())
+                        .collect();
+                }
+
+                // a sub image can arrive with only an initialClk
+                if let (Some(pt), Some(clk)) = (pt, clk.or(initial_clk)) {
                     books.iter_mut().for_each(|mb| {
                         let mut m = mb.borrow_mut(self.py);
                         m.publish_time = pt;
@@ -233,11 +558,17 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketsDeser<'a, 'py> {
                     });
                 }
 
+                books.iter_mut().for_each(|mb| {
+                    let mut m = mb.borrow_mut(self.py);
+                    m.change_type = ct;
+                    m.stream_status = status;
+                });
+
                 Ok(books)
             }
         }
 
-        const FIELDS: &[&str] = &["op", "pt", "clk", "mc"];
+        const FIELDS: &[&str] = &["op", "pt", "clk", "initialClk", "ct", "status", "mc"];
         deserializer.deserialize_struct(
             "Market",
             FIELDS,
@@ -252,7 +583,8 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketsDeser<'a, 'py> {
 
 // Used for serializing in place over the marketChange `mc` array
 struct MarketMcSeq<'a, 'py> {
-    markets: &'a [Py<Market>],
+    markets: &'a Books<Market>,
+    pt: Option<DateTime>,
     py: Python<'py>,
     config: Config,
 }
@@ -264,7 +596,8 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMcSeq<'a, 'py> {
         D: Deserializer<'de>,
     {
         struct MarketMcSeqVisitor<'a, 'py> {
-            markets: &'a [Py<Market>],
+            markets: &'a Books<Market>,
+            pt: Option<DateTime>,
             py: Python<'py>,
             config: Config,
         }
@@ -287,44 +620,42 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMcSeq<'a, 'py> {
 
                 let mut next_books: VecDeque<Py<Market>> = VecDeque::new();
 
-                while let Some(raw) = seq.next_element::<&RawValue>()? {
-                    let mut deser = serde_json::Deserializer::from_str(raw.get());
-                    let mid: IdImg = serde_json::from_str(raw.get()).map_err(Error::custom)?;
+                while let Some(raw) = seq.next_element::<Buffered>()? {
+                    let mid: IdImg = deser::peek(&raw).map_err(Error::custom)?;
 
                     let (mb, i) = {
                         let i = next_books.iter()
                             .position(|m| (*m).borrow(self.py).market_id.as_str() == mid.id);
 
                         match i {
-                            Some(i) if !mid.img.contains(&true) => {
+                            Some(i) => {
                                 (next_books.get(i).map(|m| m.borrow(self.py)), Some(i))
                             },
-                            Some(i) if mid.img.contains(&true) => {
-                                (None, Some(i))
-                            },
-                            None if !mid.img.contains(&true) => {
-                                (self.markets.iter().find(|m| {
-                                    (*m).borrow(self.py).market_id.as_str() == mid.id
-                                })
-                                .map(|o| o.borrow(self.py)), None)
+                            None => {
+                                (self.markets.get(mid.id.as_str()).map(|o| o.borrow(self.py)), None)
                             }
-                            _ => (None, None),
                         }  
                     };
 
-                    let next_m = MarketMc {
-                        id: mid.id,
-                        market: mb,
-                        py: self.py,
-                        config: self.config,
-                    }
-                    .deserialize(&mut deser)
+                    // an image replaces the runners of the previous market, but the previous
+                    // market is still passed through to keep its ids and definition
+                    let next_m = deser::replay(
+                        raw,
+                        MarketMc {
+                            id: mid.id,
+                            market: mb,
+                            img: mid.img.contains(&true),
+                            pt: self.pt,
+                            py: self.py,
+                            config: self.config,
+                        },
+                    )
                     .map_err(Error::custom)?;
 
                     match (next_m, i) {
                         (Some(m), Some(i)) => next_books[i] = Py::new(self.py, m).unwrap(),
                         (Some(m), None) => next_books.push_back(Py::new(self.py, m).unwrap()),
-                        _ => {}
+                        (None, _) => self.markets.skip(mid.id.as_str()),
                     }
                 }
 
@@ -334,6 +665,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMcSeq<'a, 'py> {
 
         deserializer.deserialize_seq(MarketMcSeqVisitor {
             markets: self.markets,
+            pt: self.pt,
             py: self.py,
             config: self.config,
         })
@@ -343,6 +675,8 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMcSeq<'a, 'py> {
 struct MarketMc<'py> {
     id: MarketID,
     market: Option<PyRef<'py, Market>>,
+    img: bool,
+    pt: Option<DateTime>,
     py: Python<'py>,
     config: Config,
 }
@@ -371,6 +705,8 @@ impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
         struct MarketMcVisitor<'py> {
             id: MarketID,
             market: Option<PyRef<'py, Market>>,
+            img: bool,
+            pt: Option<DateTime>,
             py: Python<'py>,
             config: Config,
         }
@@ -386,38 +722,57 @@ impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
                 V: MapAccess<'de>,
             {
                 let mut total_volume: Option<f64> = None;
+                let mut conflated = false;
+                let mut traded = false;
+                let mut touched: Vec<SelectionID> = Vec::new();
                 let mut next_def: Option<Arc<MarketDefinition>> = None;
                 let mut next_runners: Option<Vec<Py<Runner>>> = None;
+                let mut diff: Option<DefinitionDiff> = None;
+                let mut changes: Vec<RunnerChange> = Vec::new();
+
+                // runners (and their ladders) from before an image are thrown away
+                let prev_runners: Option<&[Py<Runner>]> = match &self.market {
+                    Some(m) if !self.img => Some(m.runners.as_slice()),
+                    _ => None,
+                };
+                let mut index: Cow<RunnerIndex> = match &self.market {
+                    Some(m) if !self.img => Cow::Borrowed(&m.index),
+                    _ => Cow::Owned(RunnerIndex::default()),
+                };
 
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::MarketDefinition => {
                             let def = self.market.as_ref().map(|m| &*m.def);
-                            let runners = self
-                                .market
-                                .as_deref()
-                                .map(|m| &m.runners)
-                                .map(|rs| (**rs).as_slice());
 
-                            (next_def, next_runners) =
+                            let d;
+                            (next_def, next_runners, d) =
                                 map.next_value_seed(MarketDefinitionDeser {
                                     def,
-                                    runners,
+                                    runners: prev_runners,
                                     next_runners,
                                     py: self.py,
                                     config: self.config,
                                 })?;
+
+                            if d.moved && let Some(rs) = &next_runners {
+                                index = Cow::Owned(RunnerIndex::new(
+                                    rs.iter().map(|r| r.borrow(self.py).selection_id),
+                                ));
+                            }
+                            diff = Some(d);
                         }
                         Field::Rc => {
-                            let runners: Option<&[Py<Runner>]> =
-                                self.market.as_ref().map(|m| (**m.runners).as_ref());
-
-                            next_runners = map.next_value_seed(RunnerChangeSeq {
-                                runners,
-                                next: next_runners,
-                                py: self.py,
-                                config: self.config,
-                            })?;
+                            (next_runners, touched, traded) =
+                                map.next_value_seed(RunnerChangeSeq {
+                                    runners: prev_runners,
+                                    next: next_runners,
+                                    index: &mut index,
+                                    changes: &mut changes,
+                                    pt: self.pt,
+                                    py: self.py,
+                                    config: self.config,
+                                })?;
 
                             // if cumulative_runner_tv is on, then tv shouldnt be sent at a market level and will have
                             // to be derived from the sum of runner tv's. This happens when using the data provided
@@ -436,7 +791,7 @@ impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
                             }
                         }
                         Field::Con => {
-                            map.next_value::<IgnoredAny>()?;
+                            conflated = map.next_value::<Option<bool>>()?.contains(&true);
                         }
                         Field::Id => {
                             map.next_value::<IgnoredAny>()?;
@@ -450,19 +805,74 @@ impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
                     }
                 }
 
-                let m = if let Some(market) = self.market {
-                    Some(Market {
+                // runners the previous update left holding deltas that this one didn't touch
+                // are replaced with reset copies, sharing a single empty ladder
+                if let Some(market) = &self.market && let Some(prev) = prev_runners {
+                    let mut empty: Option<SyncObj<Arc<Vec<PriceSize>>>> = None;
+
+                    for sid in market.deltas.iter().filter(|sid| !touched.contains(sid)) {
+                        let rs = next_runners.get_or_insert_with(|| {
+                            prev.iter().map(|r| r.clone_ref(self.py)).collect()
+                        });
+
+                        if let Some(r) = index.get(*sid).and_then(|i| rs.get_mut(i)) {
+                            let reset = {
+                                let r = r.borrow(self.py);
+                                r.has_deltas().then(|| {
+                                    let empty = empty.get_or_insert_with(Default::default);
+                                    r.without_deltas(empty, self.py)
+                                })
+                            };
+                            if let Some(reset) = reset {
+                                *r = Py::new(self.py, reset).unwrap();
+                            }
+                        }
+                    }
+                }
+
+                let deltas = match &next_runners {
+                    Some(rs) => touched
+                        .into_iter()
+                        .filter(|sid| {
+                            index
+                                .get(*sid)
+                                .and_then(|i| rs.get(i))
+                                .map_or(false, |r| r.borrow(self.py).has_deltas())
+                        })
+                        .collect(),
+                    None => Vec::new(),
+                };
+
+                let mut m = if let Some(market) = &self.market {
+                    Market {
                         market_id: market.market_id.clone(),
                         publish_time: market.publish_time,
                         clk: market.clk.clone(),
-                        total_matched: total_volume.unwrap_or(market.total_matched),
-                        runners: next_runners
-                            .map(|rs| SyncObj::new(Arc::new(rs)))
-                            .unwrap_or_else(|| market.runners.clone()),
+                        total_matched: total_volume
+                            .unwrap_or(if self.img { 0.0 } else { market.total_matched }),
+                        runners: match next_runners {
+                            Some(rs) => SyncObj::new(Arc::new(rs)),
+                            None if self.img => Default::default(),
+                            None => market.runners.clone(),
+                        },
                         def: next_def.unwrap_or_else(|| market.def.clone()),
-                    })
-                } else {
-                    Some(Market {
+                        changes: None,
+                        change_type: None,
+                        conflated,
+                        stream_status: None,
+                        last_trade_time: self.pt.filter(|_| traded).or(market.last_trade_time),
+                        index: match index {
+                            Cow::Borrowed(_) => market.index.clone(),
+                            Cow::Owned(index) => Arc::new(index),
+                        },
+                        deltas,
+                        transitions: diff
+                            .as_ref()
+                            .map(|d| d.transitions.clone())
+                            .unwrap_or_default(),
+                    }
+                } else if let Some(def) = next_def {
+                    Market {
                         market_id: SyncObj::new(self.id),
                         publish_time: DateTime::new(0),
                         clk: Default::default(),
@@ -470,12 +880,43 @@ impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
                         runners: next_runners
                             .map(|rs| SyncObj::new(Arc::new(rs)))
                             .ok_or_else(|| Error::custom("creating market without selections"))?,
-                        def: next_def
-                            .ok_or_else(|| Error::custom("creating market without definition"))?,
-                    })
+                        def,
+                        changes: None,
+                        change_type: None,
+                        conflated,
+                        stream_status: None,
+                        last_trade_time: self.pt.filter(|_| traded),
+                        index: Arc::new(index.into_owned()),
+                        deltas,
+                        transitions: diff
+                            .as_ref()
+                            .map(|d| d.transitions.clone())
+                            .unwrap_or_default(),
+                    }
+                } else {
+                    // nothing to build the market from until a definition arrives
+                    return Ok(None);
                 };
 
-                Ok(m)
+                if self.config.changes {
+                    let changes = MarketChange::new(
+                        diff.as_ref(),
+                        self.market.is_none(),
+                        changes,
+                        &m.def,
+                        &m.runners,
+                        &m.index,
+                        self.market
+                            .as_ref()
+                            .map_or(0.0, |market| market.total_matched),
+                        m.total_matched,
+                        self.py,
+                    );
+
+                    m.changes = Some(Py::new(self.py, changes).unwrap());
+                }
+
+                Ok(Some(m))
             }
         }
 
@@ -486,6 +927,8 @@ impl<'de, 'py> DeserializeSeed<'de> for MarketMc<'py> {
             MarketMcVisitor {
                 id: self.id,
                 market: self.market,
+                img: self.img,
+                pt: self.pt,
                 py: self.py,
                 config: self.config,
             },
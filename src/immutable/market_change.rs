@@ -0,0 +1,141 @@
+use pyo3::prelude::*;
+
+use super::definition::{DefinitionDiff, MarketDefinition, RunnerDefChange, Transition};
+use super::runner::Runner;
+use crate::enums::{MarketStatus, SelectionStatus};
+use crate::ids::{RunnerIndex, SelectionID};
+use crate::price_size::PriceSize;
+
+#[pyclass(name = "MarketChange")]
+pub struct MarketChange {
+    #[pyo3(get)]
+    pub definition_fields: Vec<&'static str>,
+    #[pyo3(get)]
+    pub status: Option<MarketStatus>,
+    #[pyo3(get)]
+    pub total_matched: Option<f64>,
+    #[pyo3(get)]
+    pub runners: Vec<Py<RunnerChange>>,
+}
+
+#[derive(Default)]
+#[pyclass(name = "RunnerChange")]
+pub struct RunnerChange {
+    pub selection_id: SelectionID,
+    #[pyo3(get)]
+    pub status: Option<SelectionStatus>,
+    #[pyo3(get)]
+    pub last_price_traded: Option<f64>,
+    #[pyo3(get)]
+    pub total_matched: Option<f64>,
+    #[pyo3(get)]
+    pub available_to_back: Vec<PriceSize>,
+    #[pyo3(get)]
+    pub available_to_lay: Vec<PriceSize>,
+    #[pyo3(get)]
+    pub traded_volume: Vec<PriceSize>,
+    #[pyo3(get)]
+    pub back_stake_taken: Vec<PriceSize>,
+    #[pyo3(get)]
+    pub lay_liability_taken: Vec<PriceSize>,
+}
+
+#[pymethods]
+impl MarketChange {
+    #[getter(definition)]
+    fn get_definition(&self) -> bool {
+        !self.definition_fields.is_empty()
+    }
+}
+
+#[pymethods]
+impl RunnerChange {
+    #[getter(selection_id)]
+    fn get_selection_id(&self) -> u32 {
+        self.selection_id.id()
+    }
+    #[getter(handicap)]
+    fn get_handicap(&self) -> Option<f32> {
+        self.selection_id.handicap()
+    }
+}
+
+impl MarketChange {
+    // Builds the change set from what the deserializer saw in the mc message - the runner changes
+    // as they were sent, and the diff the definition made against the one before it. A market's
+    // first update has no definition before it, so all of it is new.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        diff: Option<&DefinitionDiff>,
+        first: bool,
+        mut changes: Vec<RunnerChange>,
+        def: &MarketDefinition,
+        runners: &[Py<Runner>],
+        index: &RunnerIndex,
+        prev_total_matched: f64,
+        total_matched: f64,
+        py: Python,
+    ) -> Self {
+        let mut status = first.then_some(def.status);
+        let definition_fields = match diff {
+            Some(_) if first => vec!["market_definition"],
+            Some(diff) => diff.fields.clone(),
+            None => Vec::new(),
+        };
+
+        let mut set_status = |sid: SelectionID, s: SelectionStatus| match changes
+            .iter_mut()
+            .find(|rc| rc.selection_id == sid)
+        {
+            Some(rc) => rc.status = Some(s),
+            None => changes.push(RunnerChange {
+                selection_id: sid,
+                status: Some(s),
+                ..Default::default()
+            }),
+        };
+
+        for t in diff.iter().flat_map(|diff| &diff.transitions) {
+            match *t {
+                Transition::Market(_, to) => status = Some(to),
+                Transition::Runner(sid, _, to) => set_status(sid, to),
+                Transition::InPlay | Transition::BspReconciled => {}
+            }
+        }
+        let added = diff
+            .iter()
+            .flat_map(|diff| &diff.runners)
+            .filter_map(|r| match r {
+                RunnerDefChange::Added(sid) => Some(*sid),
+                _ => None,
+            });
+        for sid in added {
+            if let Some(r) = index.get(sid).and_then(|i| runners.get(i)) {
+                set_status(sid, r.borrow(py).status);
+            }
+        }
+
+        Self {
+            definition_fields,
+            status,
+            total_matched: (prev_total_matched != total_matched).then_some(total_matched),
+            runners: changes
+                .into_iter()
+                .map(|rc| Py::new(py, rc).unwrap())
+                .collect(),
+        }
+    }
+}
+
+impl RunnerChange {
+    pub fn is_empty(&self) -> bool {
+        self.status.is_none()
+            && self.last_price_traded.is_none()
+            && self.total_matched.is_none()
+            && self.available_to_back.is_empty()
+            && self.available_to_lay.is_empty()
+            && self.traded_volume.is_empty()
+            && self.back_stake_taken.is_empty()
+            && self.lay_liability_taken.is_empty()
+    }
+}
@@ -2,6 +2,7 @@ pub mod container;
 pub mod definition;
 pub mod file;
 pub mod market;
+pub mod market_change;
 pub mod price_size;
 pub mod runner;
 pub mod runner_book_ex;
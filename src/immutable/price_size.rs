@@ -1,139 +1,116 @@
-use serde::de::{DeserializeSeed, Visitor};
-use serde::Deserializer;
 use std::cmp::Ordering;
-use std::fmt;
 
 use crate::price_size::PriceSize;
 
-pub struct ImmutablePriceSizeBackLadder<'a>(pub Option<&'a [PriceSize]>);
-impl<'de, 'a> DeserializeSeed<'de> for ImmutablePriceSizeBackLadder<'a> {
-    type Value = Vec<PriceSize>;
-
-    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
-        struct PSVisitor<'a>(Option<&'a [PriceSize]>);
-        impl<'de, 'a> Visitor<'de> for PSVisitor<'a> {
-            type Value = Vec<PriceSize>;
+fn with_prev(prev: Option<&[PriceSize]>) -> Vec<PriceSize> {
+    match prev {
+        Some(ps) => {
+            let mut v = Vec::with_capacity(std::cmp::min(ps.len() + 5, 350));
+            ps.clone_into(&mut v);
+            v
+        }
+        None => Vec::with_capacity(10),
+    }
+}
 
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("An array of PriceSize values")
+// applies a single streamed level to a ladder, a size of 0 removes the price. Returns the
+// position in the ladder the level changed, if any
+#[inline]
+fn apply<F: Fn(&PriceSize) -> Ordering>(
+    v: &mut Vec<PriceSize>,
+    ps1: PriceSize,
+    cmp_fn: F,
+) -> Option<usize> {
+    if ps1.size == 0.0 {
+        // removing price
+        match v.binary_search_by(cmp_fn) {
+            Ok(index) => {
+                v.remove(index);
+                Some(index)
             }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: serde::de::SeqAccess<'de>,
-            {
-                let mut v = match self.0 {
-                    Some(ps) => {
-                        let mut v = Vec::with_capacity(std::cmp::min(ps.len() + 5, 350));
-                        ps.clone_into(&mut v);
-                        v
-                    }
-                    None => Vec::with_capacity(10),
-                };
-
-                while let Some(ps1) = seq.next_element::<PriceSize>()? {
-                    let cmp_fn = |ps2: &PriceSize| {
-                        if ps1.price < ps2.price {
-                            Ordering::Greater
-                        } else if ps1.price > ps2.price {
-                            Ordering::Less
-                        } else {
-                            Ordering::Equal
-                        }
-                    };
-
-                    if ps1.size == 0.0 {
-                        // removing price
-                        match v.binary_search_by(cmp_fn) {
-                            Ok(index) => {
-                                v.remove(index);
-                            }
-                            Err(_err) => {}
-                        }
-                    } else {
-                        match v.binary_search_by(cmp_fn) {
-                            // updating price
-                            Ok(index) => unsafe { v.get_unchecked_mut(index) }.size = ps1.size,
-                            // inserting price
-                            Err(index) => v.insert(index, ps1),
-                        }
-                    }
-                }
-
-                Ok(v)
+            Err(_err) => None,
+        }
+    } else {
+        match v.binary_search_by(cmp_fn) {
+            // updating price
+            Ok(index) => {
+                let ps2 = unsafe { v.get_unchecked_mut(index) };
+                (ps2.size != ps1.size).then(|| {
+                    ps2.size = ps1.size;
+                    index
+                })
+            }
+            // inserting price
+            Err(index) => {
+                v.insert(index, ps1);
+                Some(index)
             }
         }
-
-        deserializer.deserialize_seq(PSVisitor(self.0))
     }
 }
 
-pub struct ImmutablePriceSizeLayLadder<'a>(pub Option<&'a [PriceSize]>);
-impl<'de, 'a> DeserializeSeed<'de> for ImmutablePriceSizeLayLadder<'a> {
-    type Value = Vec<PriceSize>;
+#[inline]
+fn apply_back(v: &mut Vec<PriceSize>, ps1: PriceSize) -> Option<usize> {
+    apply(v, ps1, |ps2: &PriceSize| {
+        if ps1.price < ps2.price {
+            Ordering::Greater
+        } else if ps1.price > ps2.price {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        }
+    })
+}
 
-    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
-        struct PSVisitor<'a>(Option<&'a [PriceSize]>);
-        impl<'de, 'a> Visitor<'de> for PSVisitor<'a> {
-            type Value = Vec<PriceSize>;
+#[inline]
+fn apply_lay(v: &mut Vec<PriceSize>, ps1: PriceSize) -> Option<usize> {
+    apply(v, ps1, |ps2: &PriceSize| {
+        if ps1.price < ps2.price {
+            Ordering::Less
+        } else if ps1.price > ps2.price {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    })
+}
 
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("An array of PriceSize values")
-            }
+// Runner changes are read before the runner they belong to is known, so the levels are buffered
+// as sent then applied over a copy of the previous ladder. Back ladders are sorted ascending.
+pub fn apply_back_ladder(prev: Option<&[PriceSize]>, levels: &[PriceSize]) -> Vec<PriceSize> {
+    apply_back_ladder_at(prev, levels).0
+}
 
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: serde::de::SeqAccess<'de>,
-            {
-                let mut v = match self.0 {
-                    Some(ps) => {
-                        let mut v = Vec::with_capacity(std::cmp::min(ps.len() + 5, 350));
-                        ps.clone_into(&mut v);
-                        v
-                    }
-                    None => Vec::with_capacity(10),
-                };
-
-                while let Some(ps1) = seq.next_element::<PriceSize>()? {
-                    let cmp_fn = |ps2: &PriceSize| {
-                        if ps1.price < ps2.price {
-                            Ordering::Less
-                        } else if ps1.price > ps2.price {
-                            Ordering::Greater
-                        } else {
-                            Ordering::Equal
-                        }
-                    };
-
-                    if ps1.size == 0.0 {
-                        // removing price
-                        match v.binary_search_by(cmp_fn) {
-                            Ok(index) => {
-                                v.remove(index);
-                            }
-                            Err(_err) => {}
-                        }
-                    } else {
-                        match v.binary_search_by(cmp_fn) {
-                            // updating price
-                            Ok(index) => unsafe { v.get_unchecked_mut(index) }.size = ps1.size,
-                            // inserting price
-                            Err(index) => v.insert(index, ps1),
-                        }
-                    }
-                }
-
-                Ok(v)
-            }
-        }
+// lay ladders are sorted descending
+pub fn apply_lay_ladder(prev: Option<&[PriceSize]>, levels: &[PriceSize]) -> Vec<PriceSize> {
+    apply_lay_ladder_at(prev, levels).0
+}
 
-        deserializer.deserialize_seq(PSVisitor(self.0))
-    }
+// Along with the ladder, the first position the levels changed, none if they left it as it was.
+// Nothing above that position moved, so the top of the ladder seen through a ladder_depth only
+// needs building again when it's inside the depth.
+pub fn apply_back_ladder_at(
+    prev: Option<&[PriceSize]>,
+    levels: &[PriceSize],
+) -> (Vec<PriceSize>, Option<usize>) {
+    let mut v = with_prev(prev);
+    let at = levels.iter().filter_map(|ps| apply_back(&mut v, *ps)).min();
+    (v, at)
+}
+
+pub fn apply_lay_ladder_at(
+    prev: Option<&[PriceSize]>,
+    levels: &[PriceSize],
+) -> (Vec<PriceSize>, Option<usize>) {
+    let mut v = with_prev(prev);
+    let at = levels.iter().filter_map(|ps| apply_lay(&mut v, *ps)).min();
+    (v, at)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde::Deserialize;
 
     #[test]
     fn test_back_ladder_deserialize() {
@@ -152,8 +129,8 @@ mod tests {
         let ps1: Vec<PriceSize> = Vec::new();
         let ans1: Vec<PriceSize> = Vec::new();
 
-        let ps2 = ImmutablePriceSizeBackLadder(Some(&ps1))
-            .deserialize(&mut deser)
+        let ps2 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_back_ladder(Some(&ps1), &l))
             .expect("failed to deserialize");
         let ans2 = vec![
             PriceSize::new(2.0, 5.0),
@@ -163,8 +140,8 @@ mod tests {
             PriceSize::new(6.0, 5.0),
         ];
 
-        let ps3 = ImmutablePriceSizeBackLadder(Some(&ps2))
-            .deserialize(&mut deser)
+        let ps3 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_back_ladder(Some(&ps2), &l))
             .expect("failed to deserialize");
         let ans3 = vec![
             PriceSize::new(1.25, 4.0),
@@ -179,8 +156,8 @@ mod tests {
             PriceSize::new(7.0, 4.0),
         ];
 
-        let ps4 = ImmutablePriceSizeBackLadder(Some(&ps3))
-            .deserialize(&mut deser)
+        let ps4 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_back_ladder(Some(&ps3), &l))
             .expect("failed to deserialize");
         let ans4 = vec![
             PriceSize::new(2.0, 5.0),
@@ -191,8 +168,8 @@ mod tests {
             PriceSize::new(6.0, 5.0),
         ];
 
-        let ps5 = ImmutablePriceSizeBackLadder(Some(&ps4))
-            .deserialize(&mut deser)
+        let ps5 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_back_ladder(Some(&ps4), &l))
             .expect("failed to deserialize");
         let ans5 = vec![
             PriceSize::new(2.0, 1.0),
@@ -202,13 +179,13 @@ mod tests {
             PriceSize::new(6.0, 5.0),
         ];
 
-        let ps6 = ImmutablePriceSizeBackLadder(Some(&ps5))
-            .deserialize(&mut deser)
+        let ps6 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_back_ladder(Some(&ps5), &l))
             .expect("failed to deserialize");
         let ans6 = Vec::new();
 
-        let ps7 = ImmutablePriceSizeBackLadder(Some(&ps6))
-            .deserialize(&mut deser)
+        let ps7 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_back_ladder(Some(&ps6), &l))
             .expect("failed to deserialize");
         let ans7 = vec![
             PriceSize::new(1.1, 6.0),
@@ -219,8 +196,8 @@ mod tests {
             PriceSize::new(1.6, 1.0),
         ];
 
-        let ps8 = ImmutablePriceSizeBackLadder(Some(&ps7))
-            .deserialize(&mut deser)
+        let ps8 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_back_ladder(Some(&ps7), &l))
             .expect("failed to deserialize");
         let ans8 = vec![
             PriceSize::new(1.1, 6.0),
@@ -237,8 +214,8 @@ mod tests {
             PriceSize::new(2.2, 2.0),
         ];
 
-        let ps9 = ImmutablePriceSizeBackLadder(Some(&ps8))
-            .deserialize(&mut deser)
+        let ps9 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_back_ladder(Some(&ps8), &l))
             .expect("failed to deserialize");
         let ans9 = vec![
             PriceSize::new(1.1, 6.0),
@@ -295,8 +272,8 @@ mod tests {
         let ps1: Vec<PriceSize> = Vec::new();
         let ans1: Vec<PriceSize> = Vec::new();
 
-        let ps2 = ImmutablePriceSizeLayLadder(Some(&ps1))
-            .deserialize(&mut deser)
+        let ps2 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_lay_ladder(Some(&ps1), &l))
             .expect("failed to deserialize");
         let ans2 = vec![
             PriceSize::new(6.0, 5.0),
@@ -306,8 +283,8 @@ mod tests {
             PriceSize::new(2.0, 5.0),
         ];
 
-        let ps3 = ImmutablePriceSizeLayLadder(Some(&ps2))
-            .deserialize(&mut deser)
+        let ps3 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_lay_ladder(Some(&ps2), &l))
             .expect("failed to deserialize");
         let ans3 = vec![
             PriceSize::new(7.0, 4.0),
@@ -322,8 +299,8 @@ mod tests {
             PriceSize::new(1.25, 4.0),
         ];
 
-        let ps4 = ImmutablePriceSizeLayLadder(Some(&ps3))
-            .deserialize(&mut deser)
+        let ps4 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_lay_ladder(Some(&ps3), &l))
             .expect("failed to deserialize");
         let ans4 = vec![
             PriceSize::new(6.0, 5.0),
@@ -334,8 +311,8 @@ mod tests {
             PriceSize::new(2.0, 5.0),
         ];
 
-        let ps5 = ImmutablePriceSizeLayLadder(Some(&ps4))
-            .deserialize(&mut deser)
+        let ps5 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_lay_ladder(Some(&ps4), &l))
             .expect("failed to deserialize");
         let ans5 = vec![
             PriceSize::new(6.0, 5.0),
@@ -345,13 +322,13 @@ mod tests {
             PriceSize::new(2.0, 1.0),
         ];
 
-        let ps6 = ImmutablePriceSizeLayLadder(Some(&ps5))
-            .deserialize(&mut deser)
+        let ps6 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_lay_ladder(Some(&ps5), &l))
             .expect("failed to deserialize");
         let ans6 = Vec::new();
 
-        let ps7 = ImmutablePriceSizeLayLadder(Some(&ps6))
-            .deserialize(&mut deser)
+        let ps7 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_lay_ladder(Some(&ps6), &l))
             .expect("failed to deserialize");
         let ans7 = vec![
             PriceSize::new(1.6, 1.0),
@@ -362,8 +339,8 @@ mod tests {
             PriceSize::new(1.1, 6.0),
         ];
 
-        let ps8 = ImmutablePriceSizeLayLadder(Some(&ps7))
-            .deserialize(&mut deser)
+        let ps8 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_lay_ladder(Some(&ps7), &l))
             .expect("failed to deserialize");
         let ans8 = vec![
             PriceSize::new(2.2, 2.0),
@@ -380,8 +357,8 @@ mod tests {
             PriceSize::new(1.1, 6.0),
         ];
 
-        let ps9 = ImmutablePriceSizeLayLadder(Some(&ps8))
-            .deserialize(&mut deser)
+        let ps9 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_lay_ladder(Some(&ps8), &l))
             .expect("failed to deserialize");
         let ans9 = vec![
             PriceSize::new(3.1, 3.0),
@@ -407,8 +384,8 @@ mod tests {
             PriceSize::new(1.1, 6.0),
         ];
 
-        let ps10 = ImmutablePriceSizeLayLadder(Some(&ps9))
-            .deserialize(&mut deser)
+        let ps10 = Vec::<PriceSize>::deserialize(&mut deser)
+            .map(|l| apply_lay_ladder(Some(&ps9), &l))
             .expect("failed to deserialize");
         let ans10 = vec![
             PriceSize::new(4.0, 5.0),
@@ -446,4 +423,27 @@ mod tests {
         assert_eq!(ps9, ans9);
         assert_eq!(ps10, ans10);
     }
+
+    #[test]
+    fn test_ladder_changed_at() {
+        let prev = vec![
+            PriceSize::new(1.5, 1.0),
+            PriceSize::new(1.6, 2.0),
+            PriceSize::new(1.7, 3.0),
+        ];
+
+        let levels = [PriceSize::new(1.8, 1.0), PriceSize::new(1.6, 0.0)];
+        let (ps, at) = apply_back_ladder_at(Some(&prev), &levels);
+        assert_eq!(ps[1], PriceSize::new(1.7, 3.0));
+        assert_eq!(at, Some(1));
+
+        // a level re-sent with the same size, or removing a price not in the ladder, moves nothing
+        let levels = [PriceSize::new(1.7, 3.0), PriceSize::new(1.9, 0.0)];
+        assert_eq!(apply_back_ladder_at(Some(&prev), &levels).1, None);
+
+        let levels = [PriceSize::new(1.5, 4.0)];
+        assert_eq!(apply_lay_ladder_at(Some(&prev), &[]).1, None);
+        let prev: Vec<_> = prev.into_iter().rev().collect();
+        assert_eq!(apply_lay_ladder_at(Some(&prev), &levels).1, Some(2));
+    }
 }
@@ -3,17 +3,22 @@ use pyo3::prelude::*;
 use pyo3::types::PyList;
 use serde::de::{DeserializeSeed, Error, IgnoredAny, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
-use serde_json::value::RawValue;
+use std::borrow::Cow;
 use std::sync::Arc;
 
 use super::container::SyncObj;
+use super::market_change::RunnerChange;
 use super::runner_book_ex::RunnerBookEX;
 use super::runner_book_sp::RunnerBookSP;
+use crate::analytics;
 use crate::config::Config;
-use crate::datetime::DateTimeString;
+use crate::datetime::{DateTime, DateTimeString};
 use crate::enums::SelectionStatus;
-use crate::ids::SelectionID;
-use crate::immutable::price_size::{ImmutablePriceSizeBackLadder, ImmutablePriceSizeLayLadder};
+use crate::ids::{RunnerIndex, SelectionID};
+use crate::immutable::price_size::{
+    apply_back_ladder, apply_back_ladder_at, apply_lay_ladder, apply_lay_ladder_at,
+};
+use crate::pickle::{self, Decoder, Encoder, Kind, Pickle};
 use crate::price_size::{F64OrStr, PriceSize};
 use crate::py_rep::PyRep;
 
@@ -38,10 +43,102 @@ pub struct Runner {
     pub sort_priority: u16,
     #[pyo3(get)]
     pub removal_date: Option<SyncObj<DateTimeString>>,
+    #[pyo3(get)]
+    pub traded_volume_delta: SyncObj<Arc<Vec<PriceSize>>>,
+    #[pyo3(get)]
+    pub total_matched_delta: f64,
+    #[pyo3(get)]
+    pub is_crossed: bool,
+    // publish time of the last update to change the ex ladders
+    #[pyo3(get)]
+    pub last_update_time: Option<DateTime>,
+    // a definition has set the status, runners an rc creates start off without one
+    pub defined: bool,
+}
+
+impl Runner {
+    // matched in the update that last changed it
+    pub fn traded(&self) -> bool {
+        self.traded_volume_delta.iter().any(|ps| ps.size > 0.0)
+    }
+
+    pub fn has_deltas(&self) -> bool {
+        !self.traded_volume_delta.is_empty() || self.total_matched_delta != 0.0
+    }
+
+    // copy of the runner with the per update values reset, sharing everything else
+    pub fn without_deltas(&self, empty: &SyncObj<Arc<Vec<PriceSize>>>, py: Python) -> Self {
+        Self {
+            selection_id: self.selection_id,
+            status: self.status,
+            name: self.name.clone(),
+            last_price_traded: self.last_price_traded,
+            total_matched: self.total_matched,
+            adjustment_factor: self.adjustment_factor,
+            ex: self.ex.clone_ref(py),
+            sp: self.sp.clone_ref(py),
+            sort_priority: self.sort_priority,
+            removal_date: self.removal_date.clone(),
+            traded_volume_delta: empty.clone(),
+            total_matched_delta: 0.0,
+            is_crossed: self.is_crossed,
+            last_update_time: self.last_update_time,
+            defined: self.defined,
+        }
+    }
+}
+
+impl Pickle for Runner {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.selection_id.encode(e, py);
+        self.status.encode(e, py);
+        self.name.encode(e, py);
+        self.last_price_traded.encode(e, py);
+        self.total_matched.encode(e, py);
+        self.adjustment_factor.encode(e, py);
+        self.ex.encode(e, py);
+        self.sp.encode(e, py);
+        self.sort_priority.encode(e, py);
+        self.removal_date.encode(e, py);
+        self.traded_volume_delta.encode(e, py);
+        self.total_matched_delta.encode(e, py);
+        self.is_crossed.encode(e, py);
+        self.last_update_time.encode(e, py);
+        self.defined.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            selection_id: Pickle::decode(d, py)?,
+            status: Pickle::decode(d, py)?,
+            name: Pickle::decode(d, py)?,
+            last_price_traded: Pickle::decode(d, py)?,
+            total_matched: Pickle::decode(d, py)?,
+            adjustment_factor: Pickle::decode(d, py)?,
+            ex: Pickle::decode(d, py)?,
+            sp: Pickle::decode(d, py)?,
+            sort_priority: Pickle::decode(d, py)?,
+            removal_date: Pickle::decode(d, py)?,
+            traded_volume_delta: Pickle::decode(d, py)?,
+            total_matched_delta: Pickle::decode(d, py)?,
+            is_crossed: Pickle::decode(d, py)?,
+            last_update_time: Pickle::decode(d, py)?,
+            defined: Pickle::decode(d, py)?,
+        })
+    }
 }
 
 #[pymethods]
 impl Runner {
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        pickle::reduce(Kind::Runner, self, py)
+    }
+    // immutable, so copies can share the same object
+    fn __copy__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+    fn __deepcopy__(slf: PyRef<Self>, _memo: &PyAny) -> PyRef<Self> {
+        slf
+    }
     #[getter(selection_id)]
     fn get_selection_id(&self) -> u32 {
         self.selection_id.id()
@@ -50,6 +147,12 @@ impl Runner {
     fn get_handicap(&self) -> Option<f32> {
         self.selection_id.handicap()
     }
+    #[getter(implied_probability)]
+    fn get_implied_probability(&self) -> Option<f64> {
+        self.last_price_traded
+            .filter(|ltp| *ltp > 0.0)
+            .map(|ltp| 1.0 / ltp)
+    }
 }
 
 
@@ -59,28 +162,38 @@ impl PyRep for Vec<Py<Runner>> {
     }
 }
 
-pub struct RunnerChangeSeq<'a, 'py> {
+// index finds runners in next (or runners when there is no next yet), it's only copied if a
+// runner has to be added to it. With the changes option each runner change is also added to
+// changes as it was sent.
+pub struct RunnerChangeSeq<'a, 'i, 'py> {
     pub runners: Option<&'a [Py<Runner>]>,
     pub next: Option<Vec<Py<Runner>>>,
+    pub index: &'a mut Cow<'i, RunnerIndex>,
+    pub changes: &'a mut Vec<RunnerChange>,
+    pub pt: Option<DateTime>,
     pub py: Python<'py>,
     pub config: Config,
 }
 
-impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeSeq<'a, 'py> {
-    type Value = Option<Vec<Py<Runner>>>;
+// the runners after the change, the ids of the ones it updated, and whether any of them traded
+impl<'de, 'a, 'i, 'py> DeserializeSeed<'de> for RunnerChangeSeq<'a, 'i, 'py> {
+    type Value = (Option<Vec<Py<Runner>>>, Vec<SelectionID>, bool);
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct RunnerSeqVisitor<'a, 'py> {
+        struct RunnerSeqVisitor<'a, 'i, 'py> {
             runners: Option<&'a [Py<Runner>]>,
             next: Option<Vec<Py<Runner>>>,
+            index: &'a mut Cow<'i, RunnerIndex>,
+            changes: &'a mut Vec<RunnerChange>,
+            pt: Option<DateTime>,
             py: Python<'py>,
             config: Config,
         }
-        impl<'de, 'a, 'py> Visitor<'de> for RunnerSeqVisitor<'a, 'py> {
-            type Value = Option<Vec<Py<Runner>>>;
+        impl<'de, 'a, 'i, 'py> Visitor<'de> for RunnerSeqVisitor<'a, 'i, 'py> {
+            type Value = (Option<Vec<Py<Runner>>>, Vec<SelectionID>, bool);
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("")
@@ -90,12 +203,6 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeSeq<'a, 'py> {
             where
                 A: serde::de::SeqAccess<'de>,
             {
-                #[derive(Deserialize)]
-                struct RunnerWithID {
-                    id: u32,
-                    hc: Option<f32>,
-                }
-
                 let mut next_runners = match self.next {
                     Some(n) => n,
                     None => self
@@ -103,69 +210,66 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeSeq<'a, 'py> {
                         .map(|v| v.iter().map(|r| r.clone_ref(self.py)).collect::<Vec<_>>())
                         .unwrap_or_else(|| Vec::with_capacity(10)),
                 };
+                let mut touched = Vec::new();
+                let mut traded = false;
 
-                while let Some(raw) = seq.next_element::<&RawValue>()? {
-                    let mut deser = serde_json::Deserializer::from_str(raw.get());
-                    let parts: RunnerWithID =
-                        serde_json::from_str(raw.get()).map_err(Error::custom)?;
-                    let sid = SelectionID::from((parts.id, parts.hc));
+                while let Some(upt) = seq.next_element_seed(RunnerChangeDeser {
+                    config: self.config,
+                })? {
+                    touched.push(upt.id);
+                    let mut change = self.config.changes.then(|| upt.change());
+                    let sets_tv = upt.sets_total_matched(self.config);
 
-                    let index = next_runners
-                        .iter()
-                        .map(|r| r.borrow(self.py))
-                        .position(|r| r.selection_id == sid);
-
-                    match index {
+                    let runner = match self.index.get(upt.id) {
                         Some(index) => {
-                            let runner = RunnerChangeDeser {
-                                id: sid,
-                                runner: Some(unsafe {
-                                    next_runners.get_unchecked(index).borrow(self.py)
-                                }),
-                                py: self.py,
-                                config: self.config,
-                            }
-                            .deserialize(&mut deser)
-                            .map_err(Error::custom)?;
-
+                            let runner = upt.update(
+                                next_runners[index].borrow(self.py),
+                                self.pt,
+                                self.config,
+                                self.py,
+                            );
                             next_runners[index] = Py::new(self.py, runner).unwrap();
+                            &next_runners[index]
                         }
                         None => {
-                            let runner = RunnerChangeDeser {
-                                id: sid,
-                                runner: None,
-                                py: self.py,
-                                config: self.config,
-                            }
-                            .deserialize(&mut deser)
-                            .map_err(Error::custom)?;
-
+                            self.index.to_mut().push(upt.id);
+                            let runner = upt.create(self.pt, self.config, self.py);
                             next_runners.push(Py::new(self.py, runner).unwrap());
+                            next_runners.last().unwrap()
                         }
+                    };
+
+                    let runner = runner.borrow(self.py);
+                    traded |= runner.traded();
+                    if let Some(rc) = &mut change && sets_tv {
+                        rc.total_matched = Some(runner.total_matched);
                     }
+                    self.changes.extend(change.filter(|rc| !rc.is_empty()));
                 }
 
-                Ok(Some(next_runners))
+                Ok((Some(next_runners), touched, traded))
             }
         }
 
         deserializer.deserialize_seq(RunnerSeqVisitor {
             runners: self.runners,
             next: self.next,
+            index: self.index,
+            changes: self.changes,
+            pt: self.pt,
             py: self.py,
             config: self.config,
         })
     }
 }
 
-struct RunnerChangeDeser<'py> {
-    id: SelectionID,
-    runner: Option<PyRef<'py, Runner>>,
-    py: Python<'py>,
+// Reads a runner change in a single pass, the id and hc can appear anywhere in the object so the
+// ladder levels are buffered and only applied once the runner they belong to has been found
+struct RunnerChangeDeser {
     config: Config,
 }
-impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeDeser<'py> {
-    type Value = Runner;
+impl<'de> DeserializeSeed<'de> for RunnerChangeDeser {
+    type Value = RunnerChangeUpdate;
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
@@ -187,14 +291,11 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeDeser<'py> {
             Hc,
         }
 
-        struct RunnerChangeVisitor<'py> {
-            id: SelectionID,
-            runner: Option<PyRef<'py, Runner>>,
-            py: Python<'py>,
+        struct RunnerChangeVisitor {
             config: Config,
         }
-        impl<'de, 'py> Visitor<'de> for RunnerChangeVisitor<'py> {
-            type Value = Runner;
+        impl<'de> Visitor<'de> for RunnerChangeVisitor {
+            type Value = RunnerChangeUpdate;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("")
@@ -204,48 +305,32 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeDeser<'py> {
             where
                 V: MapAccess<'de>,
             {
-                let mut upt = RunnerChangeUpdate::new(self.id);
+                let mut id: Option<u32> = None;
+                let mut hc: Option<f32> = None;
+                let mut upt = RunnerChangeUpdate::default();
 
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::Id => {
-                            map.next_value::<IgnoredAny>()?;
+                            id = Some(map.next_value::<u32>()?);
+                        }
+                        Field::Hc => {
+                            hc = map.next_value::<Option<f32>>()?;
                         }
                         Field::Atb => {
-                            let ex = self.runner.as_ref().map(|r| r.ex.borrow(self.py));
-                            let atb = ex.as_ref().map(|ex| ex.available_to_back.as_slice());
-
-                            upt.atb = Some(map.next_value_seed(ImmutablePriceSizeLayLadder(atb))?);
+                            upt.atb = Some(map.next_value::<Vec<PriceSize>>()?);
                         }
                         Field::Atl => {
-                            let ex = self.runner.as_ref().map(|r| r.ex.borrow(self.py));
-                            let atl = ex.as_ref().map(|ex| ex.available_to_lay.as_slice());
-
-                            upt.atl = Some(map.next_value_seed(ImmutablePriceSizeBackLadder(atl))?);
+                            upt.atl = Some(map.next_value::<Vec<PriceSize>>()?);
                         }
                         Field::Trd => {
-                            let ex = self.runner.as_ref().map(|r| r.ex.borrow(self.py));
-                            let trd = ex.as_ref().map(|ex| ex.traded_volume.as_slice());
-
-                            let l = map.next_value_seed(ImmutablePriceSizeBackLadder(trd))?;
-
-                            if self.config.cumulative_runner_tv {
-                                upt.tv = Some(l.iter().map(|ps| ps.size).sum());
-                            }
-
-                            upt.trd = Some(l);
+                            upt.trd = Some(map.next_value::<Vec<PriceSize>>()?);
                         }
                         Field::Spb => {
-                            let sp = self.runner.as_ref().map(|r| r.sp.borrow(self.py));
-                            let spl = sp.as_ref().map(|sp| sp.lay_liability_taken.as_slice());
-
-                            upt.spl = Some(map.next_value_seed(ImmutablePriceSizeLayLadder(spl))?);
+                            upt.spl = Some(map.next_value::<Vec<PriceSize>>()?);
                         }
                         Field::Spl => {
-                            let sp = self.runner.as_ref().map(|r| r.sp.borrow(self.py));
-                            let spb = sp.as_ref().map(|sp| sp.back_stake_taken.as_slice());
-
-                            upt.spb = Some(map.next_value_seed(ImmutablePriceSizeBackLadder(spb))?);
+                            upt.spb = Some(map.next_value::<Vec<PriceSize>>()?);
                         }
                         Field::Spn => {
                             upt.spn = Some(map.next_value::<F64OrStr>()?.into());
@@ -256,9 +341,6 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeDeser<'py> {
                         Field::Ltp => {
                             upt.ltp = Some(map.next_value::<F64OrStr>()?.into());
                         }
-                        Field::Hc => {
-                            map.next_value::<IgnoredAny>()?;
-                        }
                         // The betfair historic data files differ from the stream here, they send tv deltas
                         // that need to be accumulated, whereas the stream sends the value itself.
                         Field::Tv => {
@@ -272,12 +354,10 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeDeser<'py> {
                     };
                 }
 
-                let pr = match self.runner {
-                    Some(r) => upt.update(r, self.py),
-                    None => upt.create(self.py),
-                };
+                let id = id.ok_or_else(|| Error::missing_field("id"))?;
+                upt.id = SelectionID::from((id, hc));
 
-                Ok(pr)
+                Ok(upt)
             }
         }
 
@@ -288,15 +368,14 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeDeser<'py> {
             "RunnerChange",
             FIELDS,
             RunnerChangeVisitor {
-                id: self.id,
-                runner: self.runner,
-                py: self.py,
                 config: self.config,
             },
         )
     }
 }
 
+// until apply is called the ladders hold the levels exactly as they were sent
+#[derive(Default)]
 struct RunnerChangeUpdate {
     id: SelectionID,
     atb: Option<Vec<PriceSize>>,
@@ -308,31 +387,87 @@ struct RunnerChangeUpdate {
     spf: Option<f64>,
     tv: Option<f64>,
     ltp: Option<f64>,
+    // after apply, the first position of the atb/atl ladders the levels changed
+    atb_at: Option<usize>,
+    atl_at: Option<usize>,
 }
 
 impl RunnerChangeUpdate {
-    fn new(id: SelectionID) -> Self {
-        Self {
-            id, 
-            atb: Default::default(),
-            atl: Default::default(),
-            trd: Default::default(),
-            spb: Default::default(),
-            spl: Default::default(),
-            spn: Default::default(),
-            spf: Default::default(),
-            tv:  Default::default(),
-            ltp: Default::default(),
+    fn ex_changed(&self) -> bool {
+        self.atb.is_some() || self.atl.is_some() || self.trd.is_some()
+    }
+
+    // sent a tv, or a traded ladder the total matched is summed from
+    fn sets_total_matched(&self, config: Config) -> bool {
+        self.tv.is_some() || (config.cumulative_runner_tv && self.trd.is_some())
+    }
+
+    // the change as it was sent, taken before apply merges the levels into the ladders
+    fn change(&self) -> RunnerChange {
+        let levels = |l: &Option<Vec<PriceSize>>| l.clone().unwrap_or_default();
+
+        RunnerChange {
+            selection_id: self.id,
+            status: None,
+            last_price_traded: self.ltp,
+            total_matched: None,
+            available_to_back: levels(&self.atb),
+            available_to_lay: levels(&self.atl),
+            traded_volume: levels(&self.trd),
+            back_stake_taken: levels(&self.spb),
+            lay_liability_taken: levels(&self.spl),
+        }
+    }
+
+    // merges the buffered levels into the previous ladders of the runner
+    fn apply(&mut self, runner: Option<&Runner>, config: Config, py: Python) {
+        fn merge(
+            levels: &mut Option<Vec<PriceSize>>,
+            prev: Option<&SyncObj<Arc<Vec<PriceSize>>>>,
+            f: fn(Option<&[PriceSize]>, &[PriceSize]) -> Vec<PriceSize>,
+        ) {
+            if let Some(l) = levels {
+                *l = f(prev.map(|p| p.as_slice()), l);
+            }
+        }
+
+        let ex = runner.map(|r| r.ex.borrow(py));
+        let sp = runner.map(|r| r.sp.borrow(py));
+
+        if let Some(l) = &mut self.atb {
+            let prev = ex.as_ref().map(|ex| ex.available_to_back.as_slice());
+            (*l, self.atb_at) = apply_lay_ladder_at(prev, l);
+        }
+        if let Some(l) = &mut self.atl {
+            let prev = ex.as_ref().map(|ex| ex.available_to_lay.as_slice());
+            (*l, self.atl_at) = apply_back_ladder_at(prev, l);
+        }
+        merge(&mut self.trd, ex.as_ref().map(|ex| &ex.traded_volume), apply_back_ladder);
+        merge(&mut self.spl, sp.as_ref().map(|sp| &sp.lay_liability_taken), apply_lay_ladder);
+        merge(&mut self.spb, sp.as_ref().map(|sp| &sp.back_stake_taken), apply_back_ladder);
+
+        if config.cumulative_runner_tv && let Some(trd) = &self.trd {
+            self.tv = Some(trd.iter().map(|ps| ps.size).sum());
         }
     }
 
-    fn create(self, py: Python) -> Runner {
+    fn create(mut self, pt: Option<DateTime>, config: Config, py: Python) -> Runner {
+        self.apply(None, config, py);
+        let trd_delta = self.trd.clone().unwrap_or_default();
+        let changed = self.ex_changed();
+        let atb = self.atb.unwrap_or_default();
+        let atl = self.atl.unwrap_or_default();
+        let is_crossed = analytics::is_crossed(&atb, &atl);
+
         let ex = Py::new(
             py,
             RunnerBookEX {
-                available_to_back: SyncObj::new(Arc::new(self.atb.unwrap_or_default())),
-                available_to_lay: SyncObj::new(Arc::new(self.atl.unwrap_or_default())),
+                available_to_back: SyncObj::new(Arc::new(atb)),
+                available_to_lay: SyncObj::new(Arc::new(atl)),
                 traded_volume: SyncObj::new(Arc::new(self.trd.unwrap_or_default())),
+                ladder_depth: config.ladder_depth,
+                back_view: Default::default(),
+                lay_view: Default::default(),
             },
         )
         .unwrap();
@@ -360,27 +495,64 @@ impl RunnerChangeUpdate {
             sp,
             sort_priority: 0,
             removal_date: None,
+            traded_volume_delta: SyncObj::new(Arc::new(trd_delta)),
+            total_matched_delta: self.tv.unwrap_or_default(),
+            is_crossed,
+            last_update_time: pt.filter(|_| changed),
+            defined: false,
         }
     }
 
-    fn update(self, runner: PyRef<Runner>, py: Python) -> Runner {
-        let ex = if self.atb.is_some() || self.atl.is_some() || self.trd.is_some() {
+    fn update(
+        mut self,
+        runner: PyRef<Runner>,
+        pt: Option<DateTime>,
+        config: Config,
+        py: Python,
+    ) -> Runner {
+        self.apply(Some(&*runner), config, py);
+        let trd_delta = match &self.trd {
+            Some(trd) => analytics::ladder_delta(&runner.ex.borrow(py).traded_volume, trd),
+            None => Vec::new(),
+        };
+        let tv_delta = self.tv.map_or(0.0, |tv| tv - runner.total_matched);
+
+        let changed = self.ex_changed();
+        let mut is_crossed = runner.is_crossed;
+
+        let ex = if changed {
             let ex = runner.ex.borrow(py);
+            is_crossed = analytics::is_crossed(
+                self.atb.as_deref().unwrap_or(ex.available_to_back.as_slice()),
+                self.atl.as_deref().unwrap_or(ex.available_to_lay.as_slice()),
+            );
+
+            // a side the levels left as it was keeps its ladder, and its truncated view while
+            // nothing inside the depth changed
+            let back_view = ex.carry_view(&ex.back_view, self.atb_at);
+            let lay_view = ex.carry_view(&ex.lay_view, self.atl_at);
+            let (atb_at, atl_at) = (self.atb_at, self.atl_at);
+
             Py::new(
                 py,
                 RunnerBookEX {
                     available_to_back: self
                         .atb
+                        .filter(|_| atb_at.is_some())
                         .map(|atb| SyncObj::new(Arc::new(atb)))
                         .unwrap_or_else(|| ex.available_to_back.clone()),
                     available_to_lay: self
                         .atl
+                        .filter(|_| atl_at.is_some())
                         .map(|atl| SyncObj::new(Arc::new(atl)))
                         .unwrap_or_else(|| ex.available_to_lay.clone()),
                     traded_volume: self
                         .trd
                         .map(|trd| SyncObj::new(Arc::new(trd)))
                         .unwrap_or_else(|| ex.traded_volume.clone()),
+                    ladder_depth: config.ladder_depth,
+                    back_view,
+                    lay_view,
                 },
             )
             .unwrap()
@@ -424,6 +596,11 @@ impl RunnerChangeUpdate {
             sp,
             sort_priority: runner.sort_priority,
             removal_date: runner.removal_date.clone(),
+            traded_volume_delta: SyncObj::new(Arc::new(trd_delta)),
+            total_matched_delta: tv_delta,
+            is_crossed,
+            last_update_time: pt.filter(|_| changed).or(runner.last_update_time),
+            defined: runner.defined,
         }
     }
 }
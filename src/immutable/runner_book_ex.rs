@@ -1,8 +1,13 @@
 use pyo3::prelude::*;
+use std::lazy::OnceCell;
 use std::sync::Arc;
 
+use crate::analytics;
+use crate::array;
 use crate::immutable::container::SyncObj;
+use crate::pickle::{self, Decoder, Encoder, Kind, Pickle};
 use crate::price_size::PriceSize;
+use crate::py_rep::PyRep;
 
 #[derive(Clone, Default)]
 #[pyclass]
@@ -10,20 +15,126 @@ pub struct RunnerBookEX {
     pub available_to_back: SyncObj<Arc<Vec<PriceSize>>>,
     pub available_to_lay: SyncObj<Arc<Vec<PriceSize>>>,
     pub traded_volume: SyncObj<Arc<Vec<PriceSize>>>,
+    // the number of atb/atl levels visible from python, the full ladder is kept
+    pub ladder_depth: Option<usize>,
+    // the truncated atb/atl python lists when ladder_depth is set, kept apart from the ladders'
+    // own objects which always hold every level
+    pub back_view: OnceCell<PyObject>,
+    pub lay_view: OnceCell<PyObject>,
+}
+
+impl RunnerBookEX {
+    // The truncated view of a side is carried into the next book when the update changed nothing
+    // inside the depth, `at` being the first position of the ladder it changed.
+    pub fn carry_view(&self, view: &OnceCell<PyObject>, at: Option<usize>) -> OnceCell<PyObject> {
+        match (self.ladder_depth, at) {
+            (Some(depth), Some(at)) if at < depth => OnceCell::new(),
+            _ => view.clone(),
+        }
+    }
+
+    fn ladder_rep(
+        &self,
+        ladder: &SyncObj<Arc<Vec<PriceSize>>>,
+        view: &OnceCell<PyObject>,
+        py: Python,
+    ) -> PyObject {
+        match self.ladder_depth {
+            Some(depth) => view
+                .get_or_init(|| analytics::top(ladder, Some(depth)).py_rep(py))
+                .clone_ref(py),
+            None => ladder.to_object(py),
+        }
+    }
+}
+
+impl Pickle for RunnerBookEX {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.available_to_back.encode(e, py);
+        self.available_to_lay.encode(e, py);
+        self.traded_volume.encode(e, py);
+        self.ladder_depth.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            available_to_back: Pickle::decode(d, py)?,
+            available_to_lay: Pickle::decode(d, py)?,
+            traded_volume: Pickle::decode(d, py)?,
+            ladder_depth: Pickle::decode(d, py)?,
+            back_view: OnceCell::new(),
+            lay_view: OnceCell::new(),
+        })
+    }
 }
 
 #[pymethods]
 impl RunnerBookEX {
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        pickle::reduce(Kind::RunnerBookEX, self, py)
+    }
+    // immutable, so copies can share the same object
+    fn __copy__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+    fn __deepcopy__(slf: PyRef<Self>, _memo: &PyAny) -> PyRef<Self> {
+        slf
+    }
     #[getter(available_to_back)]
     fn get_available_to_back(&self, py: Python) -> PyObject {
-        self.available_to_back.to_object(py)
+        self.ladder_rep(&self.available_to_back, &self.back_view, py)
     }
     #[getter(available_to_lay)]
     fn get_available_to_lay(&self, py: Python) -> PyObject {
-        self.available_to_lay.to_object(py)
+        self.ladder_rep(&self.available_to_lay, &self.lay_view, py)
     }
     #[getter(traded_volume)]
     fn get_traded_volume(&self, py: Python) -> PyObject {
         self.traded_volume.to_object(py)
     }
+    #[getter(available_to_back_array)]
+    fn get_available_to_back_array(&self, py: Python) -> PyResult<PyObject> {
+        array::ladder(
+            analytics::top(&self.available_to_back, self.ladder_depth),
+            py,
+        )
+    }
+    #[getter(available_to_lay_array)]
+    fn get_available_to_lay_array(&self, py: Python) -> PyResult<PyObject> {
+        array::ladder(
+            analytics::top(&self.available_to_lay, self.ladder_depth),
+            py,
+        )
+    }
+    #[getter(traded_volume_array)]
+    fn get_traded_volume_array(&self, py: Python) -> PyResult<PyObject> {
+        array::ladder(&self.traded_volume, py)
+    }
+    #[getter(best_back)]
+    fn get_best_back(&self) -> Option<PriceSize> {
+        analytics::best(&self.available_to_back)
+    }
+    #[getter(best_lay)]
+    fn get_best_lay(&self) -> Option<PriceSize> {
+        analytics::best(&self.available_to_lay)
+    }
+    #[getter(spread)]
+    fn get_spread(&self) -> Option<i32> {
+        analytics::spread(&self.available_to_back, &self.available_to_lay)
+    }
+    #[getter(wap)]
+    fn get_wap(&self) -> Option<f64> {
+        analytics::wap(&self.traded_volume)
+    }
+
+    fn volume_at_price(&self, price: f64) -> f64 {
+        analytics::volume_at_price(&self.traded_volume, price)
+    }
+    #[args(ticks = "3")]
+    fn back_depth(&self, ticks: u32) -> f64 {
+        analytics::depth(&self.available_to_back, ticks)
+    }
+    #[args(ticks = "3")]
+    fn lay_depth(&self, ticks: u32) -> f64 {
+        analytics::depth(&self.available_to_lay, ticks)
+    }
 }
@@ -12,33 +12,55 @@
     generic_associated_types
 )]
 
+mod analytics;
+mod array;
 mod bflw;
 mod config;
 mod datetime;
 mod deser;
 mod enums;
 mod errors;
+mod events;
 mod file;
 mod file_iter;
 mod files;
+mod history;
 mod ids;
+mod image;
 mod immutable;
 mod market_source;
 mod mutable;
+mod pickle;
 mod price_size;
 mod py_rep;
+mod split;
 mod strings;
+mod validate;
+mod writer;
 
+use crate::bflw::cache::BflwCache;
 use crate::bflw::file::{BflwFile, BflwFiles};
+use crate::bflw::generator::{
+    create_historical_generator_stream, HistoricalGenerator, HistoricalGeneratorStream,
+};
+use crate::events::LifecycleEvent;
 use crate::file::File;
 use crate::files::Files;
+use crate::history::DefinitionChange;
+use crate::pickle::_unpickle;
 use crate::price_size::PriceSize;
+use crate::validate::{validate, Issue};
+use crate::writer::StreamWriter;
 
 use bflw::market_book::MarketBook;
+use bflw::market_catalogue::{
+    Event, EventType, MarketCatalogue, MarketCatalogueDescription, RunnerCatalogue,
+};
 use bflw::market_definition::MarketDefinition;
 use bflw::market_definition_runner::MarketDefinitionRunner;
 use bflw::runner_book::RunnerBook;
 use immutable::market::Market;
+use immutable::market_change::{MarketChange, RunnerChange};
 use immutable::runner::Runner;
 use immutable::runner_book_ex::RunnerBookEX;
 use immutable::runner_book_sp::RunnerBookSP;
@@ -63,14 +85,31 @@ fn betfair_data(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Runner>()?;
     m.add_class::<RunnerBookEX>()?;
     m.add_class::<RunnerBookSP>()?;
+    m.add_class::<MarketChange>()?;
+    m.add_class::<RunnerChange>()?;
+    m.add_class::<StreamWriter>()?;
+    m.add_function(wrap_pyfunction!(_unpickle, m)?)?;
+    m.add_class::<Issue>()?;
+    m.add_class::<DefinitionChange>()?;
+    m.add_class::<LifecycleEvent>()?;
+    m.add_function(wrap_pyfunction!(validate, m)?)?;
 
     let bflw = PyModule::new(py, "bflw")?;
     bflw.add_class::<BflwFile>()?;
     bflw.add_class::<BflwFiles>()?;
+    bflw.add_class::<BflwCache>()?;
+    bflw.add_class::<HistoricalGeneratorStream>()?;
+    bflw.add_class::<HistoricalGenerator>()?;
+    bflw.add_function(wrap_pyfunction!(create_historical_generator_stream, bflw)?)?;
     bflw.add_class::<MarketBook>()?;
     bflw.add_class::<MarketDefinitionRunner>()?;
     bflw.add_class::<MarketDefinition>()?;
     bflw.add_class::<RunnerBook>()?;
+    bflw.add_class::<MarketCatalogue>()?;
+    bflw.add_class::<MarketCatalogueDescription>()?;
+    bflw.add_class::<Event>()?;
+    bflw.add_class::<EventType>()?;
+    bflw.add_class::<RunnerCatalogue>()?;
     m.add_submodule(bflw)?;
 
     Ok(())
@@ -8,8 +8,10 @@ use std::fmt;
 
 use crate::datetime::DateTimeString;
 use crate::enums::{MarketBettingType, MarketStatus};
-use crate::ids::{EventID, EventTypeID};
+use crate::ids::{EventID, EventTypeID, RunnerIndex};
+use crate::immutable::definition::Transition;
 use crate::mutable::runner::{Runner, RunnerDefSeqDeser};
+use crate::pickle::{Decoder, Encoder, Pickle};
 use crate::strings::{FixedSizeString, StringSetExtNeq};
 use crate::{config::Config, errors::DataError};
 
@@ -48,6 +50,77 @@ pub struct MarketDefinition {
     pub each_way_divisor: Option<f64>,
 }
 
+impl Pickle for MarketDefinition {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.bet_delay.encode(e, py);
+        self.bsp_market.encode(e, py);
+        self.bsp_reconciled.encode(e, py);
+        self.complete.encode(e, py);
+        self.cross_matching.encode(e, py);
+        self.discount_allowed.encode(e, py);
+        self.event_id.encode(e, py);
+        self.event_name.encode(e, py);
+        self.event_type_id.encode(e, py);
+        self.in_play.encode(e, py);
+        self.market_base_rate.encode(e, py);
+        self.market_type.encode(e, py);
+        self.market_name.encode(e, py);
+        self.number_of_active_runners.encode(e, py);
+        self.number_of_winners.encode(e, py);
+        self.persistence_enabled.encode(e, py);
+        self.runners_voidable.encode(e, py);
+        self.timezone.encode(e, py);
+        self.turn_in_play_enabled.encode(e, py);
+        self.venue.encode(e, py);
+        self.version.encode(e, py);
+        self.status.encode(e, py);
+        self.betting_type.encode(e, py);
+        self.market_time.encode(e, py);
+        self.open_date.encode(e, py);
+        self.suspend_time.encode(e, py);
+        self.settled_time.encode(e, py);
+        self.country_code.encode(e, py);
+        self.regulators.encode(e, py);
+        self.race_type.encode(e, py);
+        self.each_way_divisor.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            bet_delay: Pickle::decode(d, py)?,
+            bsp_market: Pickle::decode(d, py)?,
+            bsp_reconciled: Pickle::decode(d, py)?,
+            complete: Pickle::decode(d, py)?,
+            cross_matching: Pickle::decode(d, py)?,
+            discount_allowed: Pickle::decode(d, py)?,
+            event_id: Pickle::decode(d, py)?,
+            event_name: Pickle::decode(d, py)?,
+            event_type_id: Pickle::decode(d, py)?,
+            in_play: Pickle::decode(d, py)?,
+            market_base_rate: Pickle::decode(d, py)?,
+            market_type: Pickle::decode(d, py)?,
+            market_name: Pickle::decode(d, py)?,
+            number_of_active_runners: Pickle::decode(d, py)?,
+            number_of_winners: Pickle::decode(d, py)?,
+            persistence_enabled: Pickle::decode(d, py)?,
+            runners_voidable: Pickle::decode(d, py)?,
+            timezone: Pickle::decode(d, py)?,
+            turn_in_play_enabled: Pickle::decode(d, py)?,
+            venue: Pickle::decode(d, py)?,
+            version: Pickle::decode(d, py)?,
+            status: Pickle::decode(d, py)?,
+            betting_type: Pickle::decode(d, py)?,
+            market_time: Pickle::decode(d, py)?,
+            open_date: Pickle::decode(d, py)?,
+            suspend_time: Pickle::decode(d, py)?,
+            settled_time: Pickle::decode(d, py)?,
+            country_code: Pickle::decode(d, py)?,
+            regulators: Pickle::decode(d, py)?,
+            race_type: Pickle::decode(d, py)?,
+            each_way_divisor: Pickle::decode(d, py)?,
+        })
+    }
+}
+
 #[derive(Debug, Default)]
 struct MarketDefinitionUpdate<'a> {
     bet_delay: Option<u16>,
@@ -318,10 +391,14 @@ impl<'a, 'b> MarketDefinitionUpdate<'a> {
     }
 }
 
-// Used for serializing in place over the mc marketDefinition object
+// Used for serializing in place over the mc marketDefinition object, recording the statuses it
+// moves the market and its runners through when there's a previous definition to move from
 pub struct MarketDefinitionDeser<'a, 'py> {
     pub def: Option<&'a mut MarketDefinition>,
     pub runners: &'a mut Vec<Py<Runner>>,
+    pub index: &'a mut RunnerIndex,
+    pub transitions: &'a mut Vec<Transition>,
+    pub removed: &'a mut Vec<Py<Runner>>,
     pub config: Config,
     pub py: Python<'py>,
 }
@@ -377,6 +454,9 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketDefinitionDeser<'a, 'py> {
         struct MarketDefinitionVisitorDeser<'a, 'py> {
             def: Option<&'a mut MarketDefinition>,
             runners: &'a mut Vec<Py<Runner>>,
+            index: &'a mut RunnerIndex,
+            transitions: &'a mut Vec<Transition>,
+            removed: &'a mut Vec<Py<Runner>>,
             config: Config,
             py: Python<'py>,
         }
@@ -397,6 +477,9 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketDefinitionDeser<'a, 'py> {
                     match key {
                         Field::Runners => map.next_value_seed(RunnerDefSeqDeser {
                             runners: self.runners,
+                            index: self.index,
+                            transitions: self.transitions,
+                            removed: self.removed,
                             config: self.config,
                             py: self.py,
                         })?,
@@ -521,10 +604,24 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketDefinitionDeser<'a, 'py> {
 
                 match self.def {
                     Some(def) => {
+                        let (status, in_play, bsp_reconciled) =
+                            (def.status, def.in_play, def.bsp_reconciled);
+
                         upt.update(def).map_err(|err| {
                             Error::custom(format!("missing required field <{}>", err.missing_field))
                         })?;
 
+                        if def.status != status {
+                            self.transitions
+                                .push(Transition::Market(status, def.status));
+                        }
+                        if def.in_play && !in_play {
+                            self.transitions.push(Transition::InPlay);
+                        }
+                        if def.bsp_reconciled && !bsp_reconciled {
+                            self.transitions.push(Transition::BspReconciled);
+                        }
+
                         Ok(None)
                     }
                     None => {
@@ -583,6 +680,9 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketDefinitionDeser<'a, 'py> {
             MarketDefinitionVisitorDeser {
                 def: self.def,
                 runners: self.runners,
+                index: self.index,
+                transitions: self.transitions,
+                removed: self.removed,
                 config: self.config,
                 py: self.py,
             },
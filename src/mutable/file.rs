@@ -2,7 +2,7 @@ use pyo3::prelude::*;
 use std::path::Path;
 
 use crate::config::Config;
-use crate::file_iter::{FileIter, IntoMarketIter, MarketID};
+use crate::file_iter::{Books, FileIter, IntoMarketIter, MarketID};
 use crate::market_source::SourceItem;
 use crate::mutable::market::{MarketMut, MarketMutDeser};
 
@@ -41,7 +41,7 @@ impl IntoMarketIter for MutableRep {
     type Deser<'a, 'de, 'py> = MarketMutDeser<'a, 'py>;
 
     fn new<'a, 'de, 'py>(
-        books: &'a [Py<Self::Market>],
+        books: &'a Books<Self::Market>,
         py: Python<'py>,
         config: Config,
     ) -> Self::Deser<'a, 'de, 'py> {
@@ -4,16 +4,24 @@ use serde::{
     de::{DeserializeSeed, MapAccess, Visitor},
     Deserialize, Deserializer,
 };
-use serde_json::value::RawValue;
 use std::collections::VecDeque;
 use std::fmt;
 
 use super::definition::{MarketDefinition, MarketDefinitionDeser};
+use crate::analytics;
+use crate::array;
 use crate::config::Config;
 use crate::datetime::DateTime;
-use crate::ids::{Clk, MarketID};
+use crate::deser::{self, Buffered};
+use crate::enums::{ChangeType, MarketStatus, SelectionStatus};
+use crate::file_iter::Books;
+use crate::ids::{Clk, MarketID, RunnerIndex};
+use crate::image::{self, DefinitionImage, McImage, RunnerChangeImage, RunnerDefinitionImage};
 use crate::immutable::container::SyncObj;
+use crate::immutable::definition::Transition;
 use crate::mutable::runner::{Runner, RunnerChangeSeqDeser};
+use crate::pickle::{self, Decoder, Encoder, Kind, Pickle};
+use crate::price_size::F64OrStr;
 use crate::py_rep::PyRep;
 
 #[pyclass(name = "MarketMut")]
@@ -29,6 +37,22 @@ pub struct MarketMut {
     #[pyo3(get)]
     pub runners: Vec<Py<Runner>>,
     def: MarketDefinition,
+    // taken from the stream message that carried the last update
+    #[pyo3(get)]
+    change_type: Option<ChangeType>,
+    #[pyo3(get)]
+    conflated: bool,
+    #[pyo3(get)]
+    stream_status: Option<u16>,
+    // publish time of the last update that traded on any runner
+    #[pyo3(get)]
+    last_trade_time: Option<DateTime>,
+    // positions of the runners by selection id, kept up to date as runners are added or moved
+    index: RunnerIndex,
+    // statuses the update's definition moved the market and its runners through, with copies of
+    // the runners it removed as they were before
+    pub transitions: Vec<Transition>,
+    pub removed: Vec<Py<Runner>>,
 }
 
 impl MarketMut {
@@ -36,6 +60,7 @@ impl MarketMut {
         market_id: MarketID,
         def: MarketDefinition,
         runners: Vec<Py<Runner>>,
+        index: RunnerIndex,
         total_matched: f64,
     ) -> Self {
         Self {
@@ -45,6 +70,13 @@ impl MarketMut {
             total_matched,
             runners,
             def,
+            change_type: None,
+            conflated: false,
+            stream_status: None,
+            last_trade_time: None,
+            index,
+            transitions: Vec::new(),
+            removed: Vec::new(),
         }
     }
 
@@ -62,11 +94,152 @@ impl MarketMut {
             clk: self.clk.clone(),
             def: self.def.clone(),
             runners,
+            change_type: self.change_type,
+            conflated: self.conflated,
+            stream_status: self.stream_status,
+            last_trade_time: self.last_trade_time,
+            index: self.index.clone(),
+            transitions: self.transitions.clone(),
+            removed: self.removed.iter().map(|r| r.clone_ref(py)).collect(),
+        }
+    }
+
+    // drops the runners ready for a full image to be applied, the image's definition and runner
+    // changes build them again as they would for a new market
+    fn clear(&mut self) {
+        self.runners = Vec::new();
+        self.index = RunnerIndex::default();
+        self.total_matched = 0.0;
+    }
+
+    pub fn image(&self, py: Python) -> McImage {
+        let def = &self.def;
+
+        McImage {
+            id: self.market_id.as_str(),
+            market_definition: DefinitionImage {
+                bet_delay: def.bet_delay,
+                betting_type: def.betting_type,
+                bsp_market: def.bsp_market,
+                bsp_reconciled: def.bsp_reconciled,
+                complete: def.complete,
+                country_code: def.country_code.as_ref().map(|s| s.as_str()),
+                cross_matching: def.cross_matching,
+                discount_allowed: def.discount_allowed,
+                each_way_divisor: def.each_way_divisor,
+                event_id: def.event_id.to_string(),
+                event_name: def.event_name.as_deref(),
+                event_type_id: def.event_type_id.to_string(),
+                in_play: def.in_play,
+                market_base_rate: def.market_base_rate,
+                market_time: def.market_time.as_ref(),
+                market_type: &def.market_type,
+                name: def.market_name.as_deref(),
+                number_of_active_runners: def.number_of_active_runners,
+                number_of_winners: def.number_of_winners,
+                open_date: def.open_date.as_ref(),
+                persistence_enabled: def.persistence_enabled,
+                race_type: def.race_type.as_deref(),
+                regulators: &def.regulators,
+                runners: self
+                    .runners
+                    .iter()
+                    .map(|r| {
+                        let r = r.borrow(py);
+                        RunnerDefinitionImage {
+                            adjustment_factor: r.adjustment_factor,
+                            status: r.status,
+                            sort_priority: r.sort_priority,
+                            removal_date: r.removal_date.as_ref().map(|s| s.to_string()),
+                            id: r.selection_id.id(),
+                            name: r.name.clone(),
+                            hc: r.selection_id.handicap(),
+                            bsp: r.sp.borrow(py).actual_sp.map(F64OrStr::from),
+                        }
+                    })
+                    .collect(),
+                runners_voidable: def.runners_voidable,
+                settled_time: def.settled_time.as_ref().map(|s| s.as_ref()),
+                status: def.status,
+                suspend_time: def.suspend_time.as_ref().map(|s| s.as_ref()),
+                timezone: &def.timezone,
+                turn_in_play_enabled: def.turn_in_play_enabled,
+                venue: def.venue.as_deref(),
+                version: def.version,
+                line_max_unit: None,
+                line_min_unit: None,
+                line_interval: None,
+                price_ladder_definition: None,
+                key_line_definition: None,
+            },
+            rc: self
+                .runners
+                .iter()
+                .map(|r| {
+                    let r = r.borrow(py);
+                    let ex = r.ex.borrow(py);
+                    let sp = r.sp.borrow(py);
+
+                    RunnerChangeImage {
+                        id: r.selection_id.id(),
+                        hc: r.selection_id.handicap(),
+                        atb: ex.available_to_back.clone(),
+                        atl: ex.available_to_lay.clone(),
+                        trd: ex.traded_volume.clone(),
+                        spb: sp.lay_liability_taken.clone(),
+                        spl: sp.back_stake_taken.clone(),
+                        spn: sp.near_price.map(F64OrStr::from),
+                        spf: sp.far_price.map(F64OrStr::from),
+                        ltp: r.last_price_traded.map(F64OrStr::from),
+                        tv: r.total_matched,
+                    }
+                })
+                .collect(),
+            img: true,
+            tv: self.total_matched,
         }
     }
 
-    fn clear(&self, py: Python) {
-        self.runners.iter().for_each(|r| r.borrow_mut(py).clear(py));
+    fn active_runners<'a>(&'a self, py: Python<'a>) -> impl Iterator<Item = PyRef<'a, Runner>> {
+        self.runners
+            .iter()
+            .map(move |r| r.borrow(py))
+            .filter(|r| r.status == SelectionStatus::Active)
+    }
+}
+
+impl Pickle for MarketMut {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.market_id.encode(e, py);
+        self.clk.encode(e, py);
+        self.publish_time.encode(e, py);
+        self.total_matched.encode(e, py);
+        self.runners.encode(e, py);
+        self.def.encode(e, py);
+        self.change_type.encode(e, py);
+        self.conflated.encode(e, py);
+        self.stream_status.encode(e, py);
+        self.last_trade_time.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        let mut m = Self {
+            market_id: Pickle::decode(d, py)?,
+            clk: Pickle::decode(d, py)?,
+            publish_time: Pickle::decode(d, py)?,
+            total_matched: Pickle::decode(d, py)?,
+            runners: Pickle::decode(d, py)?,
+            def: Pickle::decode(d, py)?,
+            change_type: Pickle::decode(d, py)?,
+            conflated: Pickle::decode(d, py)?,
+            stream_status: Pickle::decode(d, py)?,
+            last_trade_time: Pickle::decode(d, py)?,
+            index: Default::default(),
+            transitions: Vec::new(),
+            removed: Vec::new(),
+        };
+        m.index = RunnerIndex::new(m.runners.iter().map(|r| r.borrow(py).selection_id));
+
+        Ok(m)
     }
 }
 
@@ -75,6 +248,22 @@ impl MarketMut {
     fn copy(&self, py: Python) -> PyObject {
         self.clone(py).into_py(py)
     }
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        pickle::reduce(Kind::MarketMut, self, py)
+    }
+    fn __copy__(&self, py: Python) -> PyObject {
+        self.clone(py).into_py(py)
+    }
+    fn __deepcopy__(&self, _memo: &PyAny, py: Python) -> PyObject {
+        self.clone(py).into_py(py)
+    }
+    // a full image of the market as a stream mc entry
+    fn to_json(&self, py: Python) -> PyResult<String> {
+        image::to_json(&self.image(py))
+    }
+    fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        image::to_dict(&self.image(py), py)
+    }
 
     #[getter(market_id)]
     fn get_market_id(&self) -> &str {
@@ -217,10 +406,48 @@ impl MarketMut {
     fn get_regulators(&self, py: Python) -> PyObject {
         self.def.regulators.py_rep(py)
     }
+    #[getter(back_overround)]
+    fn get_back_overround(&self, py: Python) -> f64 {
+        analytics::book_percentage(self.active_runners(py).filter_map(|r| {
+            analytics::best(&r.ex.borrow(py).available_to_back).map(|ps| ps.price)
+        }))
+    }
+    #[getter(lay_overround)]
+    fn get_lay_overround(&self, py: Python) -> f64 {
+        analytics::book_percentage(self.active_runners(py).filter_map(|r| {
+            analytics::best(&r.ex.borrow(py).available_to_lay).map(|ps| ps.price)
+        }))
+    }
+    #[getter(implied_probability)]
+    fn get_implied_probability(&self, py: Python) -> f64 {
+        analytics::book_percentage(
+            self.active_runners(py)
+                .filter_map(|r| r.last_price_traded),
+        )
+    }
+
+    // one row per runner of [selection_id, last_price_traded, total_matched, best_back, best_lay]
+    // with missing values as nan
+    fn runner_matrix(&self, py: Python) -> PyResult<PyObject> {
+        let values = self.runners.iter().flat_map(|r| {
+            let r = r.borrow(py);
+            let ex = r.ex.borrow(py);
+
+            [
+                r.selection_id.id() as f64,
+                r.last_price_traded.unwrap_or(f64::NAN),
+                r.total_matched,
+                analytics::best(&ex.available_to_back).map_or(f64::NAN, |ps| ps.price),
+                analytics::best(&ex.available_to_lay).map_or(f64::NAN, |ps| ps.price),
+            ]
+        });
+
+        array::matrix(self.runners.len(), 5, values, py)
+    }
 }
 
 pub struct MarketMutDeser<'a, 'py> {
-    pub markets: &'a [Py<MarketMut>],
+    pub markets: &'a Books<MarketMut>,
     pub py: Python<'py>,
     pub config: Config,
 }
@@ -236,12 +463,19 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMutDeser<'a, 'py> {
         enum Field {
             Op,
             Clk,
+            #[serde(rename = "initialClk")]
+            InitialClk,
             Pt,
+            Ct,
+            Status,
             Mc,
+            // id, heartbeatMs, conflateMs, segmentType and the fields of connection/status lines
+            #[serde(other)]
+            Other,
         }
 
         struct PyMarketOuterVisitor<'a, 'py> {
-            markets: &'a [Py<MarketMut>],
+            markets: &'a Books<MarketMut>,
             config: Config,
             py: Python<'py>,
         }
@@ -258,19 +492,29 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMutDeser<'a, 'py> {
             {
                 let mut pt: Option<DateTime> = None;
                 let mut clk: Option<Clk> = None;
+                let mut initial_clk: Option<Clk> = None;
+                let mut ct: Option<ChangeType> = None;
+                let mut status: Option<u16> = None;
                 let mut books: VecDeque<Py<MarketMut>> = VecDeque::new();
+                let mut late_mc: Option<Buffered> = None;
 
                 while let Some(key) = map.next_key()? {
                     match key {
-                        Field::Op => {
+                        Field::Op | Field::Other => {
                             map.next_value::<IgnoredAny>()?;
                         }
                         Field::Pt => {
                             pt = Some(DateTime::new(map.next_value::<u64>()?));
                         }
+                        // betfair writes pt ahead of mc, anything else is held back until pt
+                        // has been read so trades can be timed
+                        Field::Mc if pt.is_none() => {
+                            late_mc = Some(map.next_value::<Buffered>()?);
+                        }
                         Field::Mc => {
                             books = map.next_value_seed(PyMarketMcSeqDeser {
                                 markets: self.markets,
+                                pt,
                                 config: self.config,
                                 py: self.py,
                             })?;
@@ -278,10 +522,55 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMutDeser<'a, 'py> {
                         Field::Clk => {
                             clk = Some(map.next_value::<Clk>()?);
                         }
+                        Field::InitialClk => {
+                            initial_clk = Some(map.next_value::<Clk>()?);
+                        }
+                        Field::Ct => {
+                            ct = map.next_value::<Option<ChangeType>>()?;
+                        }
+                        Field::Status => {
+                            status = map.next_value::<Option<u16>>()?;
+                        }
                     }
                 }
 
-                if let (Some(pt), Some(clk)) = (pt, clk) {
+                if let Some(raw) = late_mc {
+                    books = deser::replay(
+                        raw,
+                        PyMarketMcSeqDeser {
+                            markets: self.markets,
+                            pt,
+                            config: self.config,
+                            py: self.py,
+                        },
+                    )
+                    .map_err(Error::custom)?;
+                }
+
+                // heartbeats carry no market changes, but re-yield every open market so gaps in
+                // a recording show up as gaps in publish_time
+                if self.config.heartbeats && ct == Some(ChangeType::Heartbeat) && books.is_empty() {
+                    books = self
+                        .markets
+                        .iter()
+                        .filter(|m| m.borrow(self.py).def.status != MarketStatus::Closed)
+                        .map(|m| {
+                            // nothing changed, so neither did the runners' deltas
+                            let mut market = m.borrow_mut(self.py);
+                            market.conflated = false;
+                            market.transitions.clear();
+                            market.removed.clear();
+                            market
+                                .runners
+                                .iter()
+                                .for_each(|r| r.borrow_mut(self.py).clear_deltas());
+                            m.clone_ref(self.py)
+                        })
+                        .collect();
+                }
+
+                // a sub image can arrive with only an initialClk
+                if let (Some(pt), Some(clk)) = (pt, clk.or(initial_clk)) {
                     books.iter_mut().for_each(|mb| {
                         let mut m = mb.borrow_mut(self.py);
                         m.publish_time = pt;
@@ -289,11 +578,17 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMutDeser<'a, 'py> {
                     });
                 }
 
+                books.iter_mut().for_each(|mb| {
+                    let mut m = mb.borrow_mut(self.py);
+                    m.change_type = ct;
+                    m.stream_status = status;
+                });
+
                 Ok(books)
             }
         }
 
-        const FIELDS: &[&str] = &["op", "pt", "clk", "mc"];
+        const FIELDS: &[&str] = &["op", "pt", "clk", "initialClk", "ct", "status", "mc"];
         deserializer.deserialize_struct(
             "MarketBook",
             FIELDS,
@@ -308,7 +603,8 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for MarketMutDeser<'a, 'py> {
 
 // Used for serializing in place over the marketChange `mc` array
 struct PyMarketMcSeqDeser<'a, 'py> {
-    markets: &'a [Py<MarketMut>],
+    markets: &'a Books<MarketMut>,
+    pt: Option<DateTime>,
     config: Config,
     py: Python<'py>,
 }
@@ -320,7 +616,8 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMcSeqDeser<'a, 'py> {
         D: Deserializer<'de>,
     {
         struct PyMarketMcSeqDeserVisitor<'a, 'py> {
-            markets: &'a [Py<MarketMut>],
+            markets: &'a Books<MarketMut>,
+            pt: Option<DateTime>,
             config: Config,
             py: Python<'py>,
         }
@@ -344,9 +641,8 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMcSeqDeser<'a, 'py> {
                 // TODO what should we do if a market appears twice in a mc
                 let mut next_books: VecDeque<Py<MarketMut>> = VecDeque::new();
 
-                while let Some(raw) = seq.next_element::<&RawValue>()? {
-                    let mut deserializer = serde_json::Deserializer::from_str(raw.get());
-                    let idimg: IdImg = serde_json::from_str(raw.get()).map_err(Error::custom)?;
+                while let Some(raw) = seq.next_element::<Buffered>()? {
+                    let idimg: IdImg = deser::peek(&raw).map_err(Error::custom)?;
 
                     let (market, i) = {
                         let i = next_books
@@ -357,7 +653,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMcSeqDeser<'a, 'py> {
                             Some(i) => {
                                 let market = next_books.get(i).unwrap();
                                 if idimg.img.contains(&true) {
-                                    market.borrow_mut(self.py).clear(self.py);
+                                    market.borrow_mut(self.py).clear();
                                 }
 
                                 (Some(market.clone_ref(self.py)), Some(i))
@@ -365,12 +661,11 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMcSeqDeser<'a, 'py> {
                             None => {
                                 let m = self
                                     .markets
-                                    .iter()
-                                    .find(|m| (*m).borrow(self.py).market_id.as_str() == idimg.id)
+                                    .get(idimg.id.as_str())
                                     .map(|o| o.clone_ref(self.py));
 
                                 if idimg.img.contains(&true) && let Some(market) = &m {
-                                    market.borrow_mut(self.py).clear(self.py);
+                                    market.borrow_mut(self.py).clear();
                                 }
 
                                 (m, None)
@@ -378,19 +673,22 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMcSeqDeser<'a, 'py> {
                         }
                     };
 
-                    let market = PyMarketMc {
-                        mid: idimg.id,
-                        market,
-                        config: self.config,
-                        py: self.py,
-                    }
-                    .deserialize(&mut deserializer)
+                    let market = deser::replay(
+                        raw,
+                        PyMarketMc {
+                            mid: idimg.id,
+                            market,
+                            pt: self.pt,
+                            config: self.config,
+                            py: self.py,
+                        },
+                    )
                     .map_err(Error::custom)?;
 
                     match (market, i) {
                         (Some(m), Some(i)) => next_books[i] = m,
                         (Some(m), None) => next_books.push_back(m),
-                        _ => {}
+                        (None, _) => self.markets.skip(idimg.id.as_str()),
                     }
                 }
 
@@ -400,16 +698,26 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMcSeqDeser<'a, 'py> {
 
         deserializer.deserialize_seq(PyMarketMcSeqDeserVisitor {
             markets: self.markets,
+            pt: self.pt,
             config: self.config,
             py: self.py,
         })
     }
 }
 
+// deltas are cleared at the start of each update, so any traded volume left is from this one
+fn traded(runners: &[Py<Runner>], py: Python) -> bool {
+    runners.iter().any(|r| {
+        let r = r.borrow(py);
+        r.traded_volume_delta.iter().any(|ps| ps.size > 0.0)
+    })
+}
+
 // Used for serializing in place over the marketChange `mc` objects
 struct PyMarketMc<'py> {
     mid: MarketID,
     market: Option<Py<MarketMut>>,
+    pt: Option<DateTime>,
     config: Config,
     py: Python<'py>,
 }
@@ -438,6 +746,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMc<'py> {
         struct PyMarketMcVisitor<'py> {
             mid: MarketID,
             market: Option<Py<MarketMut>>,
+            pt: Option<DateTime>,
             config: Config,
             py: Python<'py>,
         }
@@ -456,12 +765,23 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMc<'py> {
                     Some(market) => {
                         let m = &mut *market.borrow_mut(self.py);
 
+                        // deltas only describe the changes from this update
+                        m.runners
+                            .iter()
+                            .for_each(|r| r.borrow_mut(self.py).clear_deltas());
+                        m.conflated = false;
+                        m.transitions.clear();
+                        m.removed.clear();
+
                         while let Some(key) = map.next_key()? {
                             match key {
                                 Field::MarketDefinition => {
                                     map.next_value_seed(MarketDefinitionDeser {
                                         def: Some(&mut m.def),
                                         runners: &mut m.runners,
+                                        index: &mut m.index,
+                                        transitions: &mut m.transitions,
+                                        removed: &mut m.removed,
                                         config: self.config,
                                         py: self.py,
                                     })?;
@@ -469,10 +789,16 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMc<'py> {
                                 Field::Rc => {
                                     map.next_value_seed(RunnerChangeSeqDeser {
                                         runners: &mut m.runners,
+                                        index: &mut m.index,
+                                        pt: self.pt,
                                         config: self.config,
                                         py: self.py,
                                     })?;
 
+                                    if traded(&m.runners, self.py) {
+                                        m.last_trade_time = self.pt.or(m.last_trade_time);
+                                    }
+
                                     if self.config.cumulative_runner_tv {
                                         m.total_matched = m
                                             .runners
@@ -488,6 +814,9 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMc<'py> {
                                         map.next_value::<IgnoredAny>()?;
                                     }
                                 }
+                                Field::Con => {
+                                    m.conflated = map.next_value::<Option<bool>>()?.contains(&true);
+                                }
                                 _ => {
                                     map.next_value::<IgnoredAny>()?;
                                 }
@@ -499,7 +828,13 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMc<'py> {
                     None => {
                         let mut def = None;
                         let mut runners = Vec::with_capacity(12);
+                        let mut index = RunnerIndex::default();
+                        // a market's first definition has nothing to move its statuses from
+                        let mut transitions = Vec::new();
+                        let mut removed = Vec::new();
                         let mut total_matched = 0.0;
+                        let mut conflated = false;
+                        let mut last_trade_time = None;
 
                         while let Some(key) = map.next_key()? {
                             match key {
@@ -507,6 +842,9 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMc<'py> {
                                     def = map.next_value_seed(MarketDefinitionDeser {
                                         def: None,
                                         runners: &mut runners,
+                                        index: &mut index,
+                                        transitions: &mut transitions,
+                                        removed: &mut removed,
                                         config: self.config,
                                         py: self.py,
                                     })?;
@@ -514,10 +852,16 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMc<'py> {
                                 Field::Rc => {
                                     map.next_value_seed(RunnerChangeSeqDeser {
                                         runners: &mut runners,
+                                        index: &mut index,
+                                        pt: self.pt,
                                         config: self.config,
                                         py: self.py,
                                     })?;
 
+                                    if traded(&runners, self.py) {
+                                        last_trade_time = self.pt;
+                                    }
+
                                     if self.config.cumulative_runner_tv {
                                         total_matched = runners
                                             .iter()
@@ -532,16 +876,25 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMc<'py> {
                                         map.next_value::<IgnoredAny>()?;
                                     }
                                 }
+                                Field::Con => {
+                                    conflated = map.next_value::<Option<bool>>()?.contains(&true);
+                                }
                                 _ => {
                                     map.next_value::<IgnoredAny>()?;
                                 }
                             }
                         }
 
-                        let def = def.ok_or_else(|| {
-                            Error::custom("No MarketDefinition when creating market.")
-                        })?;
-                        let m = MarketMut::new(self.mid, def, runners, total_matched);
+                        // nothing to build the market from until a definition arrives
+                        let def = match def {
+                            Some(def) => def,
+                            None => return Ok(None),
+                        };
+                        let mut m = MarketMut::new(self.mid, def, runners, index, total_matched);
+                        m.conflated = conflated;
+                        m.last_trade_time = last_trade_time;
+                        m.transitions = transitions;
+                        m.removed = removed;
                         let py_m = Py::new(self.py, m).unwrap();
 
                         Ok(Some(py_m))
@@ -557,6 +910,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for PyMarketMc<'py> {
             PyMarketMcVisitor {
                 mid: self.mid,
                 market: self.market,
+                pt: self.pt,
                 config: self.config,
                 py: self.py,
             },
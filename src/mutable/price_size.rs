@@ -131,6 +131,81 @@ impl<'de, 'a> DeserializeSeed<'de> for PriceSizeLayLadder<'a> {
     }
 }
 
+// The traded ladder, updated in place like a back ladder, with the size each level gained
+// added to a delta ladder in ascending price order as it's applied. Levels that end the update
+// where they started are left out of the delta.
+pub struct TradedLadder<'a>(pub &'a mut Vec<PriceSize>, pub &'a mut Vec<PriceSize>);
+impl<'de, 'a> DeserializeSeed<'de> for TradedLadder<'a> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        struct PSVisitor<'a>(&'a mut Vec<PriceSize>, &'a mut Vec<PriceSize>);
+        impl<'de, 'a> Visitor<'de> for PSVisitor<'a> {
+            type Value = ();
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("An array of PriceSize values")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                // grow an empty vec
+                if self.0.capacity() == 0 {
+                    match seq.size_hint() {
+                        Some(s) => self.0.reserve_exact(std::cmp::max(MIN_VEC_CAP, s + 2)),
+                        None => self.0.reserve_exact(MIN_VEC_CAP),
+                    }
+                }
+
+                while let Some(ps1) = seq.next_element::<PriceSize>()? {
+                    let cmp_fn = |ps2: &PriceSize| {
+                        if ps1.price < ps2.price {
+                            Ordering::Greater
+                        } else if ps1.price > ps2.price {
+                            Ordering::Less
+                        } else {
+                            Ordering::Equal
+                        }
+                    };
+
+                    let prev = match self.0.binary_search_by(cmp_fn) {
+                        Ok(index) if ps1.size == 0.0 => self.0.remove(index).size,
+                        Ok(index) => {
+                            let ps = unsafe { self.0.get_unchecked_mut(index) };
+                            std::mem::replace(&mut ps.size, ps1.size)
+                        }
+                        Err(_) if ps1.size == 0.0 => 0.0,
+                        Err(index) => {
+                            self.0.insert(index, ps1);
+                            0.0
+                        }
+                    };
+
+                    let diff = ps1.size - prev;
+                    if diff != 0.0 {
+                        match self.1.binary_search_by(cmp_fn) {
+                            Ok(index) => {
+                                let ps = unsafe { self.1.get_unchecked_mut(index) };
+                                ps.size += diff;
+                                if ps.size == 0.0 {
+                                    self.1.remove(index);
+                                }
+                            }
+                            Err(index) => self.1.insert(index, PriceSize::new(ps1.price, diff)),
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        deserializer.deserialize_seq(PSVisitor(self.0, self.1))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -463,4 +538,53 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_traded_ladder_delta() {
+        let raw = r#"
+            [[2, 5],[3, 5]]
+            [[3, 8],[4, 2],[2, 5],[5, 0]]
+            [[4, 3],[4, 2],[3, 0]]
+        "#;
+        let mut deser = serde_json::Deserializer::from_str(raw);
+
+        let mut trd: Vec<PriceSize> = Vec::new();
+        let mut delta: Vec<PriceSize> = Vec::new();
+
+        TradedLadder(&mut trd, &mut delta)
+            .deserialize(&mut deser)
+            .expect("failed to deserialize");
+        assert_eq!(
+            delta,
+            vec![PriceSize::new(2.0, 5.0), PriceSize::new(3.0, 5.0)]
+        );
+
+        delta.clear();
+        TradedLadder(&mut trd, &mut delta)
+            .deserialize(&mut deser)
+            .expect("failed to deserialize");
+        assert_eq!(
+            trd,
+            vec![
+                PriceSize::new(2.0, 5.0),
+                PriceSize::new(3.0, 8.0),
+                PriceSize::new(4.0, 2.0),
+            ]
+        );
+        assert_eq!(
+            delta,
+            vec![PriceSize::new(3.0, 3.0), PriceSize::new(4.0, 2.0)]
+        );
+
+        // a level sent twice in one update nets out
+        delta.clear();
+        TradedLadder(&mut trd, &mut delta)
+            .deserialize(&mut deser)
+            .expect("failed to deserialize");
+        assert_eq!(
+            trd,
+            vec![PriceSize::new(2.0, 5.0), PriceSize::new(4.0, 2.0)]
+        );
+        assert_eq!(delta, vec![PriceSize::new(3.0, -8.0)]);
+    }
 }
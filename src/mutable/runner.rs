@@ -1,15 +1,19 @@
 use pyo3::prelude::*;
 use serde::de::{DeserializeSeed, Error, IgnoredAny, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
-use serde_json::value::RawValue;
 use std::{borrow::Cow, fmt};
 
+use crate::analytics;
 use crate::config::Config;
-use crate::datetime::DateTimeString;
+use crate::datetime::{DateTime, DateTimeString};
+use crate::deser::{self, Buffered};
 use crate::enums::SelectionStatus;
-use crate::ids::SelectionID;
-use crate::mutable::price_size::{PriceSizeBackLadder, PriceSizeLayLadder};
-use crate::price_size::F64OrStr;
+use crate::events::is_removed;
+use crate::ids::{RunnerIndex, SelectionID};
+use crate::immutable::definition::Transition;
+use crate::mutable::price_size::{PriceSizeBackLadder, PriceSizeLayLadder, TradedLadder};
+use crate::pickle::{self, Decoder, Encoder, Kind, Pickle};
+use crate::price_size::{F64OrStr, PriceSize};
 use crate::py_rep::PyRep;
 use crate::strings::{FixedSizeString, StringSetExtNeq};
 
@@ -36,11 +40,25 @@ pub struct Runner {
     #[pyo3(get)]
     pub sort_priority: u16,
     pub removal_date: Option<DateTimeString>,
+    #[pyo3(get)]
+    pub traded_volume_delta: Vec<PriceSize>,
+    #[pyo3(get)]
+    pub total_matched_delta: f64,
+    #[pyo3(get)]
+    pub is_crossed: bool,
+    // publish time of the last update to change the ex ladders
+    #[pyo3(get)]
+    pub last_update_time: Option<DateTime>,
+    // a definition has set the status, runners an rc creates start off without one
+    pub defined: bool,
 }
 
 impl Runner {
-    fn new(py: Python) -> Self {
-        let ex: RunnerBookEXMut = Default::default();
+    fn new(config: Config, py: Python) -> Self {
+        let ex = RunnerBookEXMut {
+            ladder_depth: config.ladder_depth,
+            ..Default::default()
+        };
         let sp: RunnerBookSPMut = Default::default();
 
         Runner {
@@ -52,6 +70,11 @@ impl Runner {
             adjustment_factor: Default::default(),
             sort_priority: Default::default(),
             removal_date: Default::default(),
+            traded_volume_delta: Default::default(),
+            total_matched_delta: Default::default(),
+            is_crossed: false,
+            last_update_time: None,
+            defined: false,
             ex: Py::new(py, ex).unwrap(),
             sp: Py::new(py, sp).unwrap(),
         }
@@ -70,22 +93,66 @@ impl Runner {
             adjustment_factor: self.adjustment_factor,
             sort_priority: self.sort_priority,
             removal_date: self.removal_date,
+            traded_volume_delta: self.traded_volume_delta.clone(),
+            total_matched_delta: self.total_matched_delta,
+            is_crossed: self.is_crossed,
+            last_update_time: self.last_update_time,
+            defined: self.defined,
             ex: Py::new(py, ex).unwrap(),
             sp: Py::new(py, sp).unwrap(),
         }
     }
 
-    pub fn clear(&mut self, py: Python) {
-        self.ex.borrow_mut(py).clear();
-        self.sp.borrow_mut(py).clear();
-        self.total_matched = 0.0;
-        self.last_price_traded = None;
-        self.adjustment_factor = None;
+    pub fn clear_deltas(&mut self) {
+        self.traded_volume_delta.clear();
+        self.total_matched_delta = 0.0;
+    }
+}
+
+impl Pickle for Runner {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.selection_id.encode(e, py);
+        self.status.encode(e, py);
+        self.name.encode(e, py);
+        self.last_price_traded.encode(e, py);
+        self.total_matched.encode(e, py);
+        self.adjustment_factor.encode(e, py);
+        self.ex.encode(e, py);
+        self.sp.encode(e, py);
+        self.sort_priority.encode(e, py);
+        self.removal_date.encode(e, py);
+        self.traded_volume_delta.encode(e, py);
+        self.total_matched_delta.encode(e, py);
+        self.is_crossed.encode(e, py);
+        self.last_update_time.encode(e, py);
+        self.defined.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            selection_id: Pickle::decode(d, py)?,
+            status: Pickle::decode(d, py)?,
+            name: Pickle::decode(d, py)?,
+            last_price_traded: Pickle::decode(d, py)?,
+            total_matched: Pickle::decode(d, py)?,
+            adjustment_factor: Pickle::decode(d, py)?,
+            ex: Pickle::decode(d, py)?,
+            sp: Pickle::decode(d, py)?,
+            sort_priority: Pickle::decode(d, py)?,
+            removal_date: Pickle::decode(d, py)?,
+            traded_volume_delta: Pickle::decode(d, py)?,
+            total_matched_delta: Pickle::decode(d, py)?,
+            is_crossed: Pickle::decode(d, py)?,
+            last_update_time: Pickle::decode(d, py)?,
+            defined: Pickle::decode(d, py)?,
+        })
     }
 }
 
 #[pymethods]
 impl Runner {
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        pickle::reduce(Kind::RunnerMut, self, py)
+    }
     #[getter(selection_id)]
     fn get_selection_id(&self) -> u32 {
         self.selection_id.id()
@@ -98,10 +165,18 @@ impl Runner {
     fn get_removeal_date(&self, py: Python) -> PyObject {
         self.removal_date.py_rep(py)
     }
+    #[getter(implied_probability)]
+    fn get_implied_probability(&self) -> Option<f64> {
+        self.last_price_traded
+            .filter(|ltp| *ltp > 0.0)
+            .map(|ltp| 1.0 / ltp)
+    }
 }
 
 pub struct RunnerChangeSeqDeser<'a, 'py> {
     pub runners: &'a mut Vec<Py<Runner>>,
+    pub index: &'a mut RunnerIndex,
+    pub pt: Option<DateTime>,
     pub config: Config,
     pub py: Python<'py>,
 }
@@ -114,6 +189,8 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeSeqDeser<'a, 'py> {
     {
         struct RunnerSeqVisitor<'a, 'py> {
             runners: &'a mut Vec<Py<Runner>>,
+            index: &'a mut RunnerIndex,
+            pt: Option<DateTime>,
             config: Config,
             py: Python<'py>,
         }
@@ -142,40 +219,40 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeSeqDeser<'a, 'py> {
                     hc: Option<f32>,
                 }
 
-                while let Some(raw) = seq.next_element::<&RawValue>()? {
-                    let mut deser = serde_json::Deserializer::from_str(raw.get());
-                    let parts: RunnerWithID =
-                        serde_json::from_str(raw.get()).map_err(Error::custom)?;
+                while let Some(raw) = seq.next_element::<Buffered>()? {
+                    let parts: RunnerWithID = deser::peek(&raw).map_err(Error::custom)?;
                     let rid = SelectionID::from((parts.id, parts.hc));
 
-                    let index = self
-                        .runners
-                        .iter()
-                        .map(|r| r.borrow_mut(self.py))
-                        .position(|r| r.selection_id == rid);
-
-                    match index {
+                    match self.index.get(rid) {
                         Some(index) => {
                             let mut runner =
                                 unsafe { self.runners.get_unchecked(index).borrow_mut(self.py) };
-                            RunnerChangeDeser {
-                                runner: &mut runner,
-                                config: self.config,
-                                py: self.py,
-                            }
-                            .deserialize(&mut deser)
+                            deser::replay(
+                                raw,
+                                RunnerChangeDeser {
+                                    runner: &mut runner,
+                                    pt: self.pt,
+                                    config: self.config,
+                                    py: self.py,
+                                },
+                            )
                             .map_err(Error::custom)?;
                         }
                         None => {
-                            let mut runner = Runner::new(self.py);
-                            RunnerChangeDeser {
-                                runner: &mut runner,
-                                config: self.config,
-                                py: self.py,
-                            }
-                            .deserialize(&mut deser)
+                            let mut runner = Runner::new(self.config, self.py);
+                            runner.selection_id = rid;
+                            deser::replay(
+                                raw,
+                                RunnerChangeDeser {
+                                    runner: &mut runner,
+                                    pt: self.pt,
+                                    config: self.config,
+                                    py: self.py,
+                                },
+                            )
                             .map_err(Error::custom)?;
 
+                            self.index.push(rid);
                             self.runners.push(Py::new(self.py, runner).unwrap());
                         }
                     }
@@ -187,6 +264,8 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeSeqDeser<'a, 'py> {
 
         deserializer.deserialize_seq(RunnerSeqVisitor {
             runners: self.runners,
+            index: self.index,
+            pt: self.pt,
             py: self.py,
             config: self.config,
         })
@@ -195,6 +274,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeSeqDeser<'a, 'py> {
 
 struct RunnerChangeDeser<'a, 'py> {
     pub runner: &'a mut Runner,
+    pub pt: Option<DateTime>,
     pub config: Config,
     pub py: Python<'py>,
 }
@@ -223,6 +303,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeDeser<'a, 'py> {
 
         struct RunnerChangeVisitor<'a, 'py> {
             runner: &'a mut Runner,
+            pt: Option<DateTime>,
             config: Config,
             py: Python<'py>,
         }
@@ -237,25 +318,33 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeDeser<'a, 'py> {
             where
                 V: MapAccess<'de>,
             {
+                let prev_tv = self.runner.total_matched;
+                let mut ex_changed = false;
+
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::Atb => {
+                            ex_changed = true;
                             let mut ex = self.runner.ex.borrow_mut(self.py);
                             let atb = &mut ex.available_to_back;
 
                             map.next_value_seed(PriceSizeLayLadder(atb))?;
                         }
                         Field::Atl => {
+                            ex_changed = true;
                             let mut ex = self.runner.ex.borrow_mut(self.py);
                             let atl = &mut ex.available_to_lay;
 
                             map.next_value_seed(PriceSizeBackLadder(atl))?;
                         }
                         Field::Trd => {
+                            ex_changed = true;
                             let mut ex = self.runner.ex.borrow_mut(self.py);
                             let trd = &mut ex.traded_volume;
+                            let delta = &mut self.runner.traded_volume_delta;
+                            delta.clear();
 
-                            map.next_value_seed(PriceSizeBackLadder(trd))?;
+                            map.next_value_seed(TradedLadder(trd, delta))?;
 
                             if self.config.cumulative_runner_tv {
                                 self.runner.total_matched = trd.iter().map(|ps| ps.size).sum();
@@ -303,6 +392,15 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeDeser<'a, 'py> {
                     };
                 }
 
+                self.runner.total_matched_delta = self.runner.total_matched - prev_tv;
+
+                if ex_changed {
+                    let ex = self.runner.ex.borrow(self.py);
+                    self.runner.is_crossed =
+                        analytics::is_crossed(&ex.available_to_back, &ex.available_to_lay);
+                    self.runner.last_update_time = self.pt.or(self.runner.last_update_time);
+                }
+
                 Ok(())
             }
         }
@@ -315,6 +413,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerChangeDeser<'a, 'py> {
             FIELDS,
             RunnerChangeVisitor {
                 runner: self.runner,
+                pt: self.pt,
                 config: self.config,
                 py: self.py,
             },
@@ -336,7 +435,7 @@ struct RunnerDefUpdate<'a> {
 }
 
 impl<'a> RunnerDefUpdate<'a> {
-    fn create(self, py: Python) -> Runner {
+    fn create(self, config: Config, py: Python) -> Runner {
         let sp = RunnerBookSPMut {
             actual_sp: self.bsp.map(|f| *f),
             ..Default::default()
@@ -354,9 +453,21 @@ impl<'a> RunnerDefUpdate<'a> {
                 .removal_date
                 .map(|s| DateTimeString::try_from(s).unwrap()),
             sp: Py::new(py, sp).unwrap(),
-            ex: Py::new(py, RunnerBookEXMut::default()).unwrap(),
+            ex: Py::new(
+                py,
+                RunnerBookEXMut {
+                    ladder_depth: config.ladder_depth,
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
             total_matched: 0.0,
             last_price_traded: None,
+            traded_volume_delta: Vec::new(),
+            total_matched_delta: 0.0,
+            is_crossed: false,
+            last_update_time: None,
+            defined: true,
         }
     }
 
@@ -364,6 +475,7 @@ impl<'a> RunnerDefUpdate<'a> {
         runner.sp.borrow_mut(py).actual_sp = self.bsp.map(|f| *f);
         runner.adjustment_factor = self.adjustment_factor;
         runner.status = self.status;
+        runner.defined = true;
         runner.sort_priority = self.sort_priority;
 
         match self.name {
@@ -387,8 +499,14 @@ impl<'a> RunnerDefUpdate<'a> {
     }
 }
 
+// index is rebuilt if the definition adds, drops or reorders any runners. Status changes to
+// runners a previous definition had given one are recorded, with a copy of each runner the
+// definition removes as it was before.
 pub struct RunnerDefSeqDeser<'a, 'py> {
     pub runners: &'a mut Vec<Py<Runner>>,
+    pub index: &'a mut RunnerIndex,
+    pub transitions: &'a mut Vec<Transition>,
+    pub removed: &'a mut Vec<Py<Runner>>,
     pub config: Config,
     pub py: Python<'py>,
 }
@@ -401,7 +519,10 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeqDeser<'a, 'py> {
     {
         struct RunnerSeqVisitor<'a, 'py> {
             runners: &'a mut Vec<Py<Runner>>,
-            _config: Config,
+            index: &'a mut RunnerIndex,
+            transitions: &'a mut Vec<Transition>,
+            removed: &'a mut Vec<Py<Runner>>,
+            config: Config,
             py: Python<'py>,
         }
         impl<'de, 'a, 'py> Visitor<'de> for RunnerSeqVisitor<'a, 'py> {
@@ -416,6 +537,7 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeqDeser<'a, 'py> {
                 A: serde::de::SeqAccess<'de>,
             {
                 let mut i = 0;
+                let mut moved = false;
 
                 while let Some(upt) = seq.next_element::<RunnerDefUpdate>()? {
                     let rid = SelectionID::from((upt.id, upt.hc));
@@ -431,19 +553,34 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeqDeser<'a, 'py> {
                             if index != i {
                                 self.runners.swap(index, i);
                                 index = i;
+                                moved = true;
                             }
 
                             let runner =
                                 unsafe { self.runners.get_unchecked(index).borrow_mut(self.py) };
 
+                            if runner.defined && runner.status != upt.status {
+                                self.transitions.push(Transition::Runner(
+                                    rid,
+                                    runner.status,
+                                    upt.status,
+                                ));
+
+                                if is_removed(upt.status) && !is_removed(runner.status) {
+                                    self.removed
+                                        .push(Py::new(self.py, runner.clone(self.py)).unwrap());
+                                }
+                            }
+
                             upt.update(runner, self.py);
                         }
                         None => {
-                            let runner = upt.create(self.py);
+                            let runner = upt.create(self.config, self.py);
 
                             self.runners.push(Py::new(self.py, runner).unwrap());
                             let index = self.runners.len() - 1;
                             self.runners.swap(i, index);
+                            moved = true;
                         }
                     }
 
@@ -452,7 +589,16 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeqDeser<'a, 'py> {
 
                 // remove any runners not found in the runners def,
                 // theses will have been swapped to the end of the array
-                self.runners.truncate(i);
+                if self.runners.len() > i {
+                    self.runners.truncate(i);
+                    moved = true;
+                }
+
+                if moved {
+                    *self.index = RunnerIndex::new(
+                        self.runners.iter().map(|r| r.borrow(self.py).selection_id),
+                    );
+                }
 
                 Ok(())
             }
@@ -460,7 +606,10 @@ impl<'de, 'a, 'py> DeserializeSeed<'de> for RunnerDefSeqDeser<'a, 'py> {
 
         deserializer.deserialize_seq(RunnerSeqVisitor {
             runners: self.runners,
-            _config: self.config,
+            index: self.index,
+            transitions: self.transitions,
+            removed: self.removed,
+            config: self.config,
             py: self.py,
         })
     }
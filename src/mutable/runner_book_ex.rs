@@ -1,21 +1,86 @@
+use crate::analytics;
+use crate::array;
+use crate::pickle::{Decoder, Encoder, Pickle};
 use crate::price_size::PriceSize;
+use crate::py_rep::PyRep;
 use pyo3::prelude::*;
 
 #[pyclass(name = "RunnerBookEXMut")]
 #[derive(Default, Clone)]
 pub struct RunnerBookEXMut {
-    #[pyo3(get)]
     pub available_to_back: Vec<PriceSize>,
-    #[pyo3(get)]
     pub available_to_lay: Vec<PriceSize>,
     #[pyo3(get)]
     pub traded_volume: Vec<PriceSize>,
+    // the number of atb/atl levels visible from python, the full ladder is kept
+    pub ladder_depth: Option<usize>,
 }
 
+impl Pickle for RunnerBookEXMut {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.available_to_back.encode(e, py);
+        self.available_to_lay.encode(e, py);
+        self.traded_volume.encode(e, py);
+        self.ladder_depth.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            available_to_back: Pickle::decode(d, py)?,
+            available_to_lay: Pickle::decode(d, py)?,
+            traded_volume: Pickle::decode(d, py)?,
+            ladder_depth: Pickle::decode(d, py)?,
+        })
+    }
+}
+
+#[pymethods]
 impl RunnerBookEXMut {
-    pub fn clear(&mut self) {
-        self.available_to_back.clear();
-        self.available_to_lay.clear();
-        self.traded_volume.clear();
+    #[getter(available_to_back)]
+    fn get_available_to_back(&self, py: Python) -> PyObject {
+        analytics::top(&self.available_to_back, self.ladder_depth).py_rep(py)
+    }
+    #[getter(available_to_lay)]
+    fn get_available_to_lay(&self, py: Python) -> PyObject {
+        analytics::top(&self.available_to_lay, self.ladder_depth).py_rep(py)
+    }
+    #[getter(available_to_back_array)]
+    fn get_available_to_back_array(&self, py: Python) -> PyResult<PyObject> {
+        array::ladder(analytics::top(&self.available_to_back, self.ladder_depth), py)
+    }
+    #[getter(available_to_lay_array)]
+    fn get_available_to_lay_array(&self, py: Python) -> PyResult<PyObject> {
+        array::ladder(analytics::top(&self.available_to_lay, self.ladder_depth), py)
+    }
+    #[getter(traded_volume_array)]
+    fn get_traded_volume_array(&self, py: Python) -> PyResult<PyObject> {
+        array::ladder(&self.traded_volume, py)
+    }
+    #[getter(best_back)]
+    fn get_best_back(&self) -> Option<PriceSize> {
+        analytics::best(&self.available_to_back)
+    }
+    #[getter(best_lay)]
+    fn get_best_lay(&self) -> Option<PriceSize> {
+        analytics::best(&self.available_to_lay)
+    }
+    #[getter(spread)]
+    fn get_spread(&self) -> Option<i32> {
+        analytics::spread(&self.available_to_back, &self.available_to_lay)
+    }
+    #[getter(wap)]
+    fn get_wap(&self) -> Option<f64> {
+        analytics::wap(&self.traded_volume)
+    }
+
+    fn volume_at_price(&self, price: f64) -> f64 {
+        analytics::volume_at_price(&self.traded_volume, price)
+    }
+    #[args(ticks = "3")]
+    fn back_depth(&self, ticks: u32) -> f64 {
+        analytics::depth(&self.available_to_back, ticks)
+    }
+    #[args(ticks = "3")]
+    fn lay_depth(&self, ticks: u32) -> f64 {
+        analytics::depth(&self.available_to_lay, ticks)
     }
 }
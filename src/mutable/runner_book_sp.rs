@@ -1,3 +1,4 @@
+use crate::pickle::{Decoder, Encoder, Pickle};
 use crate::price_size::PriceSize;
 use pyo3::prelude::*;
 
@@ -16,12 +17,21 @@ pub struct RunnerBookSPMut {
     pub lay_liability_taken: Vec<PriceSize>,
 }
 
-impl RunnerBookSPMut {
-    pub fn clear(&mut self) {
-        self.back_stake_taken.clear();
-        self.lay_liability_taken.clear();
-        self.actual_sp = None;
-        self.near_price = None;
-        self.far_price = None;
+impl Pickle for RunnerBookSPMut {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.far_price.encode(e, py);
+        self.near_price.encode(e, py);
+        self.actual_sp.encode(e, py);
+        self.back_stake_taken.encode(e, py);
+        self.lay_liability_taken.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(Self {
+            far_price: Pickle::decode(d, py)?,
+            near_price: Pickle::decode(d, py)?,
+            actual_sp: Pickle::decode(d, py)?,
+            back_stake_taken: Pickle::decode(d, py)?,
+            lay_liability_taken: Pickle::decode(d, py)?,
+        })
     }
 }
@@ -0,0 +1,355 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use pyo3::PyClass;
+use serde::de::{value, Deserialize, IntoDeserializer};
+use std::sync::Arc;
+
+use crate::bflw::market_book::MarketBook;
+use crate::datetime::{DateTime, DateTimeString};
+use crate::enums::{
+    ChangeType, MarketBettingType, MarketStatus, PriceLadderDefinition, SelectionStatus,
+};
+use crate::ids::{Clk, SelectionID};
+use crate::immutable::container::SyncObj;
+use crate::immutable::market::Market;
+use crate::immutable::runner::Runner;
+use crate::immutable::runner_book_ex::RunnerBookEX;
+use crate::mutable::market::MarketMut;
+use crate::mutable::runner::Runner as RunnerMut;
+use crate::price_size::PriceSize;
+use crate::strings::FixedSizeString;
+
+// The state handed to pickle: a tag for the type followed by its fields in declaration order,
+// numbers little endian and strings/sequences prefixed with their u32 length
+#[derive(Default)]
+pub struct Encoder(Vec<u8>);
+
+impl Encoder {
+    pub fn put(&mut self, bs: &[u8]) {
+        self.0.extend_from_slice(bs);
+    }
+
+    pub fn put_len(&mut self, n: usize) {
+        self.put(&(n as u32).to_le_bytes());
+    }
+}
+
+pub struct Decoder<'a>(&'a [u8]);
+
+impl<'a> Decoder<'a> {
+    pub fn take<const N: usize>(&mut self) -> PyResult<[u8; N]> {
+        let bs = self.bytes(N)?;
+        Ok(bs.try_into().unwrap())
+    }
+
+    pub fn bytes(&mut self, n: usize) -> PyResult<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(invalid());
+        }
+        let (bs, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(bs)
+    }
+
+    pub fn take_len(&mut self) -> PyResult<usize> {
+        Ok(u32::from_le_bytes(self.take()?) as usize)
+    }
+
+    pub fn str(&mut self) -> PyResult<&'a str> {
+        let n = self.take_len()?;
+        std::str::from_utf8(self.bytes(n)?).map_err(|_| invalid())
+    }
+}
+
+pub fn invalid() -> PyErr {
+    PyValueError::new_err("invalid pickle state")
+}
+
+pub trait Pickle: Sized {
+    fn encode(&self, e: &mut Encoder, py: Python);
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self>;
+}
+
+macro_rules! pickle_num {
+    ($($t:ty),+) => {
+        $(
+            impl Pickle for $t {
+                fn encode(&self, e: &mut Encoder, _py: Python) {
+                    e.put(&self.to_le_bytes());
+                }
+                fn decode(d: &mut Decoder, _py: Python) -> PyResult<Self> {
+                    Ok(<$t>::from_le_bytes(d.take()?))
+                }
+            }
+        )+
+    };
+}
+
+pickle_num!(u8, u16, u32, u64, f32, f64);
+
+// written as a u64 so the state doesn't depend on the platform
+impl Pickle for usize {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        (*self as u64).encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        u64::decode(d, py).map(|n| n as usize)
+    }
+}
+
+// enums are written as their index into the list of variants
+macro_rules! pickle_enum {
+    ($t:ty, [$($v:expr),+ $(,)?]) => {
+        #[allow(deprecated)]
+        impl Pickle for $t {
+            fn encode(&self, e: &mut Encoder, _py: Python) {
+                e.put(&[*self as u8]);
+            }
+            fn decode(d: &mut Decoder, _py: Python) -> PyResult<Self> {
+                const VARIANTS: &[$t] = &[$($v),+];
+                let [i] = d.take::<1>()?;
+                VARIANTS.get(i as usize).copied().ok_or_else(invalid)
+            }
+        }
+    };
+}
+
+pickle_enum!(
+    MarketStatus,
+    [
+        MarketStatus::InActive,
+        MarketStatus::Open,
+        MarketStatus::Suspended,
+        MarketStatus::Closed,
+    ]
+);
+pickle_enum!(
+    SelectionStatus,
+    [
+        SelectionStatus::Active,
+        SelectionStatus::Removed,
+        SelectionStatus::RemovedVacant,
+        SelectionStatus::Winner,
+        SelectionStatus::Placed,
+        SelectionStatus::Loser,
+        SelectionStatus::Hidden,
+    ]
+);
+pickle_enum!(
+    MarketBettingType,
+    [
+        MarketBettingType::Odds,
+        MarketBettingType::Line,
+        MarketBettingType::Range,
+        MarketBettingType::AsianHandicapDoubleLine,
+        MarketBettingType::AsianHandicapSingleLine,
+        MarketBettingType::FixedOdds,
+    ]
+);
+pickle_enum!(
+    PriceLadderDefinition,
+    [
+        PriceLadderDefinition::Classic,
+        PriceLadderDefinition::Finest,
+        PriceLadderDefinition::LineRange,
+    ]
+);
+pickle_enum!(
+    ChangeType,
+    [
+        ChangeType::SubImage,
+        ChangeType::ResubDelta,
+        ChangeType::Heartbeat,
+    ]
+);
+
+impl Pickle for bool {
+    fn encode(&self, e: &mut Encoder, _py: Python) {
+        e.put(&[*self as u8]);
+    }
+    fn decode(d: &mut Decoder, _py: Python) -> PyResult<Self> {
+        let [b] = d.take::<1>()?;
+        Ok(b != 0)
+    }
+}
+
+impl<T: Pickle> Pickle for Option<T> {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        match self {
+            Some(v) => {
+                e.put(&[1]);
+                v.encode(e, py);
+            }
+            None => e.put(&[0]),
+        }
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        match bool::decode(d, py)? {
+            true => Ok(Some(T::decode(d, py)?)),
+            false => Ok(None),
+        }
+    }
+}
+
+impl<T: Pickle> Pickle for Vec<T> {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        e.put_len(self.len());
+        self.iter().for_each(|v| v.encode(e, py));
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        let n = d.take_len()?;
+        (0..n).map(|_| T::decode(d, py)).collect()
+    }
+}
+
+impl Pickle for String {
+    fn encode(&self, e: &mut Encoder, _py: Python) {
+        e.put_len(self.len());
+        e.put(self.as_bytes());
+    }
+    fn decode(d: &mut Decoder, _py: Python) -> PyResult<Self> {
+        d.str().map(str::to_owned)
+    }
+}
+
+impl Pickle for Arc<str> {
+    fn encode(&self, e: &mut Encoder, _py: Python) {
+        e.put_len(self.len());
+        e.put(self.as_bytes());
+    }
+    fn decode(d: &mut Decoder, _py: Python) -> PyResult<Self> {
+        d.str().map(Arc::from)
+    }
+}
+
+impl<T: Pickle> Pickle for Arc<Vec<T>> {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.as_ref().encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Vec::<T>::decode(d, py).map(Arc::new)
+    }
+}
+
+impl<T: Pickle> Pickle for SyncObj<T> {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        (**self).encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        T::decode(d, py).map(SyncObj::new)
+    }
+}
+
+impl<T: Pickle + PyClass> Pickle for Py<T> {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.borrow(py).encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Py::new(py, T::decode(d, py)?)
+    }
+}
+
+impl<const N: usize> Pickle for FixedSizeString<N> {
+    fn encode(&self, e: &mut Encoder, _py: Python) {
+        e.put(self.as_str().as_bytes());
+    }
+    fn decode(d: &mut Decoder, _py: Python) -> PyResult<Self> {
+        let s = std::str::from_utf8(d.bytes(N)?).map_err(|_| invalid())?;
+        FixedSizeString::try_from(s).map_err(|_| invalid())
+    }
+}
+
+impl Pickle for DateTimeString {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.as_fs_str().encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        DateTimeString::try_from(FixedSizeString::decode(d, py)?).map_err(|_| invalid())
+    }
+}
+
+impl Pickle for DateTime {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        (**self).encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        u64::decode(d, py).map(DateTime::new)
+    }
+}
+
+impl Pickle for Clk {
+    fn encode(&self, e: &mut Encoder, _py: Python) {
+        e.put_len(self.as_str().len());
+        e.put(self.as_str().as_bytes());
+    }
+    fn decode(d: &mut Decoder, _py: Python) -> PyResult<Self> {
+        let de: value::StrDeserializer<value::Error> = d.str()?.into_deserializer();
+        Clk::deserialize(de).map_err(|_| invalid())
+    }
+}
+
+impl Pickle for SelectionID {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.id().encode(e, py);
+        self.handicap().encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(SelectionID::from((
+            u32::decode(d, py)?,
+            Option::decode(d, py)?,
+        )))
+    }
+}
+
+impl Pickle for PriceSize {
+    fn encode(&self, e: &mut Encoder, py: Python) {
+        self.price.encode(e, py);
+        self.size.encode(e, py);
+    }
+    fn decode(d: &mut Decoder, py: Python) -> PyResult<Self> {
+        Ok(PriceSize::new(f64::decode(d, py)?, f64::decode(d, py)?))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Kind {
+    PriceSize,
+    RunnerBookEX,
+    Runner,
+    Market,
+    MarketBook,
+    RunnerMut,
+    MarketMut,
+}
+
+// the (callable, args) pair returned from __reduce__
+pub fn reduce<T: Pickle>(kind: Kind, value: &T, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+    let mut e = Encoder::default();
+    e.put(&[kind as u8]);
+    value.encode(&mut e, py);
+
+    let unpickle = py.import("betfair_data")?.getattr("_unpickle")?;
+    Ok((unpickle.into_py(py), (PyBytes::new(py, &e.0).into_py(py),)))
+}
+
+#[pyfunction]
+pub fn _unpickle(state: &[u8], py: Python) -> PyResult<PyObject> {
+    fn new<T: Pickle + PyClass>(d: &mut Decoder, py: Python) -> PyResult<PyObject> {
+        Ok(Py::<T>::decode(d, py)?.into_py(py))
+    }
+
+    let mut d = Decoder(state);
+    let [kind] = d.take::<1>()?;
+
+    match kind {
+        k if k == Kind::PriceSize as u8 => new::<PriceSize>(&mut d, py),
+        k if k == Kind::RunnerBookEX as u8 => new::<RunnerBookEX>(&mut d, py),
+        k if k == Kind::Runner as u8 => new::<Runner>(&mut d, py),
+        k if k == Kind::Market as u8 => new::<Market>(&mut d, py),
+        k if k == Kind::MarketBook as u8 => new::<MarketBook>(&mut d, py),
+        k if k == Kind::RunnerMut as u8 => new::<RunnerMut>(&mut d, py),
+        k if k == Kind::MarketMut as u8 => new::<MarketMut>(&mut d, py),
+        _ => Err(invalid()),
+    }
+}
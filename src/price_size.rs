@@ -1,10 +1,13 @@
 use pyo3::prelude::*;
 use serde::{
     de::{Error, MapAccess, SeqAccess, Visitor},
-    Deserialize, Deserializer,
+    ser::SerializeTuple,
+    Deserialize, Deserializer, Serialize, Serializer,
 };
 use std::cmp::PartialEq;
 
+use crate::pickle::{self, Kind};
+
 use std::fmt;
 
 /**
@@ -54,6 +57,18 @@ impl PriceSize {
     fn __repr__(&self) -> String {
         format!("<PriceSize [{:.2},{:.2}]>", self.price, self.size)
     }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        pickle::reduce(Kind::PriceSize, self, py)
+    }
+
+    fn __copy__(&self) -> Self {
+        *self
+    }
+
+    fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        *self
+    }
 }
 
 #[derive(Debug, Copy, Clone, Default)]
@@ -65,6 +80,12 @@ impl From<F64OrStr> for f64 {
     }
 }
 
+impl From<f64> for F64OrStr {
+    fn from(v: f64) -> F64OrStr {
+        F64OrStr(v)
+    }
+}
+
 impl std::ops::Deref for F64OrStr {
     type Target = f64;
 
@@ -118,6 +139,33 @@ impl<'de> Deserialize<'de> for F64OrStr {
     }
 }
 
+// json has no nan or infinity, so they're written as the strings the stream uses
+impl Serialize for F64OrStr {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.0 {
+            f if f.is_nan() => serializer.serialize_str("NaN"),
+            f if f == f64::INFINITY => serializer.serialize_str("Infinity"),
+            f if f == f64::NEG_INFINITY => serializer.serialize_str("-Infinity"),
+            f => serializer.serialize_f64(f),
+        }
+    }
+}
+
+impl Serialize for PriceSize {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut tup = serializer.serialize_tuple(2)?;
+        tup.serialize_element(&F64OrStr(self.price))?;
+        tup.serialize_element(&F64OrStr(self.size))?;
+        tup.end()
+    }
+}
+
 impl<'de> Deserialize<'de> for PriceSize {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -213,4 +261,16 @@ mod tests {
         let pv: PriceSize = serde_json::from_str(raw).expect("failed to serialize");
         assert!(pv.price == 2.0 && pv.size.is_nan());
     }
+
+    #[test]
+    fn test_pricesize_serialize() {
+        let raw = serde_json::to_string(&PriceSize::new(2.5, 10.0)).unwrap();
+        assert_eq!(raw, "[2.5,10.0]");
+
+        let raw = serde_json::to_string(&PriceSize::new(f64::INFINITY, f64::NAN)).unwrap();
+        assert_eq!(raw, r#"["Infinity","NaN"]"#);
+
+        let pv: PriceSize = serde_json::from_str(&raw).unwrap();
+        assert!(pv.price == f64::INFINITY && pv.size.is_nan());
+    }
 }
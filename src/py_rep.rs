@@ -12,6 +12,12 @@ pub trait PyRep {
 }
 
 impl PyRep for Vec<PriceSize> {
+    fn py_rep(&self, py: Python) -> PyObject {
+        self.as_slice().py_rep(py)
+    }
+}
+
+impl PyRep for [PriceSize] {
     fn py_rep(&self, py: Python) -> PyObject {
         PyList::new(py, self.iter().map(|ps| ps.into_py(py))).into_py(py)
     }
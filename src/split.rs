@@ -0,0 +1,195 @@
+use log::warn;
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde_json::value::RawValue;
+use simdutf8::basic::from_utf8;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+// the top level fields of a stream line, left raw so they can be written back out. Keys borrow
+// from the line unless they hold escapes, which have to be unescaped.
+struct Line<'a>(Vec<(Cow<'a, str>, &'a RawValue)>);
+
+#[derive(serde::Deserialize)]
+struct Key<'a>(#[serde(borrow)] Cow<'a, str>);
+
+impl<'de> Deserialize<'de> for Line<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct LineVisitor;
+        impl<'de> Visitor<'de> for LineVisitor {
+            type Value = Line<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a stream message")
+            }
+
+            fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
+            where
+                V: MapAccess<'de>,
+            {
+                let mut fields = Vec::with_capacity(8);
+                while let Some((Key(k), v)) = map.next_entry::<Key, &RawValue>()? {
+                    fields.push((k, v));
+                }
+
+                Ok(Line(fields))
+            }
+        }
+
+        deserializer.deserialize_map(LineVisitor)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct McId<'a> {
+    id: &'a str,
+}
+
+// Demultiplexes a file holding several markets into one stream per market, in the order the
+// markets first appear. Each mc entry is written into its own market's stream, with the rest
+// of the line (op, clk, pt, ct ...) copied alongside it. Lines without an mc, such as
+// heartbeats, are copied into every market seen so far.
+pub fn split_markets(path: &Path, bs: &[u8]) -> Result<Vec<(PathBuf, Vec<u8>)>, Error> {
+    let s = from_utf8(bs).map_err(|_| Error::from(ErrorKind::InvalidData))?;
+
+    let mut markets: Vec<(&str, Vec<u8>)> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+
+    for line in serde_json::Deserializer::from_str(s).into_iter::<Line>() {
+        let r: Result<(), serde_json::Error> = try {
+            let line = line?;
+
+            match line.0.iter().find(|(k, _)| k == "mc") {
+                Some((_, mc)) => {
+                    let mut groups: Vec<(usize, Vec<&RawValue>)> = Vec::new();
+
+                    for entry in serde_json::from_str::<Vec<&RawValue>>(mc.get())? {
+                        let McId { id } = serde_json::from_str(entry.get())?;
+                        let i = *index.entry(id).or_insert_with(|| {
+                            markets.push((id, Vec::new()));
+                            markets.len() - 1
+                        });
+
+                        match groups.iter_mut().find(|(gi, _)| *gi == i) {
+                            Some((_, entries)) => entries.push(entry),
+                            None => groups.push((i, vec![entry])),
+                        }
+                    }
+
+                    for (i, entries) in groups {
+                        write_line(&mut markets[i].1, &line, Some(&entries));
+                    }
+                }
+                None => markets
+                    .iter_mut()
+                    .for_each(|(_, buf)| write_line(buf, &line, None)),
+            }
+        };
+
+        if let Err(err) = r {
+            warn!(target: "betfair_data", "file: {} err: (JSON Parse Error) {}", path.to_string_lossy(), err);
+            break;
+        }
+    }
+
+    Ok(markets
+        .into_iter()
+        .map(|(id, buf)| (path.join(id), buf))
+        .collect())
+}
+
+// A key as it goes back between quotes. Keys were unescaped when they were read, so any that
+// need it are escaped again, which may not match how the line escaped them.
+fn escaped_key(k: &str) -> Cow<'_, str> {
+    if k.bytes().any(|b| b == b'"' || b == b'\\' || b < 0x20) {
+        let quoted = serde_json::to_string(k).unwrap();
+        Cow::Owned(quoted[1..quoted.len() - 1].to_owned())
+    } else {
+        Cow::Borrowed(k)
+    }
+}
+
+fn write_line(buf: &mut Vec<u8>, line: &Line, mc: Option<&[&RawValue]>) {
+    buf.push(b'{');
+
+    for (n, (k, v)) in line.0.iter().enumerate() {
+        if n > 0 {
+            buf.push(b',');
+        }
+        buf.push(b'"');
+        buf.extend_from_slice(escaped_key(k).as_bytes());
+        buf.extend_from_slice(b"\":");
+
+        match mc {
+            Some(entries) if k == "mc" => {
+                buf.push(b'[');
+                for (n, e) in entries.iter().enumerate() {
+                    if n > 0 {
+                        buf.push(b',');
+                    }
+                    buf.extend_from_slice(e.get().as_bytes());
+                }
+                buf.push(b']');
+            }
+            _ => buf.extend_from_slice(v.get().as_bytes()),
+        }
+    }
+
+    buf.extend_from_slice(b"}\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_by_market_id() {
+        let raw = r#"{"op":"mcm","clk":"1","pt":1,"mc":[{"id":"1.100000001","rc":[{"id":1}]},{"id":"1.100000002","rc":[]}]}
+{"op":"mcm","clk":"2","pt":2,"ct":"HEARTBEAT"}
+{"op":"mcm","clk":"3","pt":3,"mc":[{"id":"1.100000002","tv":5},{"id":"1.100000002","con":true}]}
+"#;
+
+        let split = split_markets(Path::new("stream.json"), raw.as_bytes()).unwrap();
+        assert_eq!(split.len(), 2);
+
+        let (name, buf) = &split[0];
+        assert_eq!(name, &Path::new("stream.json").join("1.100000001"));
+        assert_eq!(
+            std::str::from_utf8(buf).unwrap(),
+            r#"{"op":"mcm","clk":"1","pt":1,"mc":[{"id":"1.100000001","rc":[{"id":1}]}]}
+{"op":"mcm","clk":"2","pt":2,"ct":"HEARTBEAT"}
+"#
+        );
+
+        let (name, buf) = &split[1];
+        assert_eq!(name, &Path::new("stream.json").join("1.100000002"));
+        assert_eq!(
+            std::str::from_utf8(buf).unwrap(),
+            r#"{"op":"mcm","clk":"1","pt":1,"mc":[{"id":"1.100000002","rc":[]}]}
+{"op":"mcm","clk":"2","pt":2,"ct":"HEARTBEAT"}
+{"op":"mcm","clk":"3","pt":3,"mc":[{"id":"1.100000002","tv":5},{"id":"1.100000002","con":true}]}
+"#
+        );
+    }
+
+    #[test]
+    fn split_escaped_keys() {
+        let raw = r#"{"op":"mcm","x\"y":1,"\u0063lk":"1","mc":[{"id":"1.100000001"}]}
+{"op":"mcm","clk":"2","mc":[{"id":"1.100000001"}]}
+"#;
+
+        let split = split_markets(Path::new("stream.json"), raw.as_bytes()).unwrap();
+        assert_eq!(split.len(), 1);
+        assert_eq!(
+            std::str::from_utf8(&split[0].1).unwrap(),
+            r#"{"op":"mcm","x\"y":1,"clk":"1","mc":[{"id":"1.100000001"}]}
+{"op":"mcm","clk":"2","mc":[{"id":"1.100000001"}]}
+"#
+        );
+    }
+}
@@ -0,0 +1,582 @@
+use pyo3::prelude::*;
+use pyo3::types::PySequence;
+use serde::de::{Deserializer, Visitor};
+use serde::Deserialize;
+use simdutf8::basic::from_utf8;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+use crate::errors::IOErr;
+use crate::files::{self, FilesSource};
+
+// A single data quality problem found in a stream file. market_id, selection_id and
+// publish_time are set when the problem belongs to a market, runner or update.
+#[pyclass(name = "Issue")]
+#[derive(Debug, Clone)]
+pub struct Issue {
+    #[pyo3(get)]
+    pub file: String,
+    #[pyo3(get)]
+    pub line: Option<usize>,
+    #[pyo3(get)]
+    pub kind: &'static str,
+    #[pyo3(get)]
+    pub market_id: Option<String>,
+    #[pyo3(get)]
+    pub selection_id: Option<u32>,
+    #[pyo3(get)]
+    pub handicap: Option<f64>,
+    #[pyo3(get)]
+    pub publish_time: Option<u64>,
+    #[pyo3(get)]
+    pub message: String,
+}
+
+#[pymethods]
+impl Issue {
+    fn __repr__(&self) -> String {
+        let line = self.line.map(|l| format!(":{}", l)).unwrap_or_default();
+        match &self.market_id {
+            Some(id) => format!(
+                "{}{} {} ({}) {}",
+                self.file, line, id, self.kind, self.message
+            ),
+            None => format!("{}{} ({}) {}", self.file, line, self.kind, self.message),
+        }
+    }
+}
+
+pub const IO_ERROR: &str = "io_error";
+pub const PARSE_ERROR: &str = "parse_error";
+pub const TRUNCATED: &str = "truncated";
+pub const NON_MONOTONIC_PT: &str = "non_monotonic_pt";
+pub const MISSING_DEFINITION: &str = "missing_definition";
+pub const VERSION_REGRESSION: &str = "version_regression";
+pub const UNKNOWN_RUNNER: &str = "unknown_runner";
+pub const INVALID_SIZE: &str = "invalid_size";
+pub const CROSSED_BOOK: &str = "crossed_book";
+pub const TRADED_VOLUME_DECREASED: &str = "traded_volume_decreased";
+pub const NOT_CLOSED: &str = "not_closed";
+
+// Replays each stream file (archives are expanded the same as Files) and returns every issue
+// found. Unlike the readers a bad line doesn't end the file, it's reported and the rest of
+// the file still checked.
+#[pyfunction]
+pub fn validate(paths: &PySequence, py: Python) -> Vec<Issue> {
+    let paths = FilesSource::get_paths(paths);
+
+    py.allow_threads(move || {
+        files::stream_files(paths)
+            .flat_map(|r| match r {
+                Ok((path, bs)) => validate_file(&path, &bs),
+                Err(IOErr { file, err }) => vec![Issue {
+                    file: file
+                        .map(|f| f.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    line: None,
+                    kind: IO_ERROR,
+                    market_id: None,
+                    selection_id: None,
+                    handicap: None,
+                    publish_time: None,
+                    message: err.to_string(),
+                }],
+            })
+            .collect()
+    })
+}
+
+pub fn validate_file(path: &Path, bs: &[u8]) -> Vec<Issue> {
+    let mut v = Validator {
+        file: path.to_string_lossy().into_owned(),
+        issues: Vec::new(),
+    };
+
+    let s = match from_utf8(bs) {
+        Ok(s) => s,
+        Err(_) => {
+            v.file_issue(None, PARSE_ERROR, "file is not valid utf8".to_owned());
+            return v.issues;
+        }
+    };
+
+    let mut markets: Vec<MarketState> = Vec::new();
+    let mut index: HashMap<Cow<str>, usize> = HashMap::new();
+
+    let lines: Vec<(usize, &str)> = s
+        .lines()
+        .enumerate()
+        .map(|(n, l)| (n + 1, l))
+        .filter(|(_, l)| !l.trim().is_empty())
+        .collect();
+
+    for (i, &(n, l)) in lines.iter().enumerate() {
+        let msg: Msg = match serde_json::from_str(l) {
+            Ok(msg) => msg,
+            // a line cut short at the end of the file is a truncated write, anywhere else
+            // the line is just broken
+            Err(err) if err.is_eof() && i == lines.len() - 1 => {
+                v.file_issue(
+                    Some(n),
+                    TRUNCATED,
+                    format!("last line is incomplete: {}", err),
+                );
+                continue;
+            }
+            Err(err) => {
+                v.file_issue(Some(n), PARSE_ERROR, err.to_string());
+                continue;
+            }
+        };
+
+        for mc in msg.mc.into_iter().flatten() {
+            let pos = *index.entry(mc.id.clone()).or_insert_with(|| {
+                markets.push(MarketState::new(mc.id.clone()));
+                markets.len() - 1
+            });
+
+            markets[pos].update(&mut v, n, msg.pt, mc);
+        }
+    }
+
+    for m in &markets {
+        if m.status != Some("CLOSED") {
+            let status = m.status.unwrap_or("no definition");
+            v.issue(
+                &m.at(None, None),
+                NOT_CLOSED,
+                format!("file ends with the market {}", status),
+            );
+        }
+    }
+
+    v.issues
+}
+
+struct Validator {
+    file: String,
+    issues: Vec<Issue>,
+}
+
+// where in the file an issue was found
+struct At<'a> {
+    line: Option<usize>,
+    market_id: Cow<'a, str>,
+    runner: Option<RunnerKey>,
+    pt: Option<u64>,
+}
+
+impl Validator {
+    fn file_issue(&mut self, line: Option<usize>, kind: &'static str, message: String) {
+        self.issues.push(Issue {
+            file: self.file.clone(),
+            line,
+            kind,
+            market_id: None,
+            selection_id: None,
+            handicap: None,
+            publish_time: None,
+            message,
+        });
+    }
+
+    fn issue(&mut self, at: &At, kind: &'static str, message: String) {
+        self.issues.push(Issue {
+            file: self.file.clone(),
+            line: at.line,
+            kind,
+            market_id: Some(at.market_id.to_string()),
+            selection_id: at.runner.map(|(id, _)| id),
+            handicap: at.runner.and_then(|(_, hc)| hc.map(f64::from_bits)),
+            publish_time: at.pt,
+            message,
+        });
+    }
+}
+
+// selection id and handicap bits
+type RunnerKey = (u32, Option<u64>);
+
+#[inline]
+fn runner_key(id: u32, hc: Option<f64>) -> RunnerKey {
+    (id, hc.map(f64::to_bits))
+}
+
+// ladder prices in hundredths
+#[inline]
+fn cents(price: f64) -> i64 {
+    (price * 100.0).round() as i64
+}
+
+// a size of 0 clears the level
+#[inline]
+fn set_level(book: &mut BTreeMap<i64, f64>, price: f64, size: f64) {
+    match size == 0.0 {
+        true => book.remove(&cents(price)),
+        false => book.insert(cents(price), size),
+    };
+}
+
+#[derive(Default)]
+struct RunnerState {
+    atb: BTreeMap<i64, f64>,
+    atl: BTreeMap<i64, f64>,
+    trd: BTreeMap<i64, f64>,
+    tv: Option<f64>,
+    crossed: bool,
+}
+
+struct MarketState<'a> {
+    id: Cow<'a, str>,
+    pt: Option<u64>,
+    version: Option<u64>,
+    status: Option<&'a str>,
+    tv: Option<f64>,
+    definition: Option<HashSet<RunnerKey>>,
+    runners: HashMap<RunnerKey, RunnerState>,
+    reported_missing: bool,
+    reported_unknown: HashSet<RunnerKey>,
+}
+
+impl<'a> MarketState<'a> {
+    fn new(id: Cow<'a, str>) -> Self {
+        Self {
+            id,
+            pt: None,
+            version: None,
+            status: None,
+            tv: None,
+            definition: None,
+            runners: HashMap::new(),
+            reported_missing: false,
+            reported_unknown: HashSet::new(),
+        }
+    }
+
+    fn at(&self, line: Option<usize>, runner: Option<RunnerKey>) -> At<'a> {
+        At {
+            line,
+            market_id: self.id.clone(),
+            runner,
+            pt: self.pt,
+        }
+    }
+
+    fn update(&mut self, v: &mut Validator, line: usize, pt: Option<u64>, mc: Mc<'a>) {
+        if let Some(pt) = pt {
+            if let Some(prev) = self.pt && pt < prev {
+                let at = At {
+                    pt: Some(pt),
+                    ..self.at(Some(line), None)
+                };
+                v.issue(
+                    &at,
+                    NON_MONOTONIC_PT,
+                    format!("pt {} is before the previous pt {}", pt, prev),
+                );
+            }
+            self.pt = Some(pt);
+        }
+
+        if let Some(def) = mc.definition {
+            if let Some(version) = def.version {
+                if let Some(prev) = self.version && version < prev {
+                    v.issue(
+                        &self.at(Some(line), None),
+                        VERSION_REGRESSION,
+                        format!(
+                            "definition version {} is before the previous version {}",
+                            version, prev
+                        ),
+                    );
+                }
+                self.version = Some(version);
+            }
+
+            self.status = def.status.or(self.status);
+            self.definition = Some(def.runners.iter().map(|r| runner_key(r.id, r.hc)).collect());
+        }
+
+        // the ladders are still followed, so the checks after the definition arrives see the
+        // whole book
+        if self.definition.is_none() && !self.reported_missing {
+            self.reported_missing = true;
+            v.issue(
+                &self.at(Some(line), None),
+                MISSING_DEFINITION,
+                "market updated before its market definition".to_owned(),
+            );
+        }
+
+        if mc.img {
+            self.runners.clear();
+            self.tv = None;
+        }
+
+        if let Some(tv) = mc.tv {
+            self.check_tv(v, line, tv);
+        }
+
+        for rc in mc.rc {
+            self.update_runner(v, line, rc);
+        }
+    }
+
+    fn update_runner(&mut self, v: &mut Validator, line: usize, rc: Rc) {
+        let key = runner_key(rc.id, rc.hc);
+        let at = self.at(Some(line), Some(key));
+
+        // without a definition yet there's nothing to know the runner from, that's reported once
+        // as the missing definition
+        let known = self.definition.as_ref().map_or(true, |d| d.contains(&key));
+        if !known && self.reported_unknown.insert(key) {
+            v.issue(
+                &at,
+                UNKNOWN_RUNNER,
+                "runner change for a runner not in the market definition".to_owned(),
+            );
+        }
+
+        let mut runner = self.runners.remove(&key).unwrap_or_default();
+
+        for (name, ladder) in [
+            ("atb", &rc.atb),
+            ("atl", &rc.atl),
+            ("trd", &rc.trd),
+            ("spb", &rc.spb),
+            ("spl", &rc.spl),
+        ] {
+            for &(Num(price), Num(size)) in ladder.iter().flatten() {
+                if size.is_nan() || size < 0.0 {
+                    v.issue(
+                        &at,
+                        INVALID_SIZE,
+                        format!("{} size {} at price {}", name, size, price),
+                    );
+                    continue;
+                }
+
+                match name {
+                    "atb" => set_level(&mut runner.atb, price, size),
+                    "atl" => set_level(&mut runner.atl, price, size),
+                    "trd" => {
+                        if let Some(prev) = runner.trd.insert(cents(price), size) && size < prev {
+                            v.issue(
+                                &at,
+                                TRADED_VOLUME_DECREASED,
+                                format!(
+                                    "traded volume at {} fell from {} to {}",
+                                    price, prev, size
+                                ),
+                            );
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(Num(tv)) = rc.tv {
+            match runner.tv {
+                Some(prev) if tv < prev => v.issue(
+                    &at,
+                    TRADED_VOLUME_DECREASED,
+                    format!("runner tv fell from {} to {}", prev, tv),
+                ),
+                _ => {}
+            }
+            runner.tv = Some(tv);
+        }
+
+        let back = runner.atb.keys().next_back();
+        let lay = runner.atl.keys().next();
+        match (back, lay) {
+            (Some(&back), Some(&lay)) if back >= lay => {
+                if !runner.crossed {
+                    v.issue(
+                        &at,
+                        CROSSED_BOOK,
+                        format!(
+                            "best back {} is at or above best lay {}",
+                            back as f64 / 100.0,
+                            lay as f64 / 100.0
+                        ),
+                    );
+                }
+                runner.crossed = true;
+            }
+            _ => runner.crossed = false,
+        }
+
+        self.runners.insert(key, runner);
+    }
+
+    fn check_tv(&mut self, v: &mut Validator, line: usize, Num(tv): Num) {
+        if let Some(prev) = self.tv && tv < prev {
+            v.issue(
+                &self.at(Some(line), None),
+                TRADED_VOLUME_DECREASED,
+                format!("market tv fell from {} to {}", prev, tv),
+            );
+        }
+        self.tv = Some(tv);
+    }
+}
+
+#[derive(Deserialize)]
+struct Msg<'a> {
+    pt: Option<u64>,
+    #[serde(borrow)]
+    mc: Option<Vec<Mc<'a>>>,
+}
+
+#[derive(Deserialize)]
+struct Mc<'a> {
+    #[serde(borrow)]
+    id: Cow<'a, str>,
+    #[serde(default)]
+    img: bool,
+    #[serde(borrow, rename = "marketDefinition")]
+    definition: Option<Definition<'a>>,
+    #[serde(default)]
+    rc: Vec<Rc>,
+    tv: Option<Num>,
+}
+
+#[derive(Deserialize)]
+struct Definition<'a> {
+    version: Option<u64>,
+    status: Option<&'a str>,
+    #[serde(default)]
+    runners: Vec<DefinitionRunner>,
+}
+
+#[derive(Deserialize)]
+struct DefinitionRunner {
+    id: u32,
+    hc: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct Rc {
+    id: u32,
+    hc: Option<f64>,
+    atb: Option<Vec<(Num, Num)>>,
+    atl: Option<Vec<(Num, Num)>>,
+    trd: Option<Vec<(Num, Num)>>,
+    spb: Option<Vec<(Num, Num)>>,
+    spl: Option<Vec<(Num, Num)>>,
+    tv: Option<Num>,
+}
+
+// a number that may also be written as one of betfair's "NaN", "Infinity" strings
+#[derive(Clone, Copy)]
+struct Num(f64);
+
+impl<'de> Deserialize<'de> for Num {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct NumVisitor;
+        impl<'de> Visitor<'de> for NumVisitor {
+            type Value = Num;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a number")
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+                Ok(Num(v))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(Num(v as f64))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(Num(v as f64))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(Num(match v {
+                    "Infinity" => f64::INFINITY,
+                    "-Infinity" => f64::NEG_INFINITY,
+                    _ => v.parse().unwrap_or(f64::NAN),
+                }))
+            }
+        }
+
+        deserializer.deserialize_any(NumVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(raw: &str) -> Vec<(&'static str, Option<usize>)> {
+        validate_file(Path::new("test.json"), raw.as_bytes())
+            .into_iter()
+            .map(|i| (i.kind, i.line))
+            .collect()
+    }
+
+    #[test]
+    fn validate_clean_file() {
+        let raw = r#"{"op":"mcm","clk":"1","pt":1,"mc":[{"id":"1.100000001","img":true,"marketDefinition":{"version":1,"status":"OPEN","runners":[{"id":1}]},"rc":[{"id":1,"atb":[[2,5]],"atl":[[2.02,5]],"trd":[[2,1]],"tv":1}],"tv":1}]}
+{"op":"mcm","clk":"2","pt":2,"ct":"HEARTBEAT"}
+{"op":"mcm","clk":"3","pt":3,"mc":[{"id":"1.100000001","rc":[{"id":1,"atb":[[2,0]],"trd":[[2,3]],"tv":3}],"tv":3}]}
+{"op":"mcm","clk":"4","pt":4,"mc":[{"id":"1.100000001","marketDefinition":{"version":2,"status":"CLOSED","runners":[{"id":1}]}}]}
+"#;
+
+        assert_eq!(kinds(raw), vec![]);
+    }
+
+    #[test]
+    fn validate_reports_issues() {
+        let raw = r#"{"op":"mcm","clk":"1","pt":5,"mc":[{"id":"1.100000001","rc":[{"id":1,"atb":[[2,5]]}]}]}
+{"op":"mcm","clk":"2","pt":6,"mc":[{"id":"1.100000001","marketDefinition":{"version":2,"status":"OPEN","runners":[{"id":1}]},"rc":[{"id":1,"atb":[[2,5]],"trd":[[2,4]],"tv":4}],"tv":4}]}
+{"op":"mcm","clk":"3","pt":4,"mc":[{"id":"1.100000001","rc":[{"id":1,"atl":[[1.99,-1],[2,"NaN"],[2,3]]}]}]}
+{"op":"mcm","clk":"4","pt":7,"mc":[{"id":"1.100000001","rc":[{"id":2,"atb":[[3,1]]},{"id":1,"trd":[[2,2]],"tv":2}],"tv":2}]}
+{"op":"mcm","clk":"5"
+{"op":"mcm","clk":"6","pt":8,"mc":[{"id":"1.100000001","marketDefinition":{"version":1,"status":"SUSPENDED","runners":[{"id":1}]}}]}
+{"op":"mcm","clk":"7","pt":9,"mc":[{"id":"1.100000001","tv":5}"#;
+
+        assert_eq!(
+            kinds(raw),
+            vec![
+                (MISSING_DEFINITION, Some(1)),
+                (NON_MONOTONIC_PT, Some(3)),
+                (INVALID_SIZE, Some(3)),
+                (INVALID_SIZE, Some(3)),
+                (CROSSED_BOOK, Some(3)),
+                (TRADED_VOLUME_DECREASED, Some(4)),
+                (UNKNOWN_RUNNER, Some(4)),
+                (TRADED_VOLUME_DECREASED, Some(4)),
+                (TRADED_VOLUME_DECREASED, Some(4)),
+                (PARSE_ERROR, Some(5)),
+                (VERSION_REGRESSION, Some(6)),
+                (TRUNCATED, Some(7)),
+                (NOT_CLOSED, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_before_definition() {
+        // the first id is escaped, and its back price still crosses the lay sent with the
+        // definition
+        let raw = r#"{"op":"mcm","clk":"1","pt":1,"mc":[{"id":"1.1000\u00300001","rc":[{"id":1,"atb":[[2.5,5]]}]}]}
+{"op":"mcm","clk":"2","pt":2,"mc":[{"id":"1.100000001","marketDefinition":{"version":1,"status":"OPEN","runners":[{"id":1}]},"rc":[{"id":1,"atl":[[2.4,5]]}]}]}
+{"op":"mcm","clk":"3","pt":3,"mc":[{"id":"1.100000001","marketDefinition":{"version":2,"status":"CLOSED","runners":[{"id":1}]}}]}
+"#;
+
+        assert_eq!(
+            kinds(raw),
+            vec![(MISSING_DEFINITION, Some(1)), (CROSSED_BOOK, Some(2))]
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use bzip2::write::BzEncoder;
+use flate2::write::GzEncoder;
+use pyo3::exceptions::{PyIOError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use serde::Serialize;
+use serde_json::value::RawValue;
+use std::fs::File;
+use std::io::{self, BufWriter, IntoInnerError, Write};
+use std::path::{Path, PathBuf};
+
+use crate::bflw::market_book::MarketBook;
+use crate::immutable::market::Market;
+use crate::mutable::market::MarketMut;
+
+enum Encoder {
+    Plain(File),
+    Gz(GzEncoder<File>),
+    Bz2(BzEncoder<File>),
+}
+
+impl Encoder {
+    fn create(path: &Path, compression: Option<&str>) -> PyResult<Self> {
+        let compression = compression.or_else(|| match path.extension().and_then(|s| s.to_str()) {
+            Some("bz2") => Some("bz2"),
+            Some("gz") => Some("gz"),
+            _ => None,
+        });
+
+        let open = || File::create(path).map_err(|err| PyIOError::new_err(err.to_string()));
+
+        match compression {
+            Some("bz2") => Ok(Self::Bz2(BzEncoder::new(
+                open()?,
+                bzip2::Compression::default(),
+            ))),
+            Some("gz") => Ok(Self::Gz(GzEncoder::new(
+                open()?,
+                flate2::Compression::default(),
+            ))),
+            Some("none") | None => Ok(Self::Plain(open()?)),
+            Some(c) => Err(PyValueError::new_err(format!(
+                "unsupported compression: {}, expected bz2, gz or none",
+                c
+            ))),
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Plain(mut f) => f.flush(),
+            Self::Gz(e) => e.finish().map(drop),
+            Self::Bz2(e) => e.finish().map(drop),
+        }
+    }
+}
+
+impl Write for Encoder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(f) => f.write(buf),
+            Self::Gz(e) => e.write(buf),
+            Self::Bz2(e) => e.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(f) => f.flush(),
+            Self::Gz(e) => e.flush(),
+            Self::Bz2(e) => e.flush(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StreamLine<'a, T: Serialize> {
+    op: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    clk: Option<&'a str>,
+    pt: u64,
+    mc: T,
+}
+
+fn io_err<E: ToString>(err: E) -> PyErr {
+    PyIOError::new_err(err.to_string())
+}
+
+// Writes markets back out in the historic stream format, one mcm message per line, so the
+// files can be read again by File and Files
+#[pyclass(name = "StreamWriter")]
+pub struct StreamWriter {
+    #[pyo3(get)]
+    file_name: PathBuf,
+    out: Option<BufWriter<Encoder>>,
+}
+
+impl StreamWriter {
+    fn write_line<T: Serialize>(&mut self, clk: Option<&str>, pt: u64, mc: T) -> PyResult<()> {
+        let out = self
+            .out
+            .as_mut()
+            .ok_or_else(|| PyValueError::new_err("write to a closed StreamWriter"))?;
+
+        let line = StreamLine {
+            op: "mcm",
+            clk,
+            pt,
+            mc,
+        };
+
+        serde_json::to_writer(&mut *out, &line).map_err(io_err)?;
+        out.write_all(b"\n").map_err(io_err)
+    }
+}
+
+#[pymethods]
+impl StreamWriter {
+    #[new]
+    #[args(compression = "None")]
+    fn __new__(file: PathBuf, compression: Option<&str>) -> PyResult<Self> {
+        let encoder = Encoder::create(&file, compression)?;
+
+        Ok(Self {
+            file_name: file,
+            out: Some(BufWriter::new(encoder)),
+        })
+    }
+
+    // a full image of the market, publish_time and clk default to the market's own, bflw
+    // books have no clk so it's only written when given
+    #[args(publish_time = "None", clk = "None")]
+    fn write_market(
+        &mut self,
+        market: &PyAny,
+        publish_time: Option<u64>,
+        clk: Option<&str>,
+        py: Python,
+    ) -> PyResult<()> {
+        if let Ok(m) = market.extract::<PyRef<Market>>() {
+            let clk = clk.or_else(|| Some(m.clk.as_str()).filter(|c| !c.is_empty()));
+            self.write_line(clk, publish_time.unwrap_or(*m.publish_time), [m.image(py)])
+        } else if let Ok(m) = market.extract::<PyRef<MarketMut>>() {
+            let clk = clk.or_else(|| Some(m.clk.as_str()).filter(|c| !c.is_empty()));
+            self.write_line(clk, publish_time.unwrap_or(*m.publish_time), [m.image(py)])
+        } else if let Ok(m) = market.extract::<PyRef<MarketBook>>() {
+            let def = m.market_definition.borrow(py);
+            self.write_line(
+                clk,
+                publish_time.unwrap_or(*m.publish_time),
+                [m.image(&def, py)],
+            )
+        } else {
+            Err(PyTypeError::new_err(
+                "expected a Market, MarketMut or bflw MarketBook",
+            ))
+        }
+    }
+
+    // raw mc deltas, either a json string or objects json.dumps can encode, holding a single
+    // mc entry or a list of them
+    #[args(clk = "None")]
+    fn write_changes(
+        &mut self,
+        publish_time: u64,
+        mc: &PyAny,
+        clk: Option<&str>,
+        py: Python,
+    ) -> PyResult<()> {
+        let mc: String = match mc.downcast::<PyString>() {
+            Ok(s) => s.to_str()?.to_owned(),
+            Err(_) => py.import("json")?.call_method1("dumps", (mc,))?.extract()?,
+        };
+
+        let mc = match mc.trim_start().starts_with('{') {
+            true => format!("[{}]", mc),
+            false => mc,
+        };
+        let mc = RawValue::from_string(mc)
+            .map_err(|err| PyValueError::new_err(format!("invalid mc json: {}", err)))?;
+
+        self.write_line(clk, publish_time, mc)
+    }
+
+    // flushes and finishes the compressed stream, the file is incomplete until this is called
+    fn close(&mut self) -> PyResult<()> {
+        match self.out.take() {
+            Some(out) => out
+                .into_inner()
+                .map_err(IntoInnerError::into_error)
+                .and_then(Encoder::finish)
+                .map_err(io_err),
+            None => Ok(()),
+        }
+    }
+
+    fn __enter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __exit__(&mut self, _ty: &PyAny, _value: &PyAny, _traceback: &PyAny) -> PyResult<bool> {
+        self.close()?;
+        Ok(false)
+    }
+}